@@ -7,4 +7,4 @@ fn main() {
         execute: ExecuteMsg,
         query: QueryMsg,
     }
-}
\ No newline at end of file
+}
@@ -1,21 +1,62 @@
 use cosmwasm_std::{
-    entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    entry_point, to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response,
+    StdResult, Uint128,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
 
 use crate::error::ContractError;
 use crate::execute::{
-    execute_add_liquidity, execute_create_pool, execute_remove_liquidity, execute_swap,
-    execute_update_admin, execute_update_fee_rate, execute_update_lp_token_code_id,
+    assert_fee_tier, execute_accept_admin, execute_add_liquidity, execute_apply_fee_change,
+    execute_cancel_admin_proposal, execute_cancel_fee_change, execute_cancel_limit_order,
+    execute_collect_fees, execute_collect_protocol_fees, execute_concentrated_swap,
+    execute_create_concentrated_pool, execute_create_pool, execute_create_weighted_pool,
+    execute_exit_weighted_pool, execute_exit_weighted_pool_single, execute_join_weighted_pool,
+    execute_join_weighted_pool_single, execute_open_position, execute_pause,
+    execute_place_limit_order, execute_propose_new_admin, execute_ramp_amp, execute_receive,
+    execute_remove_liquidity, execute_renounce_admin, execute_stop_ramp_amp, execute_swap,
+    execute_swap_exact_out, execute_swap_operations, execute_unpause, execute_update_asset_list,
+    execute_update_asset_list_mode, execute_update_fee_rate, execute_update_fee_tiers,
+    execute_update_lp_token_code_id, execute_update_native_denoms, execute_update_pauser,
+    execute_update_pool_creation_fee, execute_update_pool_fee, execute_update_protocol_fee,
+    execute_weighted_swap, execute_withdraw_position, execute_zap_in, execute_zap_out,
+    reply_instantiate_lp_token, reply_instantiate_weighted_lp_token, validate_denom_prefixes,
+    validate_fee_change_delay, validate_fee_tiers, validate_max_fee_rate, validate_native_denoms,
+    validate_pool_creation_fee, validate_protocol_fee_share, INSTANTIATE_LP_TOKEN_REPLY_ID,
+    INSTANTIATE_WEIGHTED_LP_TOKEN_REPLY_ID,
 };
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::migrations::migrate_state;
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use crate::query::{
-    query_config, query_liquidity, query_pool, query_pools, query_simulation,
+    query_active_liquidity, query_admin_proposal, query_asset_list, query_asset_listing,
+    query_concentrated_pool, query_concentrated_pools, query_concentrated_simulation, query_config,
+    query_limit_order, query_limit_orders, query_limit_orders_by_owner, query_liquidity,
+    query_pending_fee_changes, query_pending_rewards, query_pool, query_pools, query_position,
+    query_positions, query_protocol_fees, query_reverse_simulation, query_reward_schedule,
+    query_reward_schedules, query_simulate_swap_operations, query_simulate_zap_out,
+    query_simulation, query_status, query_twap, query_weighted_pool, query_weighted_pools,
+    query_weighted_simulation,
 };
-use crate::state::{Config, CONFIG};
+use crate::staking::{
+    execute_bond, execute_claim, execute_create_reward_schedule, execute_reclaim_rewards,
+    execute_unbond,
+};
+use crate::state::{AssetListMode, Config, CONFIG};
+
+pub const CONTRACT_NAME: &str = "crates.io:dex-contract";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// Fee tiers in basis points used when InstantiateMsg doesn't set any
+pub const DEFAULT_FEE_TIERS: [u128; 4] = [1, 5, 30, 100];
+
+// Highest fee tier, in basis points, and fee change delay, in seconds, used when
+// InstantiateMsg doesn't set them
+pub const DEFAULT_MAX_FEE_RATE: u128 = 100;
+pub const DEFAULT_FEE_CHANGE_DELAY: u64 = 24 * 60 * 60;
 
-const CONTRACT_NAME: &str = "crates.io:dex-contract";
-const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// Native denoms and denom prefixes allowed when InstantiateMsg doesn't set any
+pub const DEFAULT_NATIVE_DENOMS: [&str; 1] = ["uatom"];
+pub const DEFAULT_NATIVE_DENOM_PREFIXES: [&str; 2] = ["ibc/", "factory/"];
 
 #[entry_point]
 pub fn instantiate(
@@ -32,11 +73,64 @@ pub fn instantiate(
         .transpose()?
         .unwrap_or(info.sender.clone());
 
+    let fee_tiers = msg.fee_tiers.unwrap_or_else(|| {
+        DEFAULT_FEE_TIERS
+            .iter()
+            .map(|tier| Uint128::from(*tier))
+            .collect()
+    });
+
+    let treasury = msg
+        .treasury
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?
+        .unwrap_or(admin.clone());
+
+    let native_denoms = msg.native_denoms.unwrap_or_else(|| {
+        DEFAULT_NATIVE_DENOMS
+            .iter()
+            .map(|denom| denom.to_string())
+            .collect()
+    });
+    let native_denom_prefixes = msg.native_denom_prefixes.unwrap_or_else(|| {
+        DEFAULT_NATIVE_DENOM_PREFIXES
+            .iter()
+            .map(|prefix| prefix.to_string())
+            .collect()
+    });
+
+    let pauser = msg
+        .pauser
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?
+        .unwrap_or(admin.clone());
+
+    let max_fee_rate = validate_max_fee_rate(
+        msg.max_fee_rate
+            .unwrap_or(Uint128::from(DEFAULT_MAX_FEE_RATE)),
+    )?;
+
     let config = Config {
-        admin,
+        admin: Some(admin),
         fee_rate: msg.fee_rate,
+        fee_tiers: validate_fee_tiers(fee_tiers, max_fee_rate)?,
         lp_token_code_id: msg.lp_token_code_id,
+        treasury,
+        protocol_fee_share: validate_protocol_fee_share(
+            msg.protocol_fee_share.unwrap_or_default(),
+        )?,
+        native_denoms: validate_native_denoms(native_denoms)?,
+        native_denom_prefixes: validate_denom_prefixes(native_denom_prefixes)?,
+        pauser,
+        max_fee_rate,
+        fee_change_delay: validate_fee_change_delay(
+            msg.fee_change_delay.unwrap_or(DEFAULT_FEE_CHANGE_DELAY),
+        )?,
+        pool_creation_fee: validate_pool_creation_fee(deps.api, msg.pool_creation_fee)?,
+        asset_list_mode: msg.asset_list_mode.unwrap_or(AssetListMode::DenyList {}),
+        restrict_swaps: msg.restrict_swaps.unwrap_or_default(),
     };
+    assert_fee_tier(&config, config.fee_rate)?;
 
     CONFIG.save(deps.storage, &config)?;
 
@@ -60,39 +154,355 @@ pub fn execute(
             token_b,
             initial_a,
             initial_b,
-        } => execute_create_pool(deps, env, info, token_a, token_b, initial_a, initial_b),
+            fee_rate,
+            pool_type,
+        } => execute_create_pool(
+            deps, env, info, None, token_a, token_b, initial_a, initial_b, fee_rate, pool_type,
+        ),
         ExecuteMsg::AddLiquidity {
             token_a,
             token_b,
             amount_a,
             amount_b,
             min_liquidity,
-        } => execute_add_liquidity(deps, env, info, token_a, token_b, amount_a, amount_b, min_liquidity),
+        } => execute_add_liquidity(
+            deps,
+            env,
+            info,
+            None,
+            token_a,
+            token_b,
+            amount_a,
+            amount_b,
+            min_liquidity,
+        ),
         ExecuteMsg::RemoveLiquidity {
             token_a,
             token_b,
             liquidity,
             min_a,
             min_b,
-        } => execute_remove_liquidity(deps, env, info, token_a, token_b, liquidity, min_a, min_b),
+        } => execute_remove_liquidity(
+            deps, env, info, None, token_a, token_b, liquidity, min_a, min_b,
+        ),
         ExecuteMsg::Swap {
             token_in,
             token_out,
             amount_in,
             min_amount_out,
-        } => execute_swap(deps, env, info, token_in, token_out, amount_in, min_amount_out),
-        ExecuteMsg::UpdateAdmin { admin } => execute_update_admin(deps, info, admin),
-        ExecuteMsg::UpdateFeeRate { fee_rate } => execute_update_fee_rate(deps, info, fee_rate),
-        ExecuteMsg::UpdateLpTokenCodeId { lp_token_code_id } => execute_update_lp_token_code_id(deps, info, lp_token_code_id),
+        } => execute_swap(
+            deps,
+            env,
+            info,
+            None,
+            token_in,
+            token_out,
+            amount_in,
+            min_amount_out,
+        ),
+        ExecuteMsg::SwapExactOut {
+            token_in,
+            token_out,
+            amount_out,
+            max_amount_in,
+        } => execute_swap_exact_out(
+            deps,
+            env,
+            info,
+            None,
+            token_in,
+            token_out,
+            amount_out,
+            max_amount_in,
+        ),
+        ExecuteMsg::CreateWeightedPool { assets, fee_rate } => {
+            execute_create_weighted_pool(deps, env, info, assets, fee_rate)
+        }
+        ExecuteMsg::JoinWeightedPool {
+            pool_id,
+            assets,
+            min_liquidity,
+        } => execute_join_weighted_pool(deps, env, info, pool_id, assets, min_liquidity),
+        ExecuteMsg::ZapIn {
+            pool,
+            asset,
+            amount,
+            min_liquidity,
+        } => execute_zap_in(deps, env, info, None, pool, asset, amount, min_liquidity),
+        ExecuteMsg::ZapOut {
+            pool,
+            liquidity,
+            asset,
+            min_out,
+        } => execute_zap_out(deps, env, info, pool, liquidity, asset, min_out),
+        ExecuteMsg::JoinWeightedPoolSingle {
+            pool_id,
+            token,
+            amount,
+            min_liquidity,
+        } => execute_join_weighted_pool_single(
+            deps,
+            env,
+            info,
+            None,
+            pool_id,
+            token,
+            amount,
+            min_liquidity,
+        ),
+        ExecuteMsg::ExitWeightedPool {
+            pool_id,
+            liquidity,
+            min_amounts,
+        } => execute_exit_weighted_pool(deps, info, pool_id, liquidity, min_amounts),
+        ExecuteMsg::ExitWeightedPoolSingle {
+            pool_id,
+            liquidity,
+            token_out,
+            min_amount_out,
+        } => execute_exit_weighted_pool_single(
+            deps,
+            info,
+            pool_id,
+            liquidity,
+            token_out,
+            min_amount_out,
+        ),
+        ExecuteMsg::WeightedSwap {
+            pool_id,
+            token_in,
+            token_out,
+            amount_in,
+            min_amount_out,
+        } => execute_weighted_swap(
+            deps,
+            env,
+            info,
+            None,
+            pool_id,
+            token_in,
+            token_out,
+            amount_in,
+            min_amount_out,
+        ),
+        ExecuteMsg::CreateConcentratedPool {
+            token_a,
+            token_b,
+            fee_rate,
+            initial_price,
+        } => {
+            execute_create_concentrated_pool(deps, info, token_a, token_b, fee_rate, initial_price)
+        }
+        ExecuteMsg::OpenPosition {
+            pool_id,
+            lower_tick,
+            upper_tick,
+            amount_a,
+            amount_b,
+            min_liquidity,
+        } => execute_open_position(
+            deps,
+            env,
+            info,
+            pool_id,
+            lower_tick,
+            upper_tick,
+            amount_a,
+            amount_b,
+            min_liquidity,
+        ),
+        ExecuteMsg::WithdrawPosition {
+            position_id,
+            liquidity,
+            min_a,
+            min_b,
+        } => execute_withdraw_position(deps, info, position_id, liquidity, min_a, min_b),
+        ExecuteMsg::CollectFees { position_id } => execute_collect_fees(deps, info, position_id),
+        ExecuteMsg::ConcentratedSwap {
+            pool_id,
+            token_in,
+            amount_in,
+            min_amount_out,
+        } => execute_concentrated_swap(
+            deps,
+            env,
+            info,
+            None,
+            pool_id,
+            token_in,
+            amount_in,
+            min_amount_out,
+        ),
+        ExecuteMsg::PlaceLimitOrder {
+            offer_token,
+            ask_token,
+            offer_amount,
+            price,
+        } => execute_place_limit_order(
+            deps,
+            env,
+            info,
+            None,
+            offer_token,
+            ask_token,
+            offer_amount,
+            price,
+        ),
+        ExecuteMsg::CancelLimitOrder { order_id } => {
+            execute_cancel_limit_order(deps, info, order_id)
+        }
+        ExecuteMsg::ProposeNewAdmin {
+            new_admin,
+            expires_in,
+        } => execute_propose_new_admin(deps, env, info, new_admin, expires_in),
+        ExecuteMsg::AcceptAdmin {} => execute_accept_admin(deps, env, info),
+        ExecuteMsg::CancelAdminProposal {} => execute_cancel_admin_proposal(deps, info),
+        ExecuteMsg::RenounceAdmin {} => execute_renounce_admin(deps, info),
+        ExecuteMsg::UpdateFeeRate { fee_rate } => {
+            execute_update_fee_rate(deps, env, info, fee_rate)
+        }
+        ExecuteMsg::UpdateFeeTiers { fee_tiers } => execute_update_fee_tiers(deps, info, fee_tiers),
+        ExecuteMsg::UpdatePoolFee {
+            token_a,
+            token_b,
+            fee_rate,
+        } => execute_update_pool_fee(deps, env, info, token_a, token_b, fee_rate),
+        ExecuteMsg::ApplyFeeChange { change_id } => execute_apply_fee_change(deps, env, change_id),
+        ExecuteMsg::CancelFeeChange { change_id } => {
+            execute_cancel_fee_change(deps, info, change_id)
+        }
+        ExecuteMsg::RampAmp {
+            token_a,
+            token_b,
+            future_amp,
+            future_time,
+        } => execute_ramp_amp(deps, env, info, token_a, token_b, future_amp, future_time),
+        ExecuteMsg::StopRampAmp { token_a, token_b } => {
+            execute_stop_ramp_amp(deps, env, info, token_a, token_b)
+        }
+        ExecuteMsg::UpdateProtocolFee {
+            protocol_fee_share,
+            treasury,
+        } => execute_update_protocol_fee(deps, env, info, protocol_fee_share, treasury),
+        ExecuteMsg::UpdateAssetList {
+            list,
+            delist,
+            remove,
+        } => execute_update_asset_list(deps, env, info, list, delist, remove),
+        ExecuteMsg::UpdateAssetListMode {
+            mode,
+            restrict_swaps,
+        } => execute_update_asset_list_mode(deps, info, mode, restrict_swaps),
+        ExecuteMsg::UpdatePoolCreationFee { pool_creation_fee } => {
+            execute_update_pool_creation_fee(deps, info, pool_creation_fee)
+        }
+        ExecuteMsg::UpdateNativeDenoms {
+            native_denoms,
+            native_denom_prefixes,
+        } => execute_update_native_denoms(deps, info, native_denoms, native_denom_prefixes),
+        ExecuteMsg::CollectProtocolFees { tokens } => {
+            execute_collect_protocol_fees(deps, info, tokens)
+        }
+        ExecuteMsg::Pause { pool, reason } => execute_pause(deps, env, info, pool, reason),
+        ExecuteMsg::Unpause { pool } => execute_unpause(deps, info, pool),
+        ExecuteMsg::UpdatePauser { pauser } => execute_update_pauser(deps, info, pauser),
+        ExecuteMsg::UpdateLpTokenCodeId { lp_token_code_id } => {
+            execute_update_lp_token_code_id(deps, info, lp_token_code_id)
+        }
+        ExecuteMsg::CreateRewardSchedule {
+            token_a,
+            token_b,
+            reward,
+            start_time,
+            end_time,
+            rate,
+        } => execute_create_reward_schedule(
+            deps, env, info, None, token_a, token_b, reward, start_time, end_time, rate,
+        ),
+        ExecuteMsg::Bond {
+            token_a,
+            token_b,
+            amount,
+        } => execute_bond(deps, env, info, None, token_a, token_b, amount),
+        ExecuteMsg::Unbond {
+            token_a,
+            token_b,
+            amount,
+        } => execute_unbond(deps, env, info, token_a, token_b, amount),
+        ExecuteMsg::Claim { token_a, token_b } => execute_claim(deps, env, info, token_a, token_b),
+        ExecuteMsg::ReclaimRewards { schedule_id } => {
+            execute_reclaim_rewards(deps, env, info, schedule_id)
+        }
+        ExecuteMsg::ExecuteSwapOperations {
+            operations,
+            amount_in,
+            minimum_receive,
+        } => execute_swap_operations(
+            deps,
+            env,
+            info,
+            None,
+            operations,
+            amount_in,
+            minimum_receive,
+        ),
+        ExecuteMsg::Receive(cw20_msg) => execute_receive(deps, env, info, cw20_msg),
     }
 }
 
 #[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidMigrationContract {
+            contract: stored.contract,
+        });
+    }
+
+    // Refuse to replace state written by a newer version
+    let stored_version: Version = stored.version.parse()?;
+    let current_version: Version = CONTRACT_VERSION.parse()?;
+    if stored_version > current_version {
+        return Err(ContractError::MigrationDowngrade {
+            stored: stored.version,
+            current: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    let migrations = migrate_state(deps.storage, deps.api, &env, &stored_version)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let mut response = Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION);
+    if !migrations.is_empty() {
+        response = response.add_attribute("migrations", migrations.join(","));
+    }
+
+    Ok(response)
+}
+
+#[entry_point]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        INSTANTIATE_LP_TOKEN_REPLY_ID => reply_instantiate_lp_token(deps, msg),
+        INSTANTIATE_WEIGHTED_LP_TOKEN_REPLY_ID => reply_instantiate_weighted_lp_token(deps, msg),
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+#[entry_point]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
-        QueryMsg::Pool { token_a, token_b } => to_json_binary(&query_pool(deps, token_a, token_b)?),
-        QueryMsg::Pools { start_after, limit } => to_json_binary(&query_pools(deps, start_after, limit)?),
+        QueryMsg::Pool { token_a, token_b } => {
+            to_json_binary(&query_pool(deps, env, token_a, token_b)?)
+        }
+        QueryMsg::Pools { start_after, limit } => {
+            to_json_binary(&query_pools(deps, env, start_after, limit)?)
+        }
         QueryMsg::Liquidity {
             user,
             token_a,
@@ -102,6 +512,113 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             token_in,
             token_out,
             amount_in,
-        } => to_json_binary(&query_simulation(deps, token_in, token_out, amount_in)?),
+        } => to_json_binary(&query_simulation(
+            deps, env, token_in, token_out, amount_in,
+        )?),
+        QueryMsg::ReverseSimulation {
+            token_in,
+            token_out,
+            amount_out,
+        } => to_json_binary(&query_reverse_simulation(
+            deps, env, token_in, token_out, amount_out,
+        )?),
+        QueryMsg::ProtocolFees { start_after, limit } => {
+            to_json_binary(&query_protocol_fees(deps, start_after, limit)?)
+        }
+        QueryMsg::SimulateSwapOperations {
+            amount_in,
+            operations,
+        } => to_json_binary(&query_simulate_swap_operations(
+            deps, env, amount_in, operations,
+        )?),
+        QueryMsg::Twap {
+            token_a,
+            token_b,
+            window_seconds,
+        } => to_json_binary(&query_twap(deps, env, token_a, token_b, window_seconds)?),
+        QueryMsg::WeightedPool { pool_id } => to_json_binary(&query_weighted_pool(deps, pool_id)?),
+        QueryMsg::WeightedPools { start_after, limit } => {
+            to_json_binary(&query_weighted_pools(deps, start_after, limit)?)
+        }
+        QueryMsg::RewardSchedules { token_a, token_b } => {
+            to_json_binary(&query_reward_schedules(deps, env, token_a, token_b)?)
+        }
+        QueryMsg::RewardSchedule { schedule_id } => {
+            to_json_binary(&query_reward_schedule(deps, env, schedule_id)?)
+        }
+        QueryMsg::PendingRewards {
+            user,
+            token_a,
+            token_b,
+        } => to_json_binary(&query_pending_rewards(deps, env, user, token_a, token_b)?),
+        QueryMsg::SimulateZapOut {
+            pool,
+            liquidity,
+            asset,
+        } => to_json_binary(&query_simulate_zap_out(deps, env, pool, liquidity, asset)?),
+        QueryMsg::WeightedSimulation {
+            pool_id,
+            token_in,
+            token_out,
+            amount_in,
+        } => to_json_binary(&query_weighted_simulation(
+            deps, pool_id, token_in, token_out, amount_in,
+        )?),
+        QueryMsg::ConcentratedPool { pool_id } => {
+            to_json_binary(&query_concentrated_pool(deps, pool_id)?)
+        }
+        QueryMsg::ConcentratedPools { start_after, limit } => {
+            to_json_binary(&query_concentrated_pools(deps, start_after, limit)?)
+        }
+        QueryMsg::ActiveLiquidity {
+            pool_id,
+            start_after,
+            limit,
+        } => to_json_binary(&query_active_liquidity(deps, pool_id, start_after, limit)?),
+        QueryMsg::Position { position_id } => to_json_binary(&query_position(deps, position_id)?),
+        QueryMsg::Positions {
+            owner,
+            start_after,
+            limit,
+        } => to_json_binary(&query_positions(deps, owner, start_after, limit)?),
+        QueryMsg::ConcentratedSimulation {
+            pool_id,
+            token_in,
+            amount_in,
+        } => to_json_binary(&query_concentrated_simulation(
+            deps, pool_id, token_in, amount_in,
+        )?),
+        QueryMsg::LimitOrder { order_id } => to_json_binary(&query_limit_order(deps, order_id)?),
+        QueryMsg::LimitOrders {
+            offer_token,
+            ask_token,
+            start_after,
+            limit,
+        } => to_json_binary(&query_limit_orders(
+            deps,
+            offer_token,
+            ask_token,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::LimitOrdersByOwner {
+            owner,
+            start_after,
+            limit,
+        } => to_json_binary(&query_limit_orders_by_owner(
+            deps,
+            owner,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::Status {} => to_json_binary(&query_status(deps)?),
+        QueryMsg::AdminProposal {} => to_json_binary(&query_admin_proposal(deps)?),
+        QueryMsg::AssetListing { asset } => to_json_binary(&query_asset_listing(deps, asset)?),
+        QueryMsg::AssetList { start_after, limit } => {
+            to_json_binary(&query_asset_list(deps, start_after, limit)?)
+        }
+        QueryMsg::PendingFeeChanges { start_after, limit } => {
+            to_json_binary(&query_pending_fee_changes(deps, start_after, limit)?)
+        }
     }
-}
\ No newline at end of file
+}
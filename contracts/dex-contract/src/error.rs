@@ -1,4 +1,5 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
+use cw_utils::ParseReplyError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,6 +7,12 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
+    #[error("{0}")]
+    Semver(#[from] semver::Error),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
@@ -18,6 +25,9 @@ pub enum ContractError {
     #[error("Pool already exists")]
     PoolAlreadyExists {},
 
+    #[error("Insufficient pool liquidity")]
+    InsufficientLiquidity {},
+
     #[error("Invalid pool ratio")]
     InvalidPoolRatio {},
 
@@ -27,9 +37,142 @@ pub enum ContractError {
     #[error("Minimum liquidity not met")]
     MinLiquidityNotMet {},
 
+    #[error("Initial liquidity must be greater than the locked minimum of {minimum}")]
+    InsufficientInitialLiquidity { minimum: Uint128 },
+
+    #[error("Initial liquidity does not fit in 128 bits")]
+    InitialLiquidityTooLarge {},
+
     #[error("Zero amount not allowed")]
     ZeroAmount {},
 
     #[error("Invalid token pair")]
     InvalidTokenPair {},
-}
\ No newline at end of file
+
+    #[error("Invalid asset {asset}")]
+    InvalidAsset { asset: String },
+
+    #[error("Native denom {denom} is not allowed")]
+    DenomNotAllowed { denom: String },
+
+    #[error("Expected {expected}{denom}, received {received}{denom}")]
+    FundsMismatch {
+        denom: String,
+        expected: Uint128,
+        received: Uint128,
+    },
+
+    #[error("Unexpected funds attached to the message")]
+    UnexpectedFunds {},
+
+    #[error("Fee rate {fee_rate} is not an allowed fee tier")]
+    InvalidFeeTier { fee_rate: Uint128 },
+
+    #[error("Fee rate {fee_rate} exceeds the maximum fee rate of {max_fee_rate} basis points")]
+    FeeRateTooHigh {
+        fee_rate: Uint128,
+        max_fee_rate: Uint128,
+    },
+
+    #[error("Maximum fee rate {max_fee_rate} must be below 10000 basis points")]
+    InvalidMaxFeeRate { max_fee_rate: Uint128 },
+
+    #[error("Fee change delay must be at least {min} seconds")]
+    InvalidFeeChangeDelay { min: u64 },
+
+    #[error("Fee tier {fee_rate} is used by queued fee change {change_id}; cancel it first")]
+    FeeTierPending { fee_rate: Uint128, change_id: u64 },
+
+    #[error("Fee change {change_id} not found")]
+    FeeChangeNotFound { change_id: u64 },
+
+    #[error("Fee change cannot be applied before {effective_at}")]
+    FeeChangeNotReady { effective_at: u64 },
+
+    #[error("The pool creation fee cannot be paid through a CW20 Send; use CreatePool with a CW20 allowance")]
+    PoolCreationFeeNotPayable {},
+
+    #[error("Protocol fee share {protocol_fee_share} exceeds 10000 basis points")]
+    InvalidProtocolFeeShare { protocol_fee_share: Uint128 },
+
+    #[error("Amp {amp} is outside the allowed range")]
+    InvalidAmp { amp: u64 },
+
+    #[error("Invalid amp ramp")]
+    InvalidAmpRamp {},
+
+    #[error("Pool is not a stable pool")]
+    NotStablePool {},
+
+    #[error("Weighted pools need 2 to 8 distinct assets, each weighing at least 1%")]
+    InvalidPoolAssets {},
+
+    #[error("Ticks must be multiples of the tick spacing with lower_tick below upper_tick, within the tick range")]
+    InvalidTickRange {},
+
+    #[error("Asset {asset} is delisted")]
+    AssetDelisted { asset: String },
+
+    #[error("Asset {asset} is not listed")]
+    AssetNotListed { asset: String },
+
+    #[error("Zaps are not supported for concentrated pools")]
+    ZapNotSupported {},
+
+    #[error("Reward schedules must start now or later and last at most {max_duration} seconds")]
+    InvalidRewardSchedule { max_duration: u64 },
+
+    #[error("A pool may have at most {max} reward schedules that have not ended")]
+    TooManyRewardSchedules { max: usize },
+
+    #[error("No rewards to claim")]
+    NoRewards {},
+
+    #[error("Reward schedule {schedule_id} not found")]
+    RewardScheduleNotFound { schedule_id: u64 },
+
+    #[error("Reward schedule has not ended; it ends at {end_time}")]
+    RewardScheduleActive { end_time: u64 },
+
+    #[error("Limit order price must be greater than zero")]
+    InvalidOrderPrice {},
+
+    #[error("Invalid swap operations")]
+    InvalidSwapOperations {},
+
+    #[error("Contract is paused: {reason}")]
+    Paused { reason: String },
+
+    #[error("Pool is paused: {reason}")]
+    PoolPaused { reason: String },
+
+    #[error("Not paused")]
+    NotPaused {},
+
+    #[error("Pause reason must be 1 to {max_len} characters")]
+    InvalidPauseReason { max_len: usize },
+
+    #[error("Admin proposals must expire within 1 to {max_seconds} seconds")]
+    InvalidProposalExpiry { max_seconds: u64 },
+
+    #[error("No pending admin proposal")]
+    NoAdminProposal {},
+
+    #[error("Admin proposal expired")]
+    AdminProposalExpired {},
+
+    #[error("Cannot migrate from contract {contract}")]
+    InvalidMigrationContract { contract: String },
+
+    #[error("Cannot migrate from version {stored} to older version {current}")]
+    MigrationDowngrade { stored: String, current: String },
+
+    #[error("Cannot migrate from unreleased version {stored}")]
+    UnsupportedMigrationSource { stored: String },
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Invalid LP token")]
+    InvalidLpToken {},
+}
@@ -1,20 +1,287 @@
 use cosmwasm_std::{
-    coin, BankMsg, CosmosMsg, DepsMut, Env, MessageInfo, Response, Uint128, WasmMsg, to_json_binary,
+    coin, from_json, to_json_binary, Addr, Api, BankMsg, CosmosMsg, Decimal256, DepsMut, Empty,
+    Env, Int128, Isqrt, MessageInfo, Order, Reply, Response, StdError, StdResult, Storage, SubMsg,
+    Uint128, Uint256, WasmMsg,
 };
-use cw20::Cw20ExecuteMsg;
+use cw20::{Cw20Coin, Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use cw20_base::msg::InstantiateMsg as Cw20InstantiateMsg;
+use cw_storage_plus::Bound;
+use cw_utils::parse_reply_instantiate_data;
 
 use crate::error::ContractError;
-use crate::state::{liquidity_key, pool_key, LiquidityPosition, Pool, CONFIG, LIQUIDITY, POOLS};
+use crate::math::{
+    add_liquidity_delta, compute_d, compute_weighted_invariant, fees_earned, liquidity_for_amounts,
+    sqrt_price_at_tick, tick_at_sqrt_price, ReverseSwapResult, SwapResult, FEE_DENOMINATOR,
+    MAX_TICK, MIN_TICK,
+};
+use crate::msg::{
+    Cw20HookMsg, PoolCreationFeeParams, SwapOperation, TokenAmount, WeightedAssetParams,
+};
+use crate::staking::execute_bond;
+use crate::state::{
+    liquidity_key, order_book_key, pool_key, pool_pause, AdminProposal, AmpRamp, AssetInfo,
+    AssetListMode, AssetListing, ConcentratedPool, ConcentratedPosition, Config, FeeChange,
+    LimitOrder, LiquidityPosition, ListingStatus, Observation, OrderFill, PauseInfo,
+    PendingFeeChange, Pool, PoolCreationFee, PoolId, PoolType, ProtocolFee, TickInfo,
+    WeightedAsset, WeightedPool, ADMIN_PROPOSAL, ASSET_LIST, CONCENTRATED_POOLS,
+    CONCENTRATED_POOL_COUNT, CONFIG, FEE_CHANGE_COUNT, LIQUIDITY, OBSERVATIONS, ORDERS, ORDER_BOOK,
+    ORDER_COUNT, OWNER_ORDERS, OWNER_POSITIONS, PAUSE, PAUSED_CONCENTRATED_POOLS, PAUSED_PAIRS,
+    PAUSED_WEIGHTED_POOLS, PENDING_FEE_CHANGES, PENDING_LP_TOKEN, PENDING_WEIGHTED_LP_TOKEN, POOLS,
+    POSITIONS, POSITION_COUNT, PROTOCOL_FEES, TICKS, WEIGHTED_POOLS, WEIGHTED_POOL_COUNT,
+};
+
+// Reply id for the LP token instantiation sub-message sent by CreatePool
+pub const INSTANTIATE_LP_TOKEN_REPLY_ID: u64 = 1;
+
+// Pool shares permanently locked when a pool is created
+pub const MINIMUM_LIQUIDITY: u128 = 1000;
+
+// Longest route accepted by ExecuteSwapOperations
+pub const MAX_SWAP_OPERATIONS: usize = 5;
+
+// Allowed amplification range for stable pools
+pub const MIN_AMP: u64 = 1;
+pub const MAX_AMP: u64 = 1_000_000;
+
+// Shortest amp ramp, in seconds, and the largest factor one ramp may change amp by
+pub const MIN_AMP_RAMP_TIME: u64 = 24 * 60 * 60;
+pub const MAX_AMP_CHANGE: u64 = 10;
+
+// Reply id for the LP token instantiation sub-message sent by CreateWeightedPool
+pub const INSTANTIATE_WEIGHTED_LP_TOKEN_REPLY_ID: u64 = 2;
+
+// Allowed number of assets in a weighted pool and the smallest normalized weight
+pub const MIN_WEIGHTED_ASSETS: usize = 2;
+pub const MAX_WEIGHTED_ASSETS: usize = 8;
+pub const MIN_WEIGHT_PERCENT: u64 = 1;
+
+// Tick spacing of a concentrated pool per basis point of its fee rate
+pub const TICK_SPACING_PER_FEE_BPS: u128 = 2;
+
+// Longest window, in seconds, the Twap query can be asked for
+pub const MAX_TWAP_WINDOW: u64 = 7 * 24 * 60 * 60;
+
+// Longest reason a pause may give
+pub const MAX_PAUSE_REASON_LEN: usize = 256;
+
+// Shortest fee change delay, in seconds, so queued changes can always be reacted to
+pub const MIN_FEE_CHANGE_DELAY: u64 = 60 * 60;
+
+// Longest time, in seconds, a proposed admin has to accept
+pub const MAX_ADMIN_PROPOSAL_DURATION: u64 = 30 * 24 * 60 * 60;
+
+// Handles CW20 tokens sent to the contract with `Cw20ExecuteMsg::Send`.
+// The sending contract is the token being provided and `amount` is already held by the DEX.
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let token = AssetInfo::Cw20 {
+        contract_addr: info.sender.clone(),
+    };
+    let amount = cw20_msg.amount;
+    let received = Cw20Coin {
+        address: info.sender.to_string(),
+        amount,
+    };
+
+    // Act on behalf of the account that sent the tokens
+    let user_info = MessageInfo {
+        sender: deps.api.addr_validate(&cw20_msg.sender)?,
+        funds: vec![],
+    };
+
+    match from_json(&cw20_msg.msg)? {
+        Cw20HookMsg::Swap {
+            token_out,
+            min_amount_out,
+        } => execute_swap(
+            deps,
+            env,
+            user_info,
+            Some(received),
+            token,
+            token_out,
+            amount,
+            min_amount_out,
+        ),
+        Cw20HookMsg::ProvideLiquidity {
+            token_b,
+            amount_b,
+            min_liquidity,
+        } => execute_add_liquidity(
+            deps,
+            env,
+            user_info,
+            Some(received),
+            token,
+            token_b,
+            amount,
+            amount_b,
+            min_liquidity,
+        ),
+        Cw20HookMsg::CreatePool {
+            token_b,
+            initial_b,
+            fee_rate,
+            pool_type,
+        } => {
+            if CONFIG.load(deps.storage)?.pool_creation_fee.is_some() {
+                return Err(ContractError::PoolCreationFeeNotPayable {});
+            }
+            execute_create_pool(
+                deps,
+                env,
+                user_info,
+                Some(received),
+                token,
+                token_b,
+                amount,
+                initial_b,
+                fee_rate,
+                pool_type,
+            )
+        }
+        Cw20HookMsg::WithdrawLiquidity {
+            token_a,
+            token_b,
+            min_a,
+            min_b,
+        } => execute_remove_liquidity(
+            deps,
+            env,
+            user_info,
+            Some(received),
+            token_a,
+            token_b,
+            amount,
+            min_a,
+            min_b,
+        ),
+        Cw20HookMsg::SwapExactOut {
+            token_out,
+            amount_out,
+        } => execute_swap_exact_out(
+            deps,
+            env,
+            user_info,
+            Some(received),
+            token,
+            token_out,
+            amount_out,
+            amount,
+        ),
+        Cw20HookMsg::ExecuteSwapOperations {
+            operations,
+            minimum_receive,
+        } => {
+            // The route must start with the token that was sent
+            if operations.first().map(|op| &op.token_in) != Some(&token) {
+                return Err(ContractError::InvalidSwapOperations {});
+            }
+            execute_swap_operations(
+                deps,
+                env,
+                user_info,
+                Some(received),
+                operations,
+                amount,
+                minimum_receive,
+            )
+        }
+        Cw20HookMsg::WeightedSwap {
+            pool_id,
+            token_out,
+            min_amount_out,
+        } => execute_weighted_swap(
+            deps,
+            env,
+            user_info,
+            Some(received),
+            pool_id,
+            token,
+            token_out,
+            amount,
+            min_amount_out,
+        ),
+        Cw20HookMsg::JoinWeightedPoolSingle {
+            pool_id,
+            min_liquidity,
+        } => execute_join_weighted_pool_single(
+            deps,
+            env,
+            user_info,
+            Some(received),
+            pool_id,
+            token,
+            amount,
+            min_liquidity,
+        ),
+        Cw20HookMsg::ConcentratedSwap {
+            pool_id,
+            min_amount_out,
+        } => execute_concentrated_swap(
+            deps,
+            env,
+            user_info,
+            Some(received),
+            pool_id,
+            token,
+            amount,
+            min_amount_out,
+        ),
+        Cw20HookMsg::ZapIn {
+            pool,
+            min_liquidity,
+        } => execute_zap_in(
+            deps,
+            env,
+            user_info,
+            Some(received),
+            pool,
+            token,
+            amount,
+            min_liquidity,
+        ),
+        Cw20HookMsg::Bond { token_a, token_b } => execute_bond(
+            deps,
+            env,
+            user_info,
+            Some(received),
+            token_a,
+            token_b,
+            amount,
+        ),
+        Cw20HookMsg::PlaceLimitOrder { ask_token, price } => execute_place_limit_order(
+            deps,
+            env,
+            user_info,
+            Some(received),
+            token,
+            ask_token,
+            amount,
+            price,
+        ),
+    }
+}
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_create_pool(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    token_a: String,
-    token_b: String,
+    received: Option<Cw20Coin>,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
     initial_a: Uint128,
     initial_b: Uint128,
+    fee_rate: Option<Uint128>,
+    pool_type: Option<PoolType>,
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, None)?;
+
     if initial_a.is_zero() || initial_b.is_zero() {
         return Err(ContractError::ZeroAmount {});
     }
@@ -23,378 +290,3047 @@ pub fn execute_create_pool(
         return Err(ContractError::InvalidTokenPair {});
     }
 
+    let config = CONFIG.load(deps.storage)?;
+    validate_asset(deps.api, deps.storage, &config, &token_a)?;
+    validate_asset(deps.api, deps.storage, &config, &token_b)?;
+
+    let fee_rate = fee_rate.unwrap_or(config.fee_rate);
+    assert_fee_tier(&config, fee_rate)?;
+
+    let pool_type = pool_type.unwrap_or(PoolType::ConstantProduct {});
+    if let PoolType::Stable { amp } = pool_type {
+        assert_amp(amp)?;
+    }
+
     let key = pool_key(&token_a, &token_b);
-    
+
     if POOLS.has(deps.storage, key.clone()) {
         return Err(ContractError::PoolAlreadyExists {});
     }
 
-    // Calculate initial liquidity (geometric mean) - using integer square root approximation
-    let product = initial_a * initial_b;
-    let mut initial_liquidity = Uint128::zero();
-    if !product.is_zero() {
-        // Simple integer square root approximation
-        let mut x = product;
-        let mut y = (x + Uint128::from(1u128)) / Uint128::from(2u128);
-        while y < x {
-            x = y;
-            y = (x + product / x) / Uint128::from(2u128);
-        }
-        initial_liquidity = x;
-    }
-    
-    if initial_liquidity.is_zero() {
-        return Err(ContractError::MinLiquidityNotMet {});
+    let initial_liquidity = match pool_type {
+        // Initial liquidity is the geometric mean of the reserves, with the product taken
+        // in 256 bits so large reserves cannot overflow
+        PoolType::ConstantProduct {} => Uint128::try_from(initial_a.full_mul(initial_b).isqrt())
+            .map_err(|_| ContractError::InitialLiquidityTooLarge {})?,
+        // Stable pools start with one share per unit of the invariant, as in Curve
+        PoolType::Stable { amp } => compute_d(amp, initial_a, initial_b)?,
+    };
+
+    // MINIMUM_LIQUIDITY shares are locked forever so total_liquidity can never be drained
+    // back to a tiny amount that makes share rounding exploitable
+    if initial_liquidity <= Uint128::from(MINIMUM_LIQUIDITY) {
+        return Err(ContractError::InsufficientInitialLiquidity {
+            minimum: Uint128::from(MINIMUM_LIQUIDITY),
+        });
     }
 
+    // Tokens and initial reserves in pool order
+    let (pool_a, pool_b, reserve_a, reserve_b) = if token_a.to_string() == key.0 {
+        (token_a.clone(), token_b.clone(), initial_a, initial_b)
+    } else {
+        (token_b.clone(), token_a.clone(), initial_b, initial_a)
+    };
+
     let pool = Pool {
-        token_a: key.0.clone(),
-        token_b: key.1.clone(),
-        reserve_a: initial_a,
-        reserve_b: initial_b,
+        token_a: pool_a,
+        token_b: pool_b,
+        reserve_a,
+        reserve_b,
         total_liquidity: initial_liquidity,
-        lp_token_address: None, // Will be set when we implement CW20 LP tokens
+        lp_token_address: None, // Set by the reply once the LP token is instantiated
+        fee_rate,
+        price_a_cumulative: Uint256::zero(),
+        price_b_cumulative: Uint256::zero(),
+        block_time_last: _env.block.time.seconds(),
+        pool_type: pool_type.clone(),
+        amp_ramp: None,
     };
 
-    POOLS.save(deps.storage, key.clone(), &pool)?;
+    save_pool(deps.storage, key.clone(), &pool)?;
+    PENDING_LP_TOKEN.save(deps.storage, &key)?;
 
-    // Mint liquidity tokens to creator
-    let liquidity_key = liquidity_key(&info.sender, &key.0, &key.1);
-    let liquidity_position = LiquidityPosition {
-        liquidity: initial_liquidity,
-    };
-    LIQUIDITY.save(deps.storage, liquidity_key, &liquidity_position)?;
+    let lp_token_msg = instantiate_lp_token_msg(
+        &_env,
+        config.lp_token_code_id,
+        &info.sender,
+        initial_liquidity,
+        format!("dex lp token {}-{}", key.0, key.1),
+        INSTANTIATE_LP_TOKEN_REPLY_ID,
+    )?;
+
+    // Transfer tokens from user to contract: native tokens, and the creation fee, must
+    // be attached to the transaction, CW20 tokens are received through the hook or pulled
+    let creation_fee = pool_creation_fee_payment(&config);
+    let mut payments = vec![(&token_a, initial_a), (&token_b, initial_b)];
+    payments.extend(creation_fee.iter().map(|(asset, amount)| (asset, *amount)));
+    let mut messages = collect_funds(&_env, &info, &received, &payments)?;
+    messages.extend(config.pool_creation_fee.as_ref().map(pool_creation_fee_msg));
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessage(lp_token_msg)
+        .add_attribute("method", "create_pool")
+        .add_attribute("token_a", token_a.to_string())
+        .add_attribute("token_b", token_b.to_string())
+        .add_attribute("initial_a", initial_a)
+        .add_attribute("initial_b", initial_b)
+        .add_attribute("fee_rate", fee_rate)
+        .add_attribute("pool_type", pool_type_name(&pool_type))
+        .add_attribute("liquidity", initial_liquidity)
+        .add_attribute("locked_liquidity", Uint128::from(MINIMUM_LIQUIDITY)))
+}
+
+// Instantiates a CW20 LP token, minting the initial liquidity to the creator and the
+// locked MINIMUM_LIQUIDITY to the DEX itself, which never burns it
+fn instantiate_lp_token_msg(
+    env: &Env,
+    code_id: u64,
+    creator: &Addr,
+    initial_liquidity: Uint128,
+    label: String,
+    reply_id: u64,
+) -> StdResult<SubMsg> {
+    Ok(SubMsg::reply_on_success(
+        WasmMsg::Instantiate {
+            admin: Some(env.contract.address.to_string()),
+            code_id,
+            msg: to_json_binary(&Cw20InstantiateMsg {
+                name: "Cosmos DEX LP Token".to_string(),
+                symbol: "DEXLP".to_string(),
+                decimals: 6,
+                initial_balances: vec![
+                    Cw20Coin {
+                        address: creator.to_string(),
+                        amount: initial_liquidity - Uint128::from(MINIMUM_LIQUIDITY),
+                    },
+                    Cw20Coin {
+                        address: env.contract.address.to_string(),
+                        amount: Uint128::from(MINIMUM_LIQUIDITY),
+                    },
+                ],
+                mint: Some(MinterResponse {
+                    minter: env.contract.address.to_string(),
+                    cap: None,
+                }),
+                marketing: None,
+            })?,
+            funds: vec![],
+            label,
+        },
+        reply_id,
+    ))
+}
+
+fn mint_lp_msg(lp_token: &str, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: lp_token.to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::Mint {
+            recipient: recipient.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    }))
+}
+
+fn burn_lp_msg(lp_token: &str, owner: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: lp_token.to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::BurnFrom {
+            owner: owner.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    }))
+}
+
+// Checks that a CW20 asset has a valid address and a native one a well-formed denom
+// allowed by the config, listed or under one of the allowed prefixes, and that the
+// asset list lets the asset into new pools
+fn validate_asset(
+    api: &dyn Api,
+    storage: &dyn Storage,
+    config: &Config,
+    asset: &AssetInfo,
+) -> Result<(), ContractError> {
+    match asset {
+        AssetInfo::Native { denom } => {
+            if !is_valid_denom(denom) {
+                return Err(ContractError::InvalidAsset {
+                    asset: denom.clone(),
+                });
+            }
+            let allowed = config.native_denoms.contains(denom)
+                || config
+                    .native_denom_prefixes
+                    .iter()
+                    .any(|prefix| denom.starts_with(prefix.as_str()));
+            if !allowed {
+                return Err(ContractError::DenomNotAllowed {
+                    denom: denom.clone(),
+                });
+            }
+        }
+        AssetInfo::Cw20 { contract_addr } => {
+            api.addr_validate(contract_addr.as_str())?;
+        }
+    }
+    assert_asset_listed(storage, config, asset)
+}
+
+// Fails for delisted assets, and for unlisted ones in allow-list mode
+fn assert_asset_listed(
+    storage: &dyn Storage,
+    config: &Config,
+    asset: &AssetInfo,
+) -> Result<(), ContractError> {
+    let status = ASSET_LIST
+        .may_load(storage, asset.to_string())?
+        .map(|listing| listing.status);
 
-    // Validate token addresses before processing
-    if !token_a.starts_with("ibc/") && token_a != "uatom" {
-        // For CW20 tokens, validate the address format
-        deps.api.addr_validate(&token_a)?;
+    match (&config.asset_list_mode, status) {
+        (AssetListMode::Permissionless {}, _) => Ok(()),
+        (_, Some(ListingStatus::Delisted)) => Err(ContractError::AssetDelisted {
+            asset: asset.to_string(),
+        }),
+        (AssetListMode::AllowList {}, None) => Err(ContractError::AssetNotListed {
+            asset: asset.to_string(),
+        }),
+        _ => Ok(()),
     }
-    if !token_b.starts_with("ibc/") && token_b != "uatom" {
-        // For CW20 tokens, validate the address format  
-        deps.api.addr_validate(&token_b)?;
+}
+
+// Applies the asset list to swaps when Config.restrict_swaps is set
+fn assert_tradable(storage: &dyn Storage, assets: &[&AssetInfo]) -> Result<(), ContractError> {
+    let config = CONFIG.load(storage)?;
+
+    if config.restrict_swaps {
+        for asset in assets {
+            assert_asset_listed(storage, &config, asset)?;
+        }
     }
 
-    // Transfer tokens from user to contract
+    Ok(())
+}
+
+// Takes custody of `assets` from the sender: native coins must be attached in the exact
+// amounts and CW20s are received through the hook or pulled with TransferFrom.
+pub fn collect_funds(
+    env: &Env,
+    info: &MessageInfo,
+    received: &Option<Cw20Coin>,
+    assets: &[(&AssetInfo, Uint128)],
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    assert_native_payments(info, assets)?;
+
     let mut messages = vec![];
-    
-    // Handle native tokens (ATOM and IBC tokens) and CW20 tokens
-    if token_a == "uatom" || token_a.starts_with("ibc/") {
-        // Native token (including IBC tokens) - verify it was sent with the transaction
-        let sent_amount = info.funds.iter()
-            .find(|coin| coin.denom == token_a)
-            .map(|coin| coin.amount)
-            .unwrap_or_else(Uint128::zero);
-        
-        if sent_amount < initial_a {
-            return Err(ContractError::InsufficientFunds {});
+    for (asset, amount) in assets {
+        if let AssetInfo::Cw20 { contract_addr } = asset {
+            messages.extend(collect_cw20(env, info, received, contract_addr, *amount)?);
         }
-    } else {
-        // CW20 token transfer
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: token_a.clone(),
-            msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
-                owner: info.sender.to_string(),
-                recipient: _env.contract.address.to_string(),
-                amount: initial_a,
-            })?,
-            funds: vec![],
-        }));
     }
 
-    if token_b == "uatom" || token_b.starts_with("ibc/") {
-        // Native token (including IBC tokens) - verify it was sent with the transaction
-        let sent_amount = info.funds.iter()
-            .find(|coin| coin.denom == token_b)
+    Ok(messages)
+}
+
+// Pulls `amount` of a CW20 token from the sender with TransferFrom, unless the
+// token was already received through the Receive hook.
+fn collect_cw20(
+    env: &Env,
+    info: &MessageInfo,
+    received: &Option<Cw20Coin>,
+    token: &Addr,
+    amount: Uint128,
+) -> Result<Option<CosmosMsg>, ContractError> {
+    if let Some(received) = received {
+        if received.address == token.as_str() {
+            if received.amount != amount {
+                return Err(ContractError::FundsMismatch {
+                    denom: token.to_string(),
+                    expected: amount,
+                    received: received.amount,
+                });
+            }
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
+            owner: info.sender.to_string(),
+            recipient: env.contract.address.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    })))
+}
+
+// Takes `used` out of the `offered` amount of `token`. Native coins and CW20s received
+// through the hook arrive in full and the rest is refunded; other CW20s are pulled
+// with TransferFrom for the used amount only. Native funds are checked by the caller.
+fn collect_and_refund(
+    env: &Env,
+    info: &MessageInfo,
+    received: &Option<Cw20Coin>,
+    token: &AssetInfo,
+    offered: Uint128,
+    used: Uint128,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let mut messages = vec![];
+    let excess = offered - used;
+
+    match token {
+        AssetInfo::Native { .. } => {
+            if !excess.is_zero() {
+                messages.push(transfer_msg(token, &info.sender, excess)?);
+            }
+        }
+        AssetInfo::Cw20 { contract_addr }
+            if received
+                .as_ref()
+                .is_some_and(|r| r.address == contract_addr.as_str()) =>
+        {
+            collect_cw20(env, info, received, contract_addr, offered)?;
+            if !excess.is_zero() {
+                messages.push(transfer_msg(token, &info.sender, excess)?);
+            }
+        }
+        AssetInfo::Cw20 { contract_addr } => {
+            if !used.is_zero() {
+                messages.extend(collect_cw20(env, info, received, contract_addr, used)?);
+            }
+        }
+    }
+
+    Ok(messages)
+}
+
+// Checks that exactly the expected amounts of the native tokens among `assets` were
+// attached and nothing else. Amounts of the same denom add up; CW20 entries are ignored.
+fn assert_native_payments(
+    info: &MessageInfo,
+    assets: &[(&AssetInfo, Uint128)],
+) -> Result<(), ContractError> {
+    let mut expected: Vec<(&String, Uint128)> = vec![];
+    for (token, amount) in assets {
+        if let AssetInfo::Native { denom } = token {
+            match expected
+                .iter_mut()
+                .find(|(expected_denom, _)| *expected_denom == denom)
+            {
+                Some((_, total)) => *total += *amount,
+                None => expected.push((denom, *amount)),
+            }
+        }
+    }
+
+    if info
+        .funds
+        .iter()
+        .any(|coin| !expected.iter().any(|(denom, _)| **denom == coin.denom))
+    {
+        return Err(ContractError::UnexpectedFunds {});
+    }
+
+    for (denom, amount) in expected {
+        let received = info
+            .funds
+            .iter()
+            .filter(|coin| coin.denom == *denom)
             .map(|coin| coin.amount)
-            .unwrap_or_else(Uint128::zero);
-        
-        if sent_amount < initial_b {
-            return Err(ContractError::InsufficientFunds {});
+            .sum::<Uint128>();
+
+        if received != amount {
+            return Err(ContractError::FundsMismatch {
+                denom: denom.clone(),
+                expected: amount,
+                received,
+            });
         }
-    } else {
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: token_b.clone(),
-            msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
-                owner: info.sender.to_string(),
-                recipient: _env.contract.address.to_string(),
-                amount: initial_b,
+    }
+
+    Ok(())
+}
+
+// The pool creation fee as a native payment, if one is charged
+fn pool_creation_fee_payment(config: &Config) -> Option<(AssetInfo, Uint128)> {
+    config.pool_creation_fee.as_ref().map(|creation_fee| {
+        let asset = AssetInfo::Native {
+            denom: creation_fee.fee.denom.clone(),
+        };
+        (asset, creation_fee.fee.amount)
+    })
+}
+
+// Sends a collected pool creation fee to the fee collector, or burns it if there is none
+fn pool_creation_fee_msg(creation_fee: &PoolCreationFee) -> CosmosMsg {
+    match &creation_fee.collector {
+        Some(collector) => CosmosMsg::Bank(BankMsg::Send {
+            to_address: collector.to_string(),
+            amount: vec![creation_fee.fee.clone()],
+        }),
+        None => CosmosMsg::Bank(BankMsg::Burn {
+            amount: vec![creation_fee.fee.clone()],
+        }),
+    }
+}
+
+// A non-zero fee in a well-formed bank denom, with a valid collector if one is given
+pub fn validate_pool_creation_fee(
+    api: &dyn Api,
+    params: Option<PoolCreationFeeParams>,
+) -> Result<Option<PoolCreationFee>, ContractError> {
+    params
+        .map(|params| {
+            if !is_valid_denom(&params.fee.denom) {
+                return Err(ContractError::InvalidAsset {
+                    asset: params.fee.denom,
+                });
+            }
+            if params.fee.amount.is_zero() {
+                return Err(ContractError::ZeroAmount {});
+            }
+            Ok(PoolCreationFee {
+                fee: params.fee,
+                collector: params
+                    .collector
+                    .map(|addr| api.addr_validate(&addr))
+                    .transpose()?,
+            })
+        })
+        .transpose()
+}
+
+// Builds the message sending `amount` of a native or CW20 token to `recipient`.
+pub fn transfer_msg(
+    token: &AssetInfo,
+    recipient: &Addr,
+    amount: Uint128,
+) -> Result<CosmosMsg, ContractError> {
+    match token {
+        AssetInfo::Native { denom } => Ok(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![coin(amount.u128(), denom)],
+        })),
+        AssetInfo::Cw20 { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
             })?,
             funds: vec![],
-        }));
+        })),
     }
+}
+
+pub fn reply_instantiate_lp_token(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let res = parse_reply_instantiate_data(msg)?;
+    let lp_token = deps.api.addr_validate(&res.contract_address)?;
+
+    let key = PENDING_LP_TOKEN.load(deps.storage)?;
+    PENDING_LP_TOKEN.remove(deps.storage);
+
+    let mut pool = POOLS.load(deps.storage, key.clone())?;
+    pool.lp_token_address = Some(lp_token.to_string());
+    POOLS.save(deps.storage, key, &pool)?;
 
     Ok(Response::new()
-        .add_messages(messages)
-        .add_attribute("method", "create_pool")
-        .add_attribute("token_a", token_a)
-        .add_attribute("token_b", token_b)
-        .add_attribute("initial_a", initial_a)
-        .add_attribute("initial_b", initial_b)
-        .add_attribute("liquidity", initial_liquidity))
+        .add_attribute("method", "instantiate_lp_token")
+        .add_attribute("lp_token_address", lp_token))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_add_liquidity(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    token_a: String,
-    token_b: String,
+    received: Option<Cw20Coin>,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
     amount_a: Uint128,
     amount_b: Uint128,
     min_liquidity: Uint128,
 ) -> Result<Response, ContractError> {
+    assert_not_paused(
+        deps.storage,
+        Some(&PoolId::Pair {
+            token_a: token_a.clone(),
+            token_b: token_b.clone(),
+        }),
+    )?;
+
+    if amount_a.is_zero() || amount_b.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+
     let key = pool_key(&token_a, &token_b);
     let mut pool = POOLS.load(deps.storage, key.clone())?;
+    pool.accumulate_prices(_env.block.time.seconds())?;
 
-    // Calculate liquidity to mint
-    let liquidity_a = amount_a * pool.total_liquidity / pool.reserve_a;
-    let liquidity_b = amount_b * pool.total_liquidity / pool.reserve_b;
-    let liquidity = liquidity_a.min(liquidity_b);
+    // Offered amounts in pool order
+    let (offer_a, offer_b) = if token_a == pool.token_a {
+        (amount_a, amount_b)
+    } else {
+        (amount_b, amount_a)
+    };
 
-    if liquidity < min_liquidity {
+    // Amounts the pool takes from the offer and the liquidity they mint
+    let (deposit_a, deposit_b, liquidity) =
+        pool.deposit(offer_a, offer_b, _env.block.time.seconds())?;
+
+    if liquidity.is_zero() || liquidity < min_liquidity {
         return Err(ContractError::MinLiquidityNotMet {});
     }
 
     // Update pool reserves
-    pool.reserve_a += amount_a;
-    pool.reserve_b += amount_b;
+    pool.reserve_a += deposit_a;
+    pool.reserve_b += deposit_b;
     pool.total_liquidity += liquidity;
 
-    POOLS.save(deps.storage, key.clone(), &pool)?;
+    save_pool(deps.storage, key.clone(), &pool)?;
+
+    // Collect the deposits and refund what the pool ratio did not use
+    assert_native_payments(&info, &[(&pool.token_a, offer_a), (&pool.token_b, offer_b)])?;
+
+    let mut messages = vec![];
+
+    for (token, offered, deposited) in [
+        (&pool.token_a, offer_a, deposit_a),
+        (&pool.token_b, offer_b, deposit_b),
+    ] {
+        messages.extend(collect_and_refund(
+            &_env, &info, &received, token, offered, deposited,
+        )?);
+    }
+
+    if let Some(lp_token) = &pool.lp_token_address {
+        // Mint LP tokens to the provider
+        messages.push(mint_lp_msg(lp_token, &info.sender, liquidity)?);
+    } else {
+        // Pool without an LP token: track the position in contract storage
+        let liq_key = liquidity_key(&info.sender, &pool.token_a, &pool.token_b);
+        let mut position = LIQUIDITY
+            .may_load(deps.storage, liq_key.clone())?
+            .unwrap_or(LiquidityPosition {
+                liquidity: Uint128::zero(),
+            });
 
-    // Update user's liquidity position
-    let liq_key = liquidity_key(&info.sender, &key.0, &key.1);
-    let mut position = LIQUIDITY.may_load(deps.storage, liq_key.clone())?
-        .unwrap_or(LiquidityPosition { liquidity: Uint128::zero() });
-    
-    position.liquidity += liquidity;
-    LIQUIDITY.save(deps.storage, liq_key, &position)?;
+        position.liquidity += liquidity;
+        LIQUIDITY.save(deps.storage, liq_key, &position)?;
+    }
 
     Ok(Response::new()
+        .add_messages(messages)
         .add_attribute("method", "add_liquidity")
+        .add_attribute("deposit_a", deposit_a)
+        .add_attribute("deposit_b", deposit_b)
+        .add_attribute("refund_a", offer_a - deposit_a)
+        .add_attribute("refund_b", offer_b - deposit_b)
         .add_attribute("liquidity", liquidity))
 }
 
+// Burns liquidity for its share of the reserves. LP tokens are either sent in with the
+// WithdrawLiquidity hook and burned from the DEX's balance, or burned from the sender
+// with BurnFrom, which needs an allowance for the DEX.
+#[allow(clippy::too_many_arguments)]
 pub fn execute_remove_liquidity(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    token_a: String,
-    token_b: String,
+    received: Option<Cw20Coin>,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
     liquidity: Uint128,
     min_a: Uint128,
     min_b: Uint128,
 ) -> Result<Response, ContractError> {
     let key = pool_key(&token_a, &token_b);
     let mut pool = POOLS.load(deps.storage, key.clone())?;
+    pool.accumulate_prices(_env.block.time.seconds())?;
 
-    // Check user's liquidity position
-    let liq_key = liquidity_key(&info.sender, &key.0, &key.1);
-    let mut position = LIQUIDITY.load(deps.storage, liq_key.clone())?;
+    let mut messages = vec![];
 
-    if position.liquidity < liquidity {
-        return Err(ContractError::InsufficientFunds {});
+    if let Some(lp_token) = &pool.lp_token_address {
+        match &received {
+            // LP tokens already received by the hook
+            Some(received) if received.address == *lp_token => {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: lp_token.clone(),
+                    msg: to_json_binary(&Cw20ExecuteMsg::Burn { amount: liquidity })?,
+                    funds: vec![],
+                }));
+            }
+            Some(_) => return Err(ContractError::InvalidLpToken {}),
+            // Burn the LP tokens; fails unless the user holds and has approved them
+            None => messages.push(burn_lp_msg(lp_token, &info.sender, liquidity)?),
+        }
+    } else {
+        if received.is_some() {
+            return Err(ContractError::InvalidLpToken {});
+        }
+
+        // Check user's liquidity position
+        let liq_key = liquidity_key(&info.sender, &pool.token_a, &pool.token_b);
+        let mut position = LIQUIDITY.load(deps.storage, liq_key.clone())?;
+
+        if position.liquidity < liquidity {
+            return Err(ContractError::InsufficientFunds {});
+        }
+
+        position.liquidity -= liquidity;
+        LIQUIDITY.save(deps.storage, liq_key, &position)?;
     }
 
-    // Calculate amounts to return
-    let amount_a = liquidity * pool.reserve_a / pool.total_liquidity;
-    let amount_b = liquidity * pool.reserve_b / pool.total_liquidity;
+    // Calculate amounts to return, in the caller's token order
+    let (share_a, share_b) = pool.withdraw(liquidity);
+    let (amount_a, amount_b) = if token_a == pool.token_a {
+        (share_a, share_b)
+    } else {
+        (share_b, share_a)
+    };
 
     if amount_a < min_a || amount_b < min_b {
         return Err(ContractError::SlippageExceeded {});
     }
 
-    // Update pool and position
-    pool.reserve_a -= amount_a;
-    pool.reserve_b -= amount_b;
+    // Update pool
+    pool.reserve_a -= share_a;
+    pool.reserve_b -= share_b;
     pool.total_liquidity -= liquidity;
-    position.liquidity -= liquidity;
 
-    POOLS.save(deps.storage, key.clone(), &pool)?;
-    LIQUIDITY.save(deps.storage, liq_key, &position)?;
+    save_pool(deps.storage, key, &pool)?;
 
     // Send tokens back to user
+    messages.push(transfer_msg(&token_a, &info.sender, amount_a)?);
+    messages.push(transfer_msg(&token_b, &info.sender, amount_b)?);
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "remove_liquidity")
+        .add_attribute("amount_a", amount_a)
+        .add_attribute("amount_b", amount_b))
+}
+
+// Burns liquidity and pays its whole value out in `asset`. In a pair pool the share of
+// the other asset is swapped against the pool after the withdrawal; weighted pools exit
+// into one asset like ExitWeightedPoolSingle.
+pub fn execute_zap_out(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pool: PoolId,
+    liquidity: Uint128,
+    asset: AssetInfo,
+    min_out: Uint128,
+) -> Result<Response, ContractError> {
+    let (token_a, token_b) = match pool {
+        PoolId::Pair { token_a, token_b } => (token_a, token_b),
+        PoolId::Weighted { pool_id } => {
+            return execute_exit_weighted_pool_single(
+                deps, info, pool_id, liquidity, asset, min_out,
+            );
+        }
+        PoolId::Concentrated { .. } => return Err(ContractError::ZapNotSupported {}),
+    };
+
+    assert_not_paused(
+        deps.storage,
+        Some(&PoolId::Pair {
+            token_a: token_a.clone(),
+            token_b: token_b.clone(),
+        }),
+    )?;
+    assert_tradable(deps.storage, &[&token_a, &token_b])?;
+
+    if liquidity.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+    if asset != token_a && asset != token_b {
+        return Err(ContractError::InvalidAsset {
+            asset: asset.to_string(),
+        });
+    }
+
+    let key = pool_key(&token_a, &token_b);
+    let mut pool = POOLS
+        .may_load(deps.storage, key.clone())?
+        .ok_or(ContractError::PoolNotFound {})?;
+    let now = env.block.time.seconds();
+    pool.accumulate_prices(now)?;
+
     let mut messages = vec![];
-    
-    if token_a == "uatom" || token_a.starts_with("ibc/") {
-        // Native token (including IBC tokens)
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: vec![coin(amount_a.u128(), &token_a)],
-        }));
+
+    if let Some(lp_token) = &pool.lp_token_address {
+        // Burn the LP tokens; fails unless the user holds and has approved them
+        messages.push(burn_lp_msg(lp_token, &info.sender, liquidity)?);
     } else {
-        // CW20 token
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: token_a,
-            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: info.sender.to_string(),
-                amount: amount_a,
-            })?,
-            funds: vec![],
-        }));
+        let liq_key = liquidity_key(&info.sender, &pool.token_a, &pool.token_b);
+        let mut position = LIQUIDITY.load(deps.storage, liq_key.clone())?;
+
+        if position.liquidity < liquidity {
+            return Err(ContractError::InsufficientFunds {});
+        }
+
+        position.liquidity -= liquidity;
+        LIQUIDITY.save(deps.storage, liq_key, &position)?;
     }
 
-    if token_b == "uatom" || token_b.starts_with("ibc/") {
-        // Native token (including IBC tokens)
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: vec![coin(amount_b.u128(), &token_b)],
-        }));
-    } else {
-        // CW20 token
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: token_b,
-            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: info.sender.to_string(),
-                amount: amount_b,
-            })?,
-            funds: vec![],
-        }));
+    let config = CONFIG.load(deps.storage)?;
+    let (amount_out, swap) = pool.zap_out(&asset, liquidity, config.protocol_fee_share, now)?;
+
+    if amount_out < min_out {
+        return Err(ContractError::SlippageExceeded {});
     }
 
+    save_pool(deps.storage, key, &pool)?;
+
+    let token_in = if asset == pool.token_a {
+        &pool.token_b
+    } else {
+        &pool.token_a
+    };
+    accrue_protocol_fee(deps.storage, token_in, swap.protocol_fee)?;
+
+    messages.push(transfer_msg(&asset, &info.sender, amount_out)?);
+
     Ok(Response::new()
         .add_messages(messages)
-        .add_attribute("method", "remove_liquidity")
-        .add_attribute("amount_a", amount_a)
-        .add_attribute("amount_b", amount_b))
+        .add_attribute("method", "zap_out")
+        .add_attribute("asset", asset.to_string())
+        .add_attribute("liquidity", liquidity)
+        .add_attribute("swap_out", swap.amount_out)
+        .add_attribute("amount_out", amount_out))
 }
 
+// Swaps against the pool and the resting limit orders of the pair, whichever pays more
+#[allow(clippy::too_many_arguments)]
 pub fn execute_swap(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    token_in: String,
-    token_out: String,
+    received: Option<Cw20Coin>,
+    token_in: AssetInfo,
+    token_out: AssetInfo,
     amount_in: Uint128,
     min_amount_out: Uint128,
 ) -> Result<Response, ContractError> {
+    assert_not_paused(
+        deps.storage,
+        Some(&PoolId::Pair {
+            token_a: token_in.clone(),
+            token_b: token_out.clone(),
+        }),
+    )?;
+    assert_tradable(deps.storage, &[&token_in, &token_out])?;
+
     if amount_in.is_zero() {
         return Err(ContractError::ZeroAmount {});
     }
 
     let key = pool_key(&token_in, &token_out);
-    let mut pool = POOLS.load(deps.storage, key.clone())?;
+    let mut pool = POOLS
+        .may_load(deps.storage, key.clone())?
+        .ok_or(ContractError::PoolNotFound {})?;
+    pool.accumulate_prices(env.block.time.seconds())?;
 
     let config = CONFIG.load(deps.storage)?;
-
-    // Determine which reserves to use
-    let (reserve_in, reserve_out) = if token_in == pool.token_a {
-        (pool.reserve_a, pool.reserve_b)
-    } else {
-        (pool.reserve_b, pool.reserve_a)
-    };
-
-    // Calculate output amount using constant product formula
-    // amount_out = (amount_in * reserve_out) / (reserve_in + amount_in)
-    // With fee: amount_in_after_fee = amount_in * (10000 - fee_rate) / 10000
-    let fee_denominator = Uint128::from(10000u128);
-    let amount_in_after_fee = amount_in * (fee_denominator - config.fee_rate) / fee_denominator;
-    
-    let amount_out = amount_in_after_fee * reserve_out / (reserve_in + amount_in_after_fee);
+    let (
+        SwapResult {
+            amount_out,
+            fee,
+            protocol_fee,
+        },
+        fills,
+    ) = pool.swap_with_orders(
+        deps.storage,
+        &token_in,
+        amount_in,
+        config.protocol_fee_share,
+        env.block.time.seconds(),
+    )?;
 
     if amount_out < min_amount_out {
         return Err(ContractError::SlippageExceeded {});
     }
 
-    // Update pool reserves
-    if token_in == pool.token_a {
-        pool.reserve_a += amount_in;
-        pool.reserve_b -= amount_out;
-    } else {
-        pool.reserve_b += amount_in;
-        pool.reserve_a -= amount_out;
-    }
+    save_pool(deps.storage, key, &pool)?;
+    accrue_protocol_fee(deps.storage, &token_in, protocol_fee)?;
 
-    POOLS.save(deps.storage, key, &pool)?;
+    // Take custody of the input before paying out
+    let mut messages = collect_funds(&env, &info, &received, &[(&token_in, amount_in)])?;
 
-    // Send output tokens to user
-    let mut messages = vec![];
-    
-    if token_out == "uatom" || token_out.starts_with("ibc/") {
-        // Native token (including IBC tokens)
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: vec![coin(amount_out.u128(), &token_out)],
-        }));
-    } else {
-        // CW20 token
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: token_out.clone(),
-            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: info.sender.to_string(),
-                amount: amount_out,
-            })?,
-            funds: vec![],
-        }));
+    // Pay the owners of the filled orders
+    for fill in &fills {
+        apply_order_fill(deps.storage, fill)?;
+        messages.push(transfer_msg(&token_in, &fill.owner, fill.ask_amount)?);
     }
 
-    let fee = amount_in - amount_in_after_fee;
+    // Send output tokens to user
+    messages.push(transfer_msg(&token_out, &info.sender, amount_out)?);
 
     Ok(Response::new()
         .add_messages(messages)
         .add_attribute("method", "swap")
-        .add_attribute("token_in", token_in)
-        .add_attribute("token_out", token_out)
+        .add_attribute("token_in", token_in.to_string())
+        .add_attribute("token_out", token_out.to_string())
         .add_attribute("amount_in", amount_in)
         .add_attribute("amount_out", amount_out)
-        .add_attribute("fee", fee))
+        .add_attribute("fee", fee)
+        .add_attribute("orders_filled", fills.len().to_string()))
 }
 
-pub fn execute_update_admin(
+// Buys exactly `amount_out` of `token_out`. Up to `max_amount_in` is offered; the
+// unused part of attached or received funds is refunded.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_swap_exact_out(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    admin: String,
+    received: Option<Cw20Coin>,
+    token_in: AssetInfo,
+    token_out: AssetInfo,
+    amount_out: Uint128,
+    max_amount_in: Uint128,
 ) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
-    
-    if info.sender != config.admin {
-        return Err(ContractError::Unauthorized {});
+    assert_not_paused(
+        deps.storage,
+        Some(&PoolId::Pair {
+            token_a: token_in.clone(),
+            token_b: token_out.clone(),
+        }),
+    )?;
+    assert_tradable(deps.storage, &[&token_in, &token_out])?;
+
+    if amount_out.is_zero() {
+        return Err(ContractError::ZeroAmount {});
     }
 
-    config.admin = deps.api.addr_validate(&admin)?;
-    CONFIG.save(deps.storage, &config)?;
+    let key = pool_key(&token_in, &token_out);
+    let mut pool = POOLS
+        .may_load(deps.storage, key.clone())?
+        .ok_or(ContractError::PoolNotFound {})?;
 
-    Ok(Response::new()
-        .add_attribute("method", "update_admin")
-        .add_attribute("new_admin", admin))
-}
+    let (_, reserve_out) = pool.reserves(&token_in);
+    if amount_out >= reserve_out {
+        return Err(ContractError::InsufficientLiquidity {});
+    }
 
-pub fn execute_update_fee_rate(
-    deps: DepsMut,
-    info: MessageInfo,
-    fee_rate: Uint128,
-) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
-    
-    if info.sender != config.admin {
-        return Err(ContractError::Unauthorized {});
+    pool.accumulate_prices(env.block.time.seconds())?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let ReverseSwapResult {
+        amount_in,
+        fee,
+        protocol_fee,
+    } = pool.reverse_swap(
+        &token_in,
+        amount_out,
+        config.protocol_fee_share,
+        env.block.time.seconds(),
+    )?;
+
+    if amount_in > max_amount_in {
+        return Err(ContractError::SlippageExceeded {});
     }
 
-    config.fee_rate = fee_rate;
-    CONFIG.save(deps.storage, &config)?;
+    save_pool(deps.storage, key, &pool)?;
+    accrue_protocol_fee(deps.storage, &token_in, protocol_fee)?;
+
+    // Take custody of the input before paying out
+    assert_native_payments(&info, &[(&token_in, max_amount_in)])?;
+    let mut messages =
+        collect_and_refund(&env, &info, &received, &token_in, max_amount_in, amount_in)?;
+
+    messages.push(transfer_msg(&token_out, &info.sender, amount_out)?);
 
     Ok(Response::new()
-        .add_attribute("method", "update_fee_rate")
-        .add_attribute("new_fee_rate", fee_rate))
+        .add_messages(messages)
+        .add_attribute("method", "swap_exact_out")
+        .add_attribute("token_in", token_in.to_string())
+        .add_attribute("token_out", token_out.to_string())
+        .add_attribute("amount_in", amount_in)
+        .add_attribute("amount_out", amount_out)
+        .add_attribute("fee", fee))
 }
 
-pub fn execute_update_lp_token_code_id(
+// Swaps through each pool of `operations` in order, matching resting limit orders at
+// every hop like Swap. Intermediate amounts never leave the contract and slippage is
+// only checked on the final output.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_swap_operations(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    lp_token_code_id: u64,
+    received: Option<Cw20Coin>,
+    operations: Vec<SwapOperation>,
+    amount_in: Uint128,
+    minimum_receive: Uint128,
 ) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
-    
-    // Only admin can update LP token code ID
-    if info.sender != config.admin {
-        return Err(ContractError::Unauthorized {});
+    if amount_in.is_zero() {
+        return Err(ContractError::ZeroAmount {});
     }
-    
-    config.lp_token_code_id = lp_token_code_id;
-    CONFIG.save(deps.storage, &config)?;
-    
-    Ok(Response::new()
-        .add_attribute("method", "update_lp_token_code_id")
+
+    validate_swap_operations(&operations)?;
+
+    let token_in = operations[0].token_in.clone();
+    let token_out = operations[operations.len() - 1].token_out.clone();
+
+    let mut amount = amount_in;
+    let mut fill_messages = vec![];
+    for operation in &operations {
+        let (result, fills) = swap_in_pool(
+            deps.storage,
+            &env,
+            &operation.token_in,
+            &operation.token_out,
+            amount,
+        )?;
+        amount = result.amount_out;
+
+        // Pay the owners of the orders filled at this hop
+        for fill in &fills {
+            apply_order_fill(deps.storage, fill)?;
+            fill_messages.push(transfer_msg(
+                &operation.token_in,
+                &fill.owner,
+                fill.ask_amount,
+            )?);
+        }
+    }
+
+    if amount < minimum_receive {
+        return Err(ContractError::SlippageExceeded {});
+    }
+
+    // Take custody of the input before paying out
+    let mut messages = collect_funds(&env, &info, &received, &[(&token_in, amount_in)])?;
+
+    messages.extend(fill_messages);
+    messages.push(transfer_msg(&token_out, &info.sender, amount)?);
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "execute_swap_operations")
+        .add_attribute("token_in", token_in.to_string())
+        .add_attribute("token_out", token_out.to_string())
+        .add_attribute("hops", operations.len().to_string())
+        .add_attribute("amount_in", amount_in)
+        .add_attribute("amount_out", amount))
+}
+
+// Checks that the route is non-empty, not too long and that every hop starts with
+// the token the previous hop produced.
+pub fn validate_swap_operations(operations: &[SwapOperation]) -> Result<(), ContractError> {
+    if operations.is_empty() || operations.len() > MAX_SWAP_OPERATIONS {
+        return Err(ContractError::InvalidSwapOperations {});
+    }
+
+    for pair in operations.windows(2) {
+        if pair[0].token_out != pair[1].token_in {
+            return Err(ContractError::InvalidSwapOperations {});
+        }
+    }
+
+    Ok(())
+}
+
+// Swaps `amount_in` of `token_in` against the pool with `token_out` and its resting
+// limit orders, and updates the reserves. Custody of the input, payout of the output
+// and applying the returned order fills are left to the caller.
+fn swap_in_pool(
+    storage: &mut dyn Storage,
+    env: &Env,
+    token_in: &AssetInfo,
+    token_out: &AssetInfo,
+    amount_in: Uint128,
+) -> Result<(SwapResult, Vec<OrderFill>), ContractError> {
+    assert_not_paused(
+        storage,
+        Some(&PoolId::Pair {
+            token_a: token_in.clone(),
+            token_b: token_out.clone(),
+        }),
+    )?;
+    assert_tradable(storage, &[token_in, token_out])?;
+
+    let key = pool_key(token_in, token_out);
+    let mut pool = POOLS
+        .may_load(storage, key.clone())?
+        .ok_or(ContractError::PoolNotFound {})?;
+    pool.accumulate_prices(env.block.time.seconds())?;
+
+    let config = CONFIG.load(storage)?;
+    let (result, fills) = pool.swap_with_orders(
+        storage,
+        token_in,
+        amount_in,
+        config.protocol_fee_share,
+        env.block.time.seconds(),
+    )?;
+
+    save_pool(storage, key, &pool)?;
+    accrue_protocol_fee(storage, token_in, result.protocol_fee)?;
+
+    Ok((result, fills))
+}
+
+// Adds liquidity from a single asset. In a pair pool the share of `amount` that balances
+// the deposit is swapped against the pool first; weighted pools take it as a
+// single-asset join. Whatever the pool ratio leaves over is refunded.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_zap_in(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    received: Option<Cw20Coin>,
+    pool: PoolId,
+    asset: AssetInfo,
+    amount: Uint128,
+    min_liquidity: Uint128,
+) -> Result<Response, ContractError> {
+    let (token_a, token_b) = match pool {
+        PoolId::Pair { token_a, token_b } => (token_a, token_b),
+        PoolId::Weighted { pool_id } => {
+            return execute_join_weighted_pool_single(
+                deps,
+                env,
+                info,
+                received,
+                pool_id,
+                asset,
+                amount,
+                min_liquidity,
+            );
+        }
+        PoolId::Concentrated { .. } => return Err(ContractError::ZapNotSupported {}),
+    };
+
+    assert_not_paused(
+        deps.storage,
+        Some(&PoolId::Pair {
+            token_a: token_a.clone(),
+            token_b: token_b.clone(),
+        }),
+    )?;
+    assert_tradable(deps.storage, &[&token_a, &token_b])?;
+
+    if amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+    if asset != token_a && asset != token_b {
+        return Err(ContractError::InvalidAsset {
+            asset: asset.to_string(),
+        });
+    }
+
+    let key = pool_key(&token_a, &token_b);
+    let mut pool = POOLS
+        .may_load(deps.storage, key.clone())?
+        .ok_or(ContractError::PoolNotFound {})?;
+    let now = env.block.time.seconds();
+    pool.accumulate_prices(now)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let (swap_amount, swap) = pool.zap_swap(&asset, amount, config.protocol_fee_share, now)?;
+
+    // Deposit the rest of the input and the swap output, in pool order
+    let remaining = amount - swap_amount;
+    let (offer_a, offer_b) = if asset == pool.token_a {
+        (remaining, swap.amount_out)
+    } else {
+        (swap.amount_out, remaining)
+    };
+    let (deposit_a, deposit_b, liquidity) = pool.deposit(offer_a, offer_b, now)?;
+
+    if liquidity.is_zero() || liquidity < min_liquidity {
+        return Err(ContractError::MinLiquidityNotMet {});
+    }
+
+    pool.reserve_a += deposit_a;
+    pool.reserve_b += deposit_b;
+    pool.total_liquidity += liquidity;
+
+    save_pool(deps.storage, key, &pool)?;
+    accrue_protocol_fee(deps.storage, &asset, swap.protocol_fee)?;
+
+    // Take custody of the input before paying out
+    let mut messages = collect_funds(&env, &info, &received, &[(&asset, amount)])?;
+
+    // Refund what the pool ratio did not use, of the input or of the swap output
+    for (token, offered, deposited) in [
+        (&pool.token_a, offer_a, deposit_a),
+        (&pool.token_b, offer_b, deposit_b),
+    ] {
+        if offered > deposited {
+            messages.push(transfer_msg(token, &info.sender, offered - deposited)?);
+        }
+    }
+
+    if let Some(lp_token) = &pool.lp_token_address {
+        messages.push(mint_lp_msg(lp_token, &info.sender, liquidity)?);
+    } else {
+        let liq_key = liquidity_key(&info.sender, &pool.token_a, &pool.token_b);
+        let mut position = LIQUIDITY
+            .may_load(deps.storage, liq_key.clone())?
+            .unwrap_or(LiquidityPosition {
+                liquidity: Uint128::zero(),
+            });
+
+        position.liquidity += liquidity;
+        LIQUIDITY.save(deps.storage, liq_key, &position)?;
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "zap_in")
+        .add_attribute("asset", asset.to_string())
+        .add_attribute("amount", amount)
+        .add_attribute("swap_amount", swap_amount)
+        .add_attribute("swap_out", swap.amount_out)
+        .add_attribute("deposit_a", deposit_a)
+        .add_attribute("deposit_b", deposit_b)
+        .add_attribute("liquidity", liquidity))
+}
+
+pub fn execute_create_weighted_pool(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    assets: Vec<WeightedAssetParams>,
+    fee_rate: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, None)?;
+
+    if assets.len() < MIN_WEIGHTED_ASSETS || assets.len() > MAX_WEIGHTED_ASSETS {
+        return Err(ContractError::InvalidPoolAssets {});
+    }
+
+    let mut tokens: Vec<String> = assets.iter().map(|asset| asset.token.to_string()).collect();
+    tokens.sort_unstable();
+    tokens.dedup();
+    if tokens.len() != assets.len() {
+        return Err(ContractError::InvalidPoolAssets {});
+    }
+
+    if assets.iter().any(|asset| asset.amount.is_zero()) {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    for asset in &assets {
+        validate_asset(deps.api, deps.storage, &config, &asset.token)?;
+    }
+
+    let fee_rate = fee_rate.unwrap_or(config.fee_rate);
+    assert_fee_tier(&config, fee_rate)?;
+
+    // Normalize the weights so they sum to 1
+    let total_weight = assets
+        .iter()
+        .try_fold(Uint128::zero(), |total, asset| {
+            total.checked_add(asset.weight)
+        })
+        .map_err(StdError::from)?;
+    if total_weight.is_zero() {
+        return Err(ContractError::InvalidPoolAssets {});
+    }
+
+    let weighted_assets: Vec<WeightedAsset> = assets
+        .iter()
+        .map(|asset| WeightedAsset {
+            token: asset.token.clone(),
+            weight: Decimal256::from_ratio(asset.weight, total_weight),
+            reserve: asset.amount,
+        })
+        .collect();
+
+    if weighted_assets
+        .iter()
+        .any(|asset| asset.weight < Decimal256::percent(MIN_WEIGHT_PERCENT))
+    {
+        return Err(ContractError::InvalidPoolAssets {});
+    }
+
+    // Initial liquidity is the weighted geometric mean of the reserves, Balancer's
+    // invariant, less the MINIMUM_LIQUIDITY locked as for constant product pools
+    let reserves: Vec<(Uint128, Decimal256)> = weighted_assets
+        .iter()
+        .map(|asset| (asset.reserve, asset.weight))
+        .collect();
+    let initial_liquidity = compute_weighted_invariant(&reserves)?;
+    if initial_liquidity <= Uint128::from(MINIMUM_LIQUIDITY) {
+        return Err(ContractError::InsufficientInitialLiquidity {
+            minimum: Uint128::from(MINIMUM_LIQUIDITY),
+        });
+    }
+
+    let pool_id = WEIGHTED_POOL_COUNT
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    WEIGHTED_POOL_COUNT.save(deps.storage, &pool_id)?;
+
+    let pool = WeightedPool {
+        pool_id,
+        assets: weighted_assets,
+        total_liquidity: initial_liquidity,
+        lp_token_address: None,
+        fee_rate,
+    };
+
+    WEIGHTED_POOLS.save(deps.storage, pool_id, &pool)?;
+    PENDING_WEIGHTED_LP_TOKEN.save(deps.storage, &pool_id)?;
+
+    let lp_token_msg = instantiate_lp_token_msg(
+        &env,
+        config.lp_token_code_id,
+        &info.sender,
+        pool.total_liquidity,
+        format!("dex weighted lp token {}", pool_id),
+        INSTANTIATE_WEIGHTED_LP_TOKEN_REPLY_ID,
+    )?;
+
+    // Take custody of the initial reserves and the creation fee
+    let creation_fee = pool_creation_fee_payment(&config);
+    let mut payments: Vec<(&AssetInfo, Uint128)> = assets
+        .iter()
+        .map(|asset| (&asset.token, asset.amount))
+        .collect();
+    payments.extend(creation_fee.iter().map(|(asset, amount)| (asset, *amount)));
+    let mut messages = collect_funds(&env, &info, &None, &payments)?;
+    messages.extend(config.pool_creation_fee.as_ref().map(pool_creation_fee_msg));
+
+    let weights: Vec<String> = pool
+        .assets
+        .iter()
+        .map(|asset| format!("{}:{}", asset.token, asset.weight))
+        .collect();
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessage(lp_token_msg)
+        .add_attribute("method", "create_weighted_pool")
+        .add_attribute("pool_id", pool_id.to_string())
+        .add_attribute("weights", weights.join(","))
+        .add_attribute("fee_rate", fee_rate)
+        .add_attribute("liquidity", pool.total_liquidity)
+        .add_attribute("locked_liquidity", Uint128::from(MINIMUM_LIQUIDITY)))
+}
+
+pub fn reply_instantiate_weighted_lp_token(
+    deps: DepsMut,
+    msg: Reply,
+) -> Result<Response, ContractError> {
+    let res = parse_reply_instantiate_data(msg)?;
+    let lp_token = deps.api.addr_validate(&res.contract_address)?;
+
+    let pool_id = PENDING_WEIGHTED_LP_TOKEN.load(deps.storage)?;
+    PENDING_WEIGHTED_LP_TOKEN.remove(deps.storage);
+
+    let mut pool = WEIGHTED_POOLS.load(deps.storage, pool_id)?;
+    pool.lp_token_address = Some(lp_token.to_string());
+    WEIGHTED_POOLS.save(deps.storage, pool_id, &pool)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "instantiate_weighted_lp_token")
+        .add_attribute("pool_id", pool_id.to_string())
+        .add_attribute("lp_token_address", lp_token))
+}
+
+pub fn execute_join_weighted_pool(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pool_id: u64,
+    assets: Vec<TokenAmount>,
+    min_liquidity: Uint128,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Some(&PoolId::Weighted { pool_id }))?;
+
+    let mut pool = load_weighted_pool(deps.storage, pool_id)?;
+
+    // Offered amounts in pool asset order; every asset must be offered exactly once
+    if assets.len() != pool.assets.len() {
+        return Err(ContractError::InvalidPoolAssets {});
+    }
+    let offers = pool
+        .assets
+        .iter()
+        .map(|pool_asset| {
+            assets
+                .iter()
+                .find(|asset| asset.token == pool_asset.token)
+                .map(|asset| asset.amount)
+                .ok_or(ContractError::InvalidPoolAssets {})
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (deposits, liquidity) = pool.join(&offers)?;
+
+    if liquidity.is_zero() || liquidity < min_liquidity {
+        return Err(ContractError::MinLiquidityNotMet {});
+    }
+
+    for (asset, deposit) in pool.assets.iter_mut().zip(&deposits) {
+        asset.reserve += *deposit;
+    }
+    pool.total_liquidity += liquidity;
+
+    WEIGHTED_POOLS.save(deps.storage, pool_id, &pool)?;
+
+    // Collect the deposits and refund what the pool ratios did not use
+    let payments: Vec<(&AssetInfo, Uint128)> = pool
+        .assets
+        .iter()
+        .zip(&offers)
+        .map(|(asset, offer)| (&asset.token, *offer))
+        .collect();
+    assert_native_payments(&info, &payments)?;
+
+    let mut messages = vec![];
+
+    for ((token, offered), deposited) in payments.into_iter().zip(&deposits) {
+        messages.extend(collect_and_refund(
+            &env, &info, &None, token, offered, *deposited,
+        )?);
+    }
+
+    messages.push(mint_lp_msg(
+        &weighted_lp_token(&pool)?,
+        &info.sender,
+        liquidity,
+    )?);
+
+    let deposits: Vec<String> = deposits.iter().map(|deposit| deposit.to_string()).collect();
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "join_weighted_pool")
+        .add_attribute("pool_id", pool_id.to_string())
+        .add_attribute("deposits", deposits.join(","))
+        .add_attribute("liquidity", liquidity))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_join_weighted_pool_single(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    received: Option<Cw20Coin>,
+    pool_id: u64,
+    token: AssetInfo,
+    amount: Uint128,
+    min_liquidity: Uint128,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Some(&PoolId::Weighted { pool_id }))?;
+
+    if amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    let mut pool = load_weighted_pool(deps.storage, pool_id)?;
+    let index = pool.asset_index(&token)?;
+    let liquidity = pool.join_single(&token, amount)?;
+
+    if liquidity.is_zero() || liquidity < min_liquidity {
+        return Err(ContractError::MinLiquidityNotMet {});
+    }
+
+    pool.assets[index].reserve += amount;
+    pool.total_liquidity += liquidity;
+
+    WEIGHTED_POOLS.save(deps.storage, pool_id, &pool)?;
+
+    // Take custody of the deposit before minting
+    let mut messages = collect_funds(&env, &info, &received, &[(&token, amount)])?;
+
+    messages.push(mint_lp_msg(
+        &weighted_lp_token(&pool)?,
+        &info.sender,
+        liquidity,
+    )?);
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "join_weighted_pool_single")
+        .add_attribute("pool_id", pool_id.to_string())
+        .add_attribute("token", token.to_string())
+        .add_attribute("amount", amount)
+        .add_attribute("liquidity", liquidity))
+}
+
+pub fn execute_exit_weighted_pool(
+    deps: DepsMut,
+    info: MessageInfo,
+    pool_id: u64,
+    liquidity: Uint128,
+    min_amounts: Vec<TokenAmount>,
+) -> Result<Response, ContractError> {
+    if liquidity.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    let mut pool = load_weighted_pool(deps.storage, pool_id)?;
+    let amounts = pool.exit(liquidity);
+
+    for min_amount in &min_amounts {
+        if amounts[pool.asset_index(&min_amount.token)?] < min_amount.amount {
+            return Err(ContractError::SlippageExceeded {});
+        }
+    }
+
+    for (asset, amount) in pool.assets.iter_mut().zip(&amounts) {
+        asset.reserve -= *amount;
+    }
+    pool.total_liquidity -= liquidity;
+
+    WEIGHTED_POOLS.save(deps.storage, pool_id, &pool)?;
+
+    // Burn the LP tokens; fails unless the user holds and has approved them
+    let mut messages = vec![burn_lp_msg(
+        &weighted_lp_token(&pool)?,
+        &info.sender,
+        liquidity,
+    )?];
+
+    for (asset, amount) in pool.assets.iter().zip(&amounts) {
+        if !amount.is_zero() {
+            messages.push(transfer_msg(&asset.token, &info.sender, *amount)?);
+        }
+    }
+
+    let amounts: Vec<String> = amounts.iter().map(|amount| amount.to_string()).collect();
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "exit_weighted_pool")
+        .add_attribute("pool_id", pool_id.to_string())
+        .add_attribute("liquidity", liquidity)
+        .add_attribute("amounts", amounts.join(",")))
+}
+
+pub fn execute_exit_weighted_pool_single(
+    deps: DepsMut,
+    info: MessageInfo,
+    pool_id: u64,
+    liquidity: Uint128,
+    token_out: AssetInfo,
+    min_amount_out: Uint128,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Some(&PoolId::Weighted { pool_id }))?;
+
+    if liquidity.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    let mut pool = load_weighted_pool(deps.storage, pool_id)?;
+    let index = pool.asset_index(&token_out)?;
+
+    // Exiting into one asset sells the pool's other assets for it
+    let pool_assets: Vec<&AssetInfo> = pool.assets.iter().map(|asset| &asset.token).collect();
+    assert_tradable(deps.storage, &pool_assets)?;
+
+    let amount_out = pool.exit_single(&token_out, liquidity)?;
+
+    if amount_out < min_amount_out {
+        return Err(ContractError::SlippageExceeded {});
+    }
+
+    pool.assets[index].reserve -= amount_out;
+    pool.total_liquidity -= liquidity;
+
+    WEIGHTED_POOLS.save(deps.storage, pool_id, &pool)?;
+
+    // Burn the LP tokens; fails unless the user holds and has approved them
+    let messages = vec![
+        burn_lp_msg(&weighted_lp_token(&pool)?, &info.sender, liquidity)?,
+        transfer_msg(&token_out, &info.sender, amount_out)?,
+    ];
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "exit_weighted_pool_single")
+        .add_attribute("pool_id", pool_id.to_string())
+        .add_attribute("liquidity", liquidity)
+        .add_attribute("token_out", token_out.to_string())
+        .add_attribute("amount_out", amount_out))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_weighted_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    received: Option<Cw20Coin>,
+    pool_id: u64,
+    token_in: AssetInfo,
+    token_out: AssetInfo,
+    amount_in: Uint128,
+    min_amount_out: Uint128,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Some(&PoolId::Weighted { pool_id }))?;
+
+    if amount_in.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    assert_tradable(deps.storage, &[&token_in, &token_out])?;
+
+    let mut pool = load_weighted_pool(deps.storage, pool_id)?;
+    let config = CONFIG.load(deps.storage)?;
+    let SwapResult {
+        amount_out,
+        fee,
+        protocol_fee,
+    } = pool.swap(&token_in, &token_out, amount_in, config.protocol_fee_share)?;
+
+    if amount_out < min_amount_out {
+        return Err(ContractError::SlippageExceeded {});
+    }
+
+    WEIGHTED_POOLS.save(deps.storage, pool_id, &pool)?;
+    accrue_protocol_fee(deps.storage, &token_in, protocol_fee)?;
+
+    // Take custody of the input before paying out
+    let mut messages = collect_funds(&env, &info, &received, &[(&token_in, amount_in)])?;
+
+    messages.push(transfer_msg(&token_out, &info.sender, amount_out)?);
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "weighted_swap")
+        .add_attribute("pool_id", pool_id.to_string())
+        .add_attribute("token_in", token_in.to_string())
+        .add_attribute("token_out", token_out.to_string())
+        .add_attribute("amount_in", amount_in)
+        .add_attribute("amount_out", amount_out)
+        .add_attribute("fee", fee))
+}
+
+fn load_weighted_pool(storage: &dyn Storage, pool_id: u64) -> Result<WeightedPool, ContractError> {
+    WEIGHTED_POOLS
+        .may_load(storage, pool_id)?
+        .ok_or(ContractError::PoolNotFound {})
+}
+
+// The LP token is set by the reply in the same transaction that creates the pool
+fn weighted_lp_token(pool: &WeightedPool) -> StdResult<String> {
+    pool.lp_token_address
+        .clone()
+        .ok_or_else(|| StdError::generic_err("Weighted pool LP token not instantiated"))
+}
+
+pub fn execute_create_concentrated_pool(
+    deps: DepsMut,
+    info: MessageInfo,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
+    fee_rate: Option<Uint128>,
+    initial_price: Decimal256,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, None)?;
+
+    if token_a == token_b {
+        return Err(ContractError::InvalidTokenPair {});
+    }
+
+    if initial_price.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    validate_asset(deps.api, deps.storage, &config, &token_a)?;
+    validate_asset(deps.api, deps.storage, &config, &token_b)?;
+
+    let fee_rate = fee_rate.unwrap_or(config.fee_rate);
+    assert_fee_tier(&config, fee_rate)?;
+
+    // The pool starts empty, so nothing but the creation fee may be attached
+    let creation_fee = pool_creation_fee_payment(&config);
+    let payments: Vec<(&AssetInfo, Uint128)> = creation_fee
+        .iter()
+        .map(|(asset, amount)| (asset, *amount))
+        .collect();
+    assert_native_payments(&info, &payments)?;
+
+    let sqrt_price = initial_price.sqrt();
+    let tick = tick_at_sqrt_price(sqrt_price)?;
+
+    // Wider spacing for higher fee tiers, as in Uniswap v3 (30 bps -> 60 ticks)
+    let tick_spacing = i32::try_from((fee_rate.u128() * TICK_SPACING_PER_FEE_BPS).max(1))
+        .map_err(|_| StdError::generic_err("Fee rate too large for a concentrated pool"))?;
+
+    let pool_id = CONCENTRATED_POOL_COUNT
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    CONCENTRATED_POOL_COUNT.save(deps.storage, &pool_id)?;
+
+    let pool = ConcentratedPool {
+        pool_id,
+        token_a,
+        token_b,
+        fee_rate,
+        tick_spacing,
+        sqrt_price,
+        tick,
+        liquidity: Uint128::zero(),
+        fee_growth_global_a: Uint256::zero(),
+        fee_growth_global_b: Uint256::zero(),
+    };
+
+    CONCENTRATED_POOLS.save(deps.storage, pool_id, &pool)?;
+
+    Ok(Response::new()
+        .add_messages(config.pool_creation_fee.as_ref().map(pool_creation_fee_msg))
+        .add_attribute("method", "create_concentrated_pool")
+        .add_attribute("pool_id", pool_id.to_string())
+        .add_attribute("token_a", pool.token_a.to_string())
+        .add_attribute("token_b", pool.token_b.to_string())
+        .add_attribute("fee_rate", fee_rate)
+        .add_attribute("tick_spacing", tick_spacing.to_string())
+        .add_attribute("tick", tick.to_string()))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_open_position(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pool_id: u64,
+    lower_tick: i32,
+    upper_tick: i32,
+    amount_a: Uint128,
+    amount_b: Uint128,
+    min_liquidity: Uint128,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Some(&PoolId::Concentrated { pool_id }))?;
+
+    let mut pool = load_concentrated_pool(deps.storage, pool_id)?;
+
+    if lower_tick >= upper_tick
+        || lower_tick < MIN_TICK
+        || upper_tick > MAX_TICK
+        || lower_tick % pool.tick_spacing != 0
+        || upper_tick % pool.tick_spacing != 0
+    {
+        return Err(ContractError::InvalidTickRange {});
+    }
+
+    let liquidity = liquidity_for_amounts(
+        pool.sqrt_price,
+        sqrt_price_at_tick(lower_tick)?,
+        sqrt_price_at_tick(upper_tick)?,
+        amount_a,
+        amount_b,
+    )?;
+
+    if liquidity.is_zero() || liquidity < min_liquidity {
+        return Err(ContractError::MinLiquidityNotMet {});
+    }
+
+    let (deposit_a, deposit_b) =
+        pool.amounts_for_liquidity(lower_tick, upper_tick, liquidity, true)?;
+    if deposit_a > amount_a || deposit_b > amount_b {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    let position_id = POSITION_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    POSITION_COUNT.save(deps.storage, &position_id)?;
+
+    let mut position = ConcentratedPosition {
+        owner: info.sender.clone(),
+        pool_id,
+        lower_tick,
+        upper_tick,
+        liquidity: Uint128::zero(),
+        fee_growth_inside_a_last: Uint256::zero(),
+        fee_growth_inside_b_last: Uint256::zero(),
+        tokens_owed_a: Uint128::zero(),
+        tokens_owed_b: Uint128::zero(),
+    };
+    update_position(
+        deps.storage,
+        &mut pool,
+        &mut position,
+        liquidity_delta(liquidity, false)?,
+    )?;
+
+    CONCENTRATED_POOLS.save(deps.storage, pool_id, &pool)?;
+    POSITIONS.save(deps.storage, position_id, &position)?;
+    OWNER_POSITIONS.save(deps.storage, (info.sender.clone(), position_id), &Empty {})?;
+
+    // Collect the deposits and refund what the range did not use
+    assert_native_payments(
+        &info,
+        &[(&pool.token_a, amount_a), (&pool.token_b, amount_b)],
+    )?;
+
+    let mut messages = collect_and_refund(&env, &info, &None, &pool.token_a, amount_a, deposit_a)?;
+    messages.extend(collect_and_refund(
+        &env,
+        &info,
+        &None,
+        &pool.token_b,
+        amount_b,
+        deposit_b,
+    )?);
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "open_position")
+        .add_attribute("pool_id", pool_id.to_string())
+        .add_attribute("position_id", position_id.to_string())
+        .add_attribute("lower_tick", lower_tick.to_string())
+        .add_attribute("upper_tick", upper_tick.to_string())
+        .add_attribute("liquidity", liquidity)
+        .add_attribute("amount_a", deposit_a)
+        .add_attribute("amount_b", deposit_b))
+}
+
+pub fn execute_withdraw_position(
+    deps: DepsMut,
+    info: MessageInfo,
+    position_id: u64,
+    liquidity: Uint128,
+    min_a: Uint128,
+    min_b: Uint128,
+) -> Result<Response, ContractError> {
+    if liquidity.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    let mut position = load_position(deps.storage, &info, position_id)?;
+    if liquidity > position.liquidity {
+        return Err(ContractError::InsufficientLiquidity {});
+    }
+
+    let mut pool = load_concentrated_pool(deps.storage, position.pool_id)?;
+    update_position(
+        deps.storage,
+        &mut pool,
+        &mut position,
+        liquidity_delta(liquidity, true)?,
+    )?;
+
+    let (amount_a, amount_b) =
+        pool.amounts_for_liquidity(position.lower_tick, position.upper_tick, liquidity, false)?;
+
+    if amount_a < min_a || amount_b < min_b {
+        return Err(ContractError::SlippageExceeded {});
+    }
+
+    // Fees are paid out together with the withdrawn liquidity
+    let fees_a = std::mem::take(&mut position.tokens_owed_a);
+    let fees_b = std::mem::take(&mut position.tokens_owed_b);
+
+    CONCENTRATED_POOLS.save(deps.storage, pool.pool_id, &pool)?;
+    if position.liquidity.is_zero() {
+        POSITIONS.remove(deps.storage, position_id);
+        OWNER_POSITIONS.remove(deps.storage, (info.sender.clone(), position_id));
+    } else {
+        POSITIONS.save(deps.storage, position_id, &position)?;
+    }
+
+    let mut messages = vec![];
+    for (token, amount) in [
+        (&pool.token_a, amount_a + fees_a),
+        (&pool.token_b, amount_b + fees_b),
+    ] {
+        if !amount.is_zero() {
+            messages.push(transfer_msg(token, &info.sender, amount)?);
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "withdraw_position")
+        .add_attribute("position_id", position_id.to_string())
+        .add_attribute("liquidity", liquidity)
+        .add_attribute("amount_a", amount_a)
+        .add_attribute("amount_b", amount_b)
+        .add_attribute("fees_a", fees_a)
+        .add_attribute("fees_b", fees_b))
+}
+
+pub fn execute_collect_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+    position_id: u64,
+) -> Result<Response, ContractError> {
+    let mut position = load_position(deps.storage, &info, position_id)?;
+    let mut pool = load_concentrated_pool(deps.storage, position.pool_id)?;
+
+    // A zero liquidity change credits the fees earned since the last update
+    update_position(deps.storage, &mut pool, &mut position, 0)?;
+
+    let fees_a = std::mem::take(&mut position.tokens_owed_a);
+    let fees_b = std::mem::take(&mut position.tokens_owed_b);
+    POSITIONS.save(deps.storage, position_id, &position)?;
+
+    let mut messages = vec![];
+    for (token, amount) in [(&pool.token_a, fees_a), (&pool.token_b, fees_b)] {
+        if !amount.is_zero() {
+            messages.push(transfer_msg(token, &info.sender, amount)?);
+        }
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "collect_fees")
+        .add_attribute("position_id", position_id.to_string())
+        .add_attribute("fees_a", fees_a)
+        .add_attribute("fees_b", fees_b))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_concentrated_swap(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    received: Option<Cw20Coin>,
+    pool_id: u64,
+    token_in: AssetInfo,
+    amount_in: Uint128,
+    min_amount_out: Uint128,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Some(&PoolId::Concentrated { pool_id }))?;
+
+    if amount_in.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    let mut pool = load_concentrated_pool(deps.storage, pool_id)?;
+    assert_tradable(deps.storage, &[&pool.token_a, &pool.token_b])?;
+    let config = CONFIG.load(deps.storage)?;
+    let (
+        SwapResult {
+            amount_out,
+            fee,
+            protocol_fee,
+        },
+        crossings,
+    ) = pool.swap(
+        deps.storage,
+        &token_in,
+        amount_in,
+        config.protocol_fee_share,
+    )?;
+
+    if amount_out.is_zero() || amount_out < min_amount_out {
+        return Err(ContractError::SlippageExceeded {});
+    }
+
+    // Fee growth outside a crossed tick is now on the other side of the price
+    for crossing in crossings {
+        TICKS.update(
+            deps.storage,
+            (pool_id, crossing.tick),
+            |tick| -> StdResult<_> {
+                let mut tick = tick.ok_or_else(|| StdError::not_found("TickInfo"))?;
+                tick.fee_growth_outside_a = crossing
+                    .fee_growth_global_a
+                    .wrapping_sub(tick.fee_growth_outside_a);
+                tick.fee_growth_outside_b = crossing
+                    .fee_growth_global_b
+                    .wrapping_sub(tick.fee_growth_outside_b);
+                Ok(tick)
+            },
+        )?;
+    }
+
+    CONCENTRATED_POOLS.save(deps.storage, pool_id, &pool)?;
+    accrue_protocol_fee(deps.storage, &token_in, protocol_fee)?;
+
+    let token_out = if token_in == pool.token_a {
+        &pool.token_b
+    } else {
+        &pool.token_a
+    };
+
+    // Take custody of the input before paying out
+    let mut messages = collect_funds(&env, &info, &received, &[(&token_in, amount_in)])?;
+
+    messages.push(transfer_msg(token_out, &info.sender, amount_out)?);
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "concentrated_swap")
+        .add_attribute("pool_id", pool_id.to_string())
+        .add_attribute("token_in", token_in.to_string())
+        .add_attribute("token_out", token_out.to_string())
+        .add_attribute("amount_in", amount_in)
+        .add_attribute("amount_out", amount_out)
+        .add_attribute("fee", fee)
+        .add_attribute("tick", pool.tick.to_string()))
+}
+
+fn load_concentrated_pool(
+    storage: &dyn Storage,
+    pool_id: u64,
+) -> Result<ConcentratedPool, ContractError> {
+    CONCENTRATED_POOLS
+        .may_load(storage, pool_id)?
+        .ok_or(ContractError::PoolNotFound {})
+}
+
+// Loads a position owned by the sender
+fn load_position(
+    storage: &dyn Storage,
+    info: &MessageInfo,
+    position_id: u64,
+) -> Result<ConcentratedPosition, ContractError> {
+    let position = POSITIONS.load(storage, position_id)?;
+    if position.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(position)
+}
+
+// Signed liquidity change for adding or removing `liquidity`
+fn liquidity_delta(liquidity: Uint128, remove: bool) -> StdResult<i128> {
+    let delta = i128::try_from(liquidity.u128())
+        .map_err(|_| StdError::generic_err("Liquidity too large"))?;
+    Ok(if remove { -delta } else { delta })
+}
+
+// Changes a position's liquidity by liquidity_delta as in Uniswap v3: updates both ticks,
+// credits the fees earned inside the range since the last update and, if the range holds
+// the current price, the pool's active liquidity. Ticks left without liquidity are removed.
+fn update_position(
+    storage: &mut dyn Storage,
+    pool: &mut ConcentratedPool,
+    position: &mut ConcentratedPosition,
+    liquidity_delta: i128,
+) -> StdResult<()> {
+    let lower = update_tick(storage, pool, position.lower_tick, liquidity_delta, false)?;
+    let upper = update_tick(storage, pool, position.upper_tick, liquidity_delta, true)?;
+
+    let (inside_a, inside_b) =
+        pool.fee_growth_inside(position.lower_tick, &lower, position.upper_tick, &upper);
+    position.tokens_owed_a += fees_earned(
+        position.liquidity,
+        inside_a.wrapping_sub(position.fee_growth_inside_a_last),
+    )?;
+    position.tokens_owed_b += fees_earned(
+        position.liquidity,
+        inside_b.wrapping_sub(position.fee_growth_inside_b_last),
+    )?;
+    position.fee_growth_inside_a_last = inside_a;
+    position.fee_growth_inside_b_last = inside_b;
+    position.liquidity = add_liquidity_delta(position.liquidity, liquidity_delta)?;
+
+    if pool.is_active(position.lower_tick, position.upper_tick) {
+        pool.liquidity = add_liquidity_delta(pool.liquidity, liquidity_delta)?;
+    }
+
+    for (tick, info) in [(position.lower_tick, lower), (position.upper_tick, upper)] {
+        if info.liquidity_gross.is_zero() {
+            TICKS.remove(storage, (pool.pool_id, tick));
+        } else {
+            TICKS.save(storage, (pool.pool_id, tick), &info)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Applies liquidity_delta to a tick without saving it. A tick that was not initialized
+// assumes all fee growth so far happened below it.
+fn update_tick(
+    storage: &dyn Storage,
+    pool: &ConcentratedPool,
+    tick: i32,
+    liquidity_delta: i128,
+    upper: bool,
+) -> StdResult<TickInfo> {
+    let mut info = match TICKS.may_load(storage, (pool.pool_id, tick))? {
+        Some(info) => info,
+        None if tick <= pool.tick => TickInfo {
+            liquidity_gross: Uint128::zero(),
+            liquidity_net: Int128::zero(),
+            fee_growth_outside_a: pool.fee_growth_global_a,
+            fee_growth_outside_b: pool.fee_growth_global_b,
+        },
+        None => TickInfo {
+            liquidity_gross: Uint128::zero(),
+            liquidity_net: Int128::zero(),
+            fee_growth_outside_a: Uint256::zero(),
+            fee_growth_outside_b: Uint256::zero(),
+        },
+    };
+
+    // Liquidity enters the active range at its lower tick and leaves at its upper tick
+    info.liquidity_gross = add_liquidity_delta(info.liquidity_gross, liquidity_delta)?;
+    info.liquidity_net = if upper {
+        info.liquidity_net
+            .checked_sub(Int128::new(liquidity_delta))?
+    } else {
+        info.liquidity_net
+            .checked_add(Int128::new(liquidity_delta))?
+    };
+
+    Ok(info)
+}
+
+// Rests an order selling offer_amount of offer_token at `price` ask_token each. The pair
+// must have a pool; swaps fill the order whenever it pays better than the pool.
+#[allow(clippy::too_many_arguments)]
+pub fn execute_place_limit_order(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    received: Option<Cw20Coin>,
+    offer_token: AssetInfo,
+    ask_token: AssetInfo,
+    offer_amount: Uint128,
+    price: Decimal256,
+) -> Result<Response, ContractError> {
+    assert_not_paused(
+        deps.storage,
+        Some(&PoolId::Pair {
+            token_a: offer_token.clone(),
+            token_b: ask_token.clone(),
+        }),
+    )?;
+    assert_tradable(deps.storage, &[&offer_token, &ask_token])?;
+
+    if offer_amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+    if price.is_zero() {
+        return Err(ContractError::InvalidOrderPrice {});
+    }
+    if offer_token == ask_token {
+        return Err(ContractError::InvalidTokenPair {});
+    }
+    if !POOLS.has(deps.storage, pool_key(&offer_token, &ask_token)) {
+        return Err(ContractError::PoolNotFound {});
+    }
+
+    let order_id = ORDER_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    ORDER_COUNT.save(deps.storage, &order_id)?;
+
+    let order = LimitOrder {
+        order_id,
+        owner: info.sender.clone(),
+        offer_token: offer_token.clone(),
+        ask_token: ask_token.clone(),
+        price,
+        offer_amount,
+        remaining: offer_amount,
+        received: Uint128::zero(),
+    };
+    ORDERS.save(deps.storage, order_id, &order)?;
+    ORDER_BOOK.save(deps.storage, order_book_key(&order), &order_id)?;
+    OWNER_ORDERS.save(deps.storage, (info.sender.clone(), order_id), &Empty {})?;
+
+    // Take custody of the offered tokens
+    let messages = collect_funds(&env, &info, &received, &[(&offer_token, offer_amount)])?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "place_limit_order")
+        .add_attribute("order_id", order_id.to_string())
+        .add_attribute("offer_token", offer_token.to_string())
+        .add_attribute("ask_token", ask_token.to_string())
+        .add_attribute("offer_amount", offer_amount)
+        .add_attribute("price", price.to_string()))
+}
+
+// Removes an order from the book and refunds its unsold part to the owner
+pub fn execute_cancel_limit_order(
+    deps: DepsMut,
+    info: MessageInfo,
+    order_id: u64,
+) -> Result<Response, ContractError> {
+    let order = ORDERS.load(deps.storage, order_id)?;
+    if order.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    remove_order(deps.storage, &order);
+
+    Ok(Response::new()
+        .add_message(transfer_msg(
+            &order.offer_token,
+            &order.owner,
+            order.remaining,
+        )?)
+        .add_attribute("method", "cancel_limit_order")
+        .add_attribute("order_id", order_id.to_string())
+        .add_attribute("refund", order.remaining))
+}
+
+// Records a fill on its order, removing the order once nothing is left to sell
+fn apply_order_fill(storage: &mut dyn Storage, fill: &OrderFill) -> StdResult<()> {
+    let mut order = ORDERS.load(storage, fill.order_id)?;
+    order.remaining -= fill.offer_amount;
+    order.received += fill.ask_amount;
+
+    if order.remaining.is_zero() {
+        remove_order(storage, &order);
+    } else {
+        ORDERS.save(storage, fill.order_id, &order)?;
+    }
+
+    Ok(())
+}
+
+fn remove_order(storage: &mut dyn Storage, order: &LimitOrder) {
+    ORDERS.remove(storage, order.order_id);
+    ORDER_BOOK.remove(storage, order_book_key(order));
+    OWNER_ORDERS.remove(storage, (order.owner.clone(), order.order_id));
+}
+
+// Offers the admin role to `new_admin`, replacing any pending proposal. Nothing
+// changes until the new admin accepts.
+pub fn execute_propose_new_admin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_admin: String,
+    expires_in: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if expires_in == 0 || expires_in > MAX_ADMIN_PROPOSAL_DURATION {
+        return Err(ContractError::InvalidProposalExpiry {
+            max_seconds: MAX_ADMIN_PROPOSAL_DURATION,
+        });
+    }
+
+    let proposal = AdminProposal {
+        new_admin: deps.api.addr_validate(&new_admin)?,
+        expires_at: env.block.time.seconds() + expires_in,
+    };
+    ADMIN_PROPOSAL.save(deps.storage, &proposal)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "propose_new_admin")
+        .add_attribute("new_admin", proposal.new_admin)
+        .add_attribute("expires_at", proposal.expires_at.to_string()))
+}
+
+pub fn execute_accept_admin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let proposal = ADMIN_PROPOSAL
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoAdminProposal {})?;
+
+    if info.sender != proposal.new_admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if env.block.time.seconds() >= proposal.expires_at {
+        return Err(ContractError::AdminProposalExpired {});
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    config.admin = Some(proposal.new_admin);
+    CONFIG.save(deps.storage, &config)?;
+    ADMIN_PROPOSAL.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("method", "accept_admin")
+        .add_attribute("new_admin", info.sender))
+}
+
+pub fn execute_cancel_admin_proposal(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if ADMIN_PROPOSAL.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoAdminProposal {});
+    }
+    ADMIN_PROPOSAL.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("method", "cancel_admin_proposal"))
+}
+
+// Gives up the admin role for good, leaving the config immutable. The pauser can
+// still pause and the treasury can still collect protocol fees.
+pub fn execute_renounce_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.admin = None;
+    CONFIG.save(deps.storage, &config)?;
+    ADMIN_PROPOSAL.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("method", "renounce_admin")
+        .add_attribute("admin", info.sender))
+}
+
+// Queues a new default fee rate for new pools
+pub fn execute_update_fee_rate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    fee_rate: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    assert_fee_tier(&config, fee_rate)?;
+
+    let pending = queue_fee_change(deps.storage, &env, &config, FeeChange::FeeRate { fee_rate })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_fee_rate")
+        .add_attribute("new_fee_rate", fee_rate)
+        .add_attribute("change_id", pending.change_id.to_string())
+        .add_attribute("effective_at", pending.effective_at.to_string()))
+}
+
+pub fn execute_update_fee_tiers(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee_tiers: Vec<Uint128>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.fee_tiers = validate_fee_tiers(fee_tiers, config.max_fee_rate)?;
+
+    // The default fee for new pools and every queued fee rate must remain allowed tiers
+    assert_fee_tier(&config, config.fee_rate)?;
+    for item in PENDING_FEE_CHANGES.range(deps.storage, None, None, Order::Ascending) {
+        let (change_id, pending) = item?;
+        if let FeeChange::FeeRate { fee_rate } | FeeChange::PoolFee { fee_rate, .. } =
+            pending.change
+        {
+            if !config.fee_tiers.contains(&fee_rate) {
+                return Err(ContractError::FeeTierPending {
+                    fee_rate,
+                    change_id,
+                });
+            }
+        }
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    let tiers: Vec<String> = config
+        .fee_tiers
+        .iter()
+        .map(|tier| tier.to_string())
+        .collect();
+
+    Ok(Response::new()
+        .add_attribute("method", "update_fee_tiers")
+        .add_attribute("fee_tiers", tiers.join(",")))
+}
+
+// Queues moving a pool to another fee tier
+pub fn execute_update_pool_fee(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
+    fee_rate: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    assert_fee_tier(&config, fee_rate)?;
+
+    let pool = POOLS
+        .may_load(deps.storage, pool_key(&token_a, &token_b))?
+        .ok_or(ContractError::PoolNotFound {})?;
+
+    let change = FeeChange::PoolFee {
+        token_a: pool.token_a.clone(),
+        token_b: pool.token_b.clone(),
+        fee_rate,
+    };
+    let pending = queue_fee_change(deps.storage, &env, &config, change)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_pool_fee")
+        .add_attribute("token_a", pool.token_a.to_string())
+        .add_attribute("token_b", pool.token_b.to_string())
+        .add_attribute("fee_rate", fee_rate)
+        .add_attribute("change_id", pending.change_id.to_string())
+        .add_attribute("effective_at", pending.effective_at.to_string()))
+}
+
+// Applies a queued fee change once its delay has passed. Anyone may apply it, since
+// the change was announced when it was queued.
+pub fn execute_apply_fee_change(
+    deps: DepsMut,
+    env: Env,
+    change_id: u64,
+) -> Result<Response, ContractError> {
+    let pending = PENDING_FEE_CHANGES
+        .may_load(deps.storage, change_id)?
+        .ok_or(ContractError::FeeChangeNotFound { change_id })?;
+
+    if env.block.time.seconds() < pending.effective_at {
+        return Err(ContractError::FeeChangeNotReady {
+            effective_at: pending.effective_at,
+        });
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+
+    // Tiers may have changed since the change was queued
+    match &pending.change {
+        FeeChange::FeeRate { fee_rate } => {
+            assert_fee_tier(&config, *fee_rate)?;
+            config.fee_rate = *fee_rate;
+            CONFIG.save(deps.storage, &config)?;
+        }
+        FeeChange::PoolFee {
+            token_a,
+            token_b,
+            fee_rate,
+        } => {
+            assert_fee_tier(&config, *fee_rate)?;
+            let key = pool_key(token_a, token_b);
+            let mut pool = POOLS
+                .may_load(deps.storage, key.clone())?
+                .ok_or(ContractError::PoolNotFound {})?;
+            pool.fee_rate = *fee_rate;
+            POOLS.save(deps.storage, key, &pool)?;
+        }
+        FeeChange::ProtocolFeeShare { protocol_fee_share } => {
+            config.protocol_fee_share = *protocol_fee_share;
+            CONFIG.save(deps.storage, &config)?;
+        }
+    }
+
+    PENDING_FEE_CHANGES.remove(deps.storage, change_id);
+
+    Ok(Response::new()
+        .add_attribute("method", "apply_fee_change")
+        .add_attribute("change_id", change_id.to_string()))
+}
+
+pub fn execute_cancel_fee_change(
+    deps: DepsMut,
+    info: MessageInfo,
+    change_id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !PENDING_FEE_CHANGES.has(deps.storage, change_id) {
+        return Err(ContractError::FeeChangeNotFound { change_id });
+    }
+    PENDING_FEE_CHANGES.remove(deps.storage, change_id);
+
+    Ok(Response::new()
+        .add_attribute("method", "cancel_fee_change")
+        .add_attribute("change_id", change_id.to_string()))
+}
+
+// Queues `change` to take effect after the fee change delay, replacing any pending
+// change to the same setting
+fn queue_fee_change(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &Config,
+    change: FeeChange,
+) -> StdResult<PendingFeeChange> {
+    let replaced = PENDING_FEE_CHANGES
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, pending)| pending.change.same_setting(&change))
+        })
+        .map(|item| item.map(|(change_id, _)| change_id))
+        .collect::<StdResult<Vec<u64>>>()?;
+    for change_id in replaced {
+        PENDING_FEE_CHANGES.remove(storage, change_id);
+    }
+
+    let change_id = FEE_CHANGE_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    FEE_CHANGE_COUNT.save(storage, &change_id)?;
+
+    let now = env.block.time.seconds();
+    let pending = PendingFeeChange {
+        change_id,
+        change,
+        queued_at: now,
+        effective_at: now + config.fee_change_delay,
+    };
+    PENDING_FEE_CHANGES.save(storage, change_id, &pending)?;
+
+    Ok(pending)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_ramp_amp(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
+    future_amp: u64,
+    future_time: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let key = pool_key(&token_a, &token_b);
+    let mut pool = POOLS
+        .may_load(deps.storage, key.clone())?
+        .ok_or(ContractError::PoolNotFound {})?;
+
+    let now = env.block.time.seconds();
+    let initial_amp = pool.amp(now).ok_or(ContractError::NotStablePool {})?;
+    assert_amp(future_amp)?;
+
+    // Ramps must last at least MIN_AMP_RAMP_TIME, start at least that long after the
+    // previous one and change amp by at most MAX_AMP_CHANGE times
+    let recently_ramped = pool
+        .amp_ramp
+        .as_ref()
+        .is_some_and(|ramp| now < ramp.initial_time + MIN_AMP_RAMP_TIME);
+    if recently_ramped
+        || future_time < now + MIN_AMP_RAMP_TIME
+        || future_amp > initial_amp * MAX_AMP_CHANGE
+        || future_amp * MAX_AMP_CHANGE < initial_amp
+    {
+        return Err(ContractError::InvalidAmpRamp {});
+    }
+
+    // Accumulate prices at the old amp before the curve starts to move
+    pool.accumulate_prices(now)?;
+    pool.pool_type = PoolType::Stable { amp: future_amp };
+    pool.amp_ramp = Some(AmpRamp {
+        initial_amp,
+        initial_time: now,
+        future_time,
+    });
+
+    save_pool(deps.storage, key, &pool)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "ramp_amp")
+        .add_attribute("initial_amp", initial_amp.to_string())
+        .add_attribute("future_amp", future_amp.to_string())
+        .add_attribute("future_time", future_time.to_string()))
+}
+
+pub fn execute_stop_ramp_amp(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let key = pool_key(&token_a, &token_b);
+    let mut pool = POOLS
+        .may_load(deps.storage, key.clone())?
+        .ok_or(ContractError::PoolNotFound {})?;
+
+    let now = env.block.time.seconds();
+    let amp = pool.amp(now).ok_or(ContractError::NotStablePool {})?;
+
+    pool.accumulate_prices(now)?;
+    pool.pool_type = PoolType::Stable { amp };
+    pool.amp_ramp = None;
+
+    save_pool(deps.storage, key, &pool)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "stop_ramp_amp")
+        .add_attribute("amp", amp.to_string()))
+}
+
+// Updates the treasury right away and queues a new protocol fee share
+pub fn execute_update_protocol_fee(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    protocol_fee_share: Option<Uint128>,
+    treasury: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(treasury) = treasury {
+        config.treasury = deps.api.addr_validate(&treasury)?;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    let mut response = Response::new()
+        .add_attribute("method", "update_protocol_fee")
+        .add_attribute("treasury", config.treasury.to_string());
+
+    if let Some(protocol_fee_share) = protocol_fee_share {
+        let change = FeeChange::ProtocolFeeShare {
+            protocol_fee_share: validate_protocol_fee_share(protocol_fee_share)?,
+        };
+        let pending = queue_fee_change(deps.storage, &env, &config, change)?;
+        response = response
+            .add_attribute("protocol_fee_share", protocol_fee_share)
+            .add_attribute("change_id", pending.change_id.to_string())
+            .add_attribute("effective_at", pending.effective_at.to_string());
+    }
+
+    Ok(response)
+}
+
+// Lists, delists or removes assets from the asset list (admin only)
+pub fn execute_update_asset_list(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    list: Vec<AssetInfo>,
+    delist: Vec<AssetInfo>,
+    remove: Vec<AssetInfo>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Each asset may only appear once
+    let mut keys: Vec<String> = list
+        .iter()
+        .chain(&delist)
+        .chain(&remove)
+        .map(|asset| asset.to_string())
+        .collect();
+    keys.sort_unstable();
+    if let Some(pair) = keys.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(ContractError::InvalidAsset {
+            asset: pair[0].clone(),
+        });
+    }
+
+    let now = env.block.time.seconds();
+    for (assets, status) in [
+        (&list, ListingStatus::Listed),
+        (&delist, ListingStatus::Delisted),
+    ] {
+        for asset in assets {
+            if let AssetInfo::Cw20 { contract_addr } = asset {
+                deps.api.addr_validate(contract_addr.as_str())?;
+            }
+            let listing = AssetListing {
+                asset: asset.clone(),
+                status: status.clone(),
+                updated_at: now,
+            };
+            ASSET_LIST.save(deps.storage, asset.to_string(), &listing)?;
+        }
+    }
+    for asset in &remove {
+        ASSET_LIST.remove(deps.storage, asset.to_string());
+    }
+
+    let mut response = Response::new().add_attribute("method", "update_asset_list");
+    for (key, assets) in [
+        ("listed", &list),
+        ("delisted", &delist),
+        ("removed", &remove),
+    ] {
+        if !assets.is_empty() {
+            let names: Vec<String> = assets.iter().map(|asset| asset.to_string()).collect();
+            response = response.add_attribute(key, names.join(","));
+        }
+    }
+
+    Ok(response)
+}
+
+// Switches between permissionless, allow-list and deny-list modes and whether the
+// asset list also applies to swaps
+pub fn execute_update_asset_list_mode(
+    deps: DepsMut,
+    info: MessageInfo,
+    mode: Option<AssetListMode>,
+    restrict_swaps: Option<bool>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(mode) = mode {
+        config.asset_list_mode = mode;
+    }
+    if let Some(restrict_swaps) = restrict_swaps {
+        config.restrict_swaps = restrict_swaps;
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_asset_list_mode")
+        .add_attribute("mode", config.asset_list_mode.to_string())
+        .add_attribute("restrict_swaps", config.restrict_swaps.to_string()))
+}
+
+// Sets or, with None, waives the fee charged for creating a pool
+pub fn execute_update_pool_creation_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    pool_creation_fee: Option<PoolCreationFeeParams>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.pool_creation_fee = validate_pool_creation_fee(deps.api, pool_creation_fee)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    let (fee, collector) = match &config.pool_creation_fee {
+        Some(creation_fee) => (
+            creation_fee.fee.to_string(),
+            creation_fee
+                .collector
+                .as_ref()
+                .map_or("burn".to_string(), |collector| collector.to_string()),
+        ),
+        None => ("none".to_string(), "none".to_string()),
+    };
+
+    Ok(Response::new()
+        .add_attribute("method", "update_pool_creation_fee")
+        .add_attribute("pool_creation_fee", fee)
+        .add_attribute("fee_collector", collector))
+}
+
+// Replaces the native denoms and/or denom prefixes new pools may use. Existing pools
+// are not affected.
+pub fn execute_update_native_denoms(
+    deps: DepsMut,
+    info: MessageInfo,
+    native_denoms: Option<Vec<String>>,
+    native_denom_prefixes: Option<Vec<String>>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(native_denoms) = native_denoms {
+        config.native_denoms = validate_native_denoms(native_denoms)?;
+    }
+    if let Some(native_denom_prefixes) = native_denom_prefixes {
+        config.native_denom_prefixes = validate_denom_prefixes(native_denom_prefixes)?;
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_native_denoms")
+        .add_attribute("native_denoms", config.native_denoms.join(","))
+        .add_attribute(
+            "native_denom_prefixes",
+            config.native_denom_prefixes.join(","),
+        ))
+}
+
+// Sends the accumulated protocol fees for `tokens` (all tokens if None) to the treasury
+pub fn execute_collect_protocol_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+    tokens: Option<Vec<AssetInfo>>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) && info.sender != config.treasury {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let fees: Vec<ProtocolFee> = match tokens {
+        Some(tokens) => tokens
+            .into_iter()
+            .filter_map(|token| {
+                PROTOCOL_FEES
+                    .may_load(deps.storage, token.to_string())
+                    .transpose()
+            })
+            .collect::<StdResult<_>>()?,
+        None => PROTOCOL_FEES
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, fee)| fee))
+            .collect::<StdResult<_>>()?,
+    };
+
+    let mut messages = vec![];
+    for fee in fees.iter().filter(|fee| !fee.amount.is_zero()) {
+        PROTOCOL_FEES.remove(deps.storage, fee.asset.to_string());
+        messages.push(transfer_msg(&fee.asset, &config.treasury, fee.amount)?);
+    }
+
+    let collected: Vec<String> = fees
+        .iter()
+        .filter(|fee| !fee.amount.is_zero())
+        .map(|fee| format!("{}{}", fee.amount, fee.asset))
+        .collect();
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "collect_protocol_fees")
+        .add_attribute("treasury", config.treasury)
+        .add_attribute("collected", collected.join(",")))
+}
+
+// Pauses the whole contract or one pool; pausing again replaces the reason
+pub fn execute_pause(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pool: Option<PoolId>,
+    reason: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.pauser && !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if reason.is_empty() || reason.len() > MAX_PAUSE_REASON_LEN {
+        return Err(ContractError::InvalidPauseReason {
+            max_len: MAX_PAUSE_REASON_LEN,
+        });
+    }
+
+    let pause = PauseInfo {
+        reason: reason.clone(),
+        paused_by: info.sender,
+        paused_at: env.block.time.seconds(),
+    };
+
+    match &pool {
+        None => PAUSE.save(deps.storage, &pause)?,
+        Some(PoolId::Pair { token_a, token_b }) => {
+            let key = pool_key(token_a, token_b);
+            if !POOLS.has(deps.storage, key.clone()) {
+                return Err(ContractError::PoolNotFound {});
+            }
+            PAUSED_PAIRS.save(deps.storage, key, &pause)?;
+        }
+        Some(PoolId::Weighted { pool_id }) => {
+            load_weighted_pool(deps.storage, *pool_id)?;
+            PAUSED_WEIGHTED_POOLS.save(deps.storage, *pool_id, &pause)?;
+        }
+        Some(PoolId::Concentrated { pool_id }) => {
+            load_concentrated_pool(deps.storage, *pool_id)?;
+            PAUSED_CONCENTRATED_POOLS.save(deps.storage, *pool_id, &pause)?;
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "pause")
+        .add_attribute("pool", pause_scope(&pool))
+        .add_attribute("reason", reason))
+}
+
+pub fn execute_unpause(
+    deps: DepsMut,
+    info: MessageInfo,
+    pool: Option<PoolId>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.pauser && !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let paused = match &pool {
+        None => PAUSE.may_load(deps.storage)?,
+        Some(pool) => pool_pause(deps.storage, pool)?,
+    };
+    if paused.is_none() {
+        return Err(ContractError::NotPaused {});
+    }
+
+    match &pool {
+        None => PAUSE.remove(deps.storage),
+        Some(PoolId::Pair { token_a, token_b }) => {
+            PAUSED_PAIRS.remove(deps.storage, pool_key(token_a, token_b))
+        }
+        Some(PoolId::Weighted { pool_id }) => PAUSED_WEIGHTED_POOLS.remove(deps.storage, *pool_id),
+        Some(PoolId::Concentrated { pool_id }) => {
+            PAUSED_CONCENTRATED_POOLS.remove(deps.storage, *pool_id)
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "unpause")
+        .add_attribute("pool", pause_scope(&pool)))
+}
+
+pub fn execute_update_pauser(
+    deps: DepsMut,
+    info: MessageInfo,
+    pauser: String,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.pauser = deps.api.addr_validate(&pauser)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_pauser")
+        .add_attribute("new_pauser", pauser))
+}
+
+pub fn execute_update_lp_token_code_id(
+    deps: DepsMut,
+    info: MessageInfo,
+    lp_token_code_id: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    // Only admin can update LP token code ID
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.lp_token_code_id = lp_token_code_id;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_lp_token_code_id")
         .add_attribute("lp_token_code_id", lp_token_code_id.to_string()))
-}
\ No newline at end of file
+}
+
+// Sorts and dedupes fee tiers, rejecting an empty set or rates of 100% and above
+pub fn validate_fee_tiers(
+    mut fee_tiers: Vec<Uint128>,
+    max_fee_rate: Uint128,
+) -> Result<Vec<Uint128>, ContractError> {
+    fee_tiers.sort();
+    fee_tiers.dedup();
+
+    if fee_tiers.is_empty() {
+        return Err(ContractError::InvalidFeeTier {
+            fee_rate: Uint128::zero(),
+        });
+    }
+
+    if let Some(fee_rate) = fee_tiers.iter().find(|tier| **tier > max_fee_rate) {
+        return Err(ContractError::FeeRateTooHigh {
+            fee_rate: *fee_rate,
+            max_fee_rate,
+        });
+    }
+
+    Ok(fee_tiers)
+}
+
+pub fn validate_fee_change_delay(fee_change_delay: u64) -> Result<u64, ContractError> {
+    if fee_change_delay < MIN_FEE_CHANGE_DELAY {
+        return Err(ContractError::InvalidFeeChangeDelay {
+            min: MIN_FEE_CHANGE_DELAY,
+        });
+    }
+    Ok(fee_change_delay)
+}
+
+pub fn validate_max_fee_rate(max_fee_rate: Uint128) -> Result<Uint128, ContractError> {
+    if max_fee_rate.u128() >= FEE_DENOMINATOR {
+        return Err(ContractError::InvalidMaxFeeRate { max_fee_rate });
+    }
+    Ok(max_fee_rate)
+}
+
+// Sorted, deduplicated native denoms, each well-formed
+pub fn validate_native_denoms(mut denoms: Vec<String>) -> Result<Vec<String>, ContractError> {
+    denoms.sort();
+    denoms.dedup();
+
+    if let Some(denom) = denoms.iter().find(|denom| !is_valid_denom(denom)) {
+        return Err(ContractError::InvalidAsset {
+            asset: denom.clone(),
+        });
+    }
+
+    Ok(denoms)
+}
+
+// Sorted, deduplicated denom prefixes, each a non-empty start of a well-formed denom
+pub fn validate_denom_prefixes(mut prefixes: Vec<String>) -> Result<Vec<String>, ContractError> {
+    prefixes.sort();
+    prefixes.dedup();
+
+    if let Some(prefix) = prefixes.iter().find(|prefix| !is_denom_start(prefix)) {
+        return Err(ContractError::InvalidAsset {
+            asset: prefix.clone(),
+        });
+    }
+
+    Ok(prefixes)
+}
+
+// Bank denom format of the Cosmos SDK: a letter followed by 2 to 127 letters, digits or /:._-
+fn is_valid_denom(denom: &str) -> bool {
+    (3..=128).contains(&denom.len()) && is_denom_start(denom)
+}
+
+fn is_denom_start(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic())
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c))
+}
+
+// Fails while the whole contract, or the given pool, is paused
+fn assert_not_paused(storage: &dyn Storage, pool: Option<&PoolId>) -> Result<(), ContractError> {
+    if let Some(pause) = PAUSE.may_load(storage)? {
+        return Err(ContractError::Paused {
+            reason: pause.reason,
+        });
+    }
+
+    if let Some(pause) = pool
+        .map(|pool| pool_pause(storage, pool))
+        .transpose()?
+        .flatten()
+    {
+        return Err(ContractError::PoolPaused {
+            reason: pause.reason,
+        });
+    }
+
+    Ok(())
+}
+
+// Pool attribute of pause events
+fn pause_scope(pool: &Option<PoolId>) -> String {
+    match pool {
+        None => "all".to_string(),
+        Some(PoolId::Pair { token_a, token_b }) => format!("{}-{}", token_a, token_b),
+        Some(PoolId::Weighted { pool_id }) => format!("weighted-{}", pool_id),
+        Some(PoolId::Concentrated { pool_id }) => format!("concentrated-{}", pool_id),
+    }
+}
+
+pub fn assert_fee_tier(config: &Config, fee_rate: Uint128) -> Result<(), ContractError> {
+    if !config.fee_tiers.contains(&fee_rate) {
+        return Err(ContractError::InvalidFeeTier { fee_rate });
+    }
+    Ok(())
+}
+
+pub fn validate_protocol_fee_share(protocol_fee_share: Uint128) -> Result<Uint128, ContractError> {
+    if protocol_fee_share.u128() > FEE_DENOMINATOR {
+        return Err(ContractError::InvalidProtocolFeeShare { protocol_fee_share });
+    }
+    Ok(protocol_fee_share)
+}
+
+pub fn assert_amp(amp: u64) -> Result<(), ContractError> {
+    if !(MIN_AMP..=MAX_AMP).contains(&amp) {
+        return Err(ContractError::InvalidAmp { amp });
+    }
+    Ok(())
+}
+
+fn pool_type_name(pool_type: &PoolType) -> &'static str {
+    match pool_type {
+        PoolType::ConstantProduct {} => "constant_product",
+        PoolType::Stable { .. } => "stable",
+    }
+}
+
+// Saves a pool whose reserves changed and records its cumulative prices for the TWAP.
+// Observations older than MAX_TWAP_WINDOW are dropped, except the newest of them which
+// still bounds the longest window.
+pub fn save_pool(storage: &mut dyn Storage, key: (String, String), pool: &Pool) -> StdResult<()> {
+    POOLS.save(storage, key.clone(), pool)?;
+
+    let (token_a, token_b) = key;
+    OBSERVATIONS.save(
+        storage,
+        (token_a.clone(), token_b.clone(), pool.block_time_last),
+        &Observation {
+            price_a_cumulative: pool.price_a_cumulative,
+            price_b_cumulative: pool.price_b_cumulative,
+        },
+    )?;
+
+    let cutoff = pool.block_time_last.saturating_sub(MAX_TWAP_WINDOW);
+    let stale = OBSERVATIONS
+        .prefix((token_a.clone(), token_b.clone()))
+        .keys(
+            storage,
+            None,
+            Some(Bound::exclusive(cutoff)),
+            Order::Descending,
+        )
+        .skip(1)
+        .collect::<StdResult<Vec<u64>>>()?;
+    for time in stale {
+        OBSERVATIONS.remove(storage, (token_a.clone(), token_b.clone(), time));
+    }
+
+    Ok(())
+}
+
+// Adds the protocol's cut of a swap fee to the amount awaiting collection
+fn accrue_protocol_fee(
+    storage: &mut dyn Storage,
+    token: &AssetInfo,
+    amount: Uint128,
+) -> StdResult<()> {
+    if amount.is_zero() {
+        return Ok(());
+    }
+    PROTOCOL_FEES.update(storage, token.to_string(), |fees| -> StdResult<_> {
+        Ok(ProtocolFee {
+            asset: token.clone(),
+            amount: fees.map(|fees| fees.amount).unwrap_or_default() + amount,
+        })
+    })?;
+    Ok(())
+}
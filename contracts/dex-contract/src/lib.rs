@@ -1,11 +1,14 @@
 pub mod contract;
 pub mod error;
-pub mod msg;
-pub mod state;
 pub mod execute;
+pub mod math;
+pub mod migrations;
+pub mod msg;
 pub mod query;
+pub mod staking;
+pub mod state;
 
 pub use crate::error::ContractError;
 
 // Re-export contract entry points for the optimizer
-pub use crate::contract::{instantiate, execute, query};
\ No newline at end of file
+pub use crate::contract::{execute, instantiate, migrate, query, reply};
@@ -0,0 +1,1408 @@
+use cosmwasm_std::{Decimal256, Isqrt, StdError, StdResult, Uint128, Uint256, Uint512};
+
+// Fee rates are expressed in basis points
+pub const FEE_DENOMINATOR: u128 = 10000;
+
+// Newton iterations allowed for the StableSwap invariant before giving up
+const STABLE_MAX_ITERATIONS: usize = 64;
+
+// Number of assets in a StableSwap pool (n in the Curve paper)
+const STABLE_N_COINS: u128 = 2;
+
+// Largest share of a weighted pool balance a single swap, join or exit may move, as in
+// Balancer. It also keeps every base passed to pow_approx inside its (0, 2) range.
+const WEIGHTED_MAX_RATIO_PERCENT: u64 = 30;
+
+// Terms of the pow_approx series are summed until they drop below this
+const POW_PRECISION: u128 = 100_000_000; // 1e-10 in Decimal256 atomics
+const POW_MAX_ITERATIONS: u64 = 300;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapResult {
+    pub amount_out: Uint128,
+    pub fee: Uint128,
+    pub protocol_fee: Uint128, // Part of fee that is kept out of the reserves
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReverseSwapResult {
+    pub amount_in: Uint128,
+    pub fee: Uint128,
+    pub protocol_fee: Uint128, // Part of fee that is kept out of the reserves
+}
+
+// Constant product swap with the fee taken from the input:
+// amount_in_after_fee = amount_in * (10000 - fee_rate) / 10000
+// amount_out = amount_in_after_fee * reserve_out / (reserve_in + amount_in_after_fee)
+// protocol_fee = fee * protocol_fee_share / 10000
+pub fn compute_swap(
+    reserve_in: Uint128,
+    reserve_out: Uint128,
+    amount_in: Uint128,
+    fee_rate: Uint128,
+    protocol_fee_share: Uint128,
+) -> StdResult<SwapResult> {
+    let fee_denominator = Uint128::from(FEE_DENOMINATOR);
+    let amount_in_after_fee =
+        amount_in.multiply_ratio(fee_denominator.checked_sub(fee_rate)?, fee_denominator);
+
+    let amount_out = amount_in_after_fee
+        .multiply_ratio(reserve_out, reserve_in.checked_add(amount_in_after_fee)?);
+
+    let fee = amount_in - amount_in_after_fee;
+
+    Ok(SwapResult {
+        amount_out,
+        fee,
+        protocol_fee: fee.multiply_ratio(protocol_fee_share, fee_denominator),
+    })
+}
+
+// Inverse of compute_swap: the smallest amount_in for which compute_swap returns at
+// least amount_out. Both divisions round up so the pool never gives away value.
+// amount_in_after_fee = ceil(amount_out * reserve_in / (reserve_out - amount_out))
+// amount_in = ceil(amount_in_after_fee * 10000 / (10000 - fee_rate))
+pub fn compute_reverse_swap(
+    reserve_in: Uint128,
+    reserve_out: Uint128,
+    amount_out: Uint128,
+    fee_rate: Uint128,
+    protocol_fee_share: Uint128,
+) -> StdResult<ReverseSwapResult> {
+    if amount_out >= reserve_out {
+        return Err(StdError::generic_err("Insufficient pool liquidity"));
+    }
+
+    let fee_denominator = Uint128::from(FEE_DENOMINATOR);
+    let amount_in_after_fee =
+        ceil_multiply_ratio(amount_out, reserve_in, reserve_out - amount_out)?;
+    let amount_in = ceil_multiply_ratio(
+        amount_in_after_fee,
+        fee_denominator,
+        fee_denominator.checked_sub(fee_rate)?,
+    )?;
+
+    let fee = amount_in - amount_in_after_fee;
+
+    Ok(ReverseSwapResult {
+        amount_in,
+        fee,
+        protocol_fee: fee.multiply_ratio(protocol_fee_share, fee_denominator),
+    })
+}
+
+// Largest constant product input after which the marginal output, after the fee, is still
+// at least output_price per unit of input. With g = 1 - fee, c = 1 - fee * protocol_fee_share
+// (the share of the input left in the reserves) and u = amount_in / reserve_in, the marginal
+// output after the swap is g * reserve_out / (reserve_in * (1 + g * u) * (1 + c * u)), so
+// u is the positive root of g * c * u^2 + (g + c) * u + 1 - tau with
+// tau = g * reserve_out / (reserve_in * output_price).
+pub fn compute_amount_in_to_price(
+    reserve_in: Uint128,
+    reserve_out: Uint128,
+    output_price: Decimal256,
+    fee_rate: Uint128,
+    protocol_fee_share: Uint128,
+) -> StdResult<Uint128> {
+    let fee = Decimal256::from_ratio(fee_rate, FEE_DENOMINATOR);
+    let g = Decimal256::one().checked_sub(fee)?;
+    let c = Decimal256::one() - fee * Decimal256::from_ratio(protocol_fee_share, FEE_DENOMINATOR);
+    if g.is_zero() || output_price.is_zero() || reserve_in.is_zero() {
+        return Ok(Uint128::zero());
+    }
+
+    let tau = g * Decimal256::from_ratio(reserve_out, reserve_in) / output_price;
+    if tau <= Decimal256::one() {
+        return Ok(Uint128::zero());
+    }
+
+    let (low, high) = if g > c { (c, g) } else { (g, c) };
+    let spread = high - low;
+    let root = (spread * spread + Decimal256::from_ratio(4u8, 1u8) * g * c * tau).sqrt();
+    let u = (root - (g + c)) / (Decimal256::from_ratio(2u8, 1u8) * g * c);
+
+    let amount_in = (Decimal256::from_ratio(reserve_in, 1u8) * u).to_uint_floor();
+    Ok(Uint128::try_from(amount_in)?)
+}
+
+// Part of amount_in to swap in a constant product pool so that the rest and the swap
+// output match the reserves after the swap, with the fee taken from the input:
+// swap = (sqrt((r * (F + g))^2 + 4 * g * F * r * amount_in) - r * (F + g)) / (2 * g)
+// where r = reserve_in, F = 10000 and g = 10000 - fee_rate
+pub fn compute_zap_swap_amount(
+    reserve_in: Uint128,
+    amount_in: Uint128,
+    fee_rate: Uint128,
+) -> StdResult<Uint128> {
+    let fee_denominator = Uint512::from(FEE_DENOMINATOR);
+    let g = Uint512::from(Uint128::from(FEE_DENOMINATOR).checked_sub(fee_rate)?);
+    if g.is_zero() {
+        return Err(StdError::generic_err("Division by zero"));
+    }
+
+    let r = Uint512::from(reserve_in);
+    let b = r * (fee_denominator + g);
+    let root =
+        (b * b + Uint512::from(4u128) * g * fee_denominator * r * Uint512::from(amount_in)).isqrt();
+    let amount = (root - b) / (Uint512::from(2u128) * g);
+
+    Ok(Uint128::try_from(amount)?.min(amount_in))
+}
+
+// value * numerator / denominator, rounded up
+pub fn ceil_multiply_ratio(
+    value: Uint128,
+    numerator: Uint128,
+    denominator: Uint128,
+) -> StdResult<Uint128> {
+    if denominator.is_zero() {
+        return Err(StdError::generic_err("Division by zero"));
+    }
+
+    let denominator = Uint256::from(denominator);
+    let result = (value.full_mul(numerator) + denominator - Uint256::one()) / denominator;
+
+    Ok(Uint128::try_from(result)?)
+}
+
+// StableSwap swap with the fee taken from the input, as in compute_swap:
+// amount_out = reserve_out - y(reserve_in + amount_in_after_fee) - 1
+// where y(x) keeps the invariant D unchanged. The extra unit rounds in the pool's favour.
+pub fn compute_stable_swap(
+    reserve_in: Uint128,
+    reserve_out: Uint128,
+    amount_in: Uint128,
+    fee_rate: Uint128,
+    protocol_fee_share: Uint128,
+    amp: u64,
+) -> StdResult<SwapResult> {
+    let fee_denominator = Uint128::from(FEE_DENOMINATOR);
+    let amount_in_after_fee =
+        amount_in.multiply_ratio(fee_denominator.checked_sub(fee_rate)?, fee_denominator);
+
+    let d = compute_d(amp, reserve_in, reserve_out)?;
+    let new_reserve_out = compute_y(amp, reserve_in.checked_add(amount_in_after_fee)?, d)?;
+    let amount_out = reserve_out
+        .saturating_sub(new_reserve_out)
+        .saturating_sub(Uint128::one());
+
+    let fee = amount_in - amount_in_after_fee;
+
+    Ok(SwapResult {
+        amount_out,
+        fee,
+        protocol_fee: fee.multiply_ratio(protocol_fee_share, fee_denominator),
+    })
+}
+
+// Inverse of compute_stable_swap, rounded up like compute_reverse_swap:
+// amount_in_after_fee = y(reserve_out - amount_out) - reserve_in + 1
+// amount_in = ceil(amount_in_after_fee * 10000 / (10000 - fee_rate))
+pub fn compute_stable_reverse_swap(
+    reserve_in: Uint128,
+    reserve_out: Uint128,
+    amount_out: Uint128,
+    fee_rate: Uint128,
+    protocol_fee_share: Uint128,
+    amp: u64,
+) -> StdResult<ReverseSwapResult> {
+    if amount_out >= reserve_out {
+        return Err(StdError::generic_err("Insufficient pool liquidity"));
+    }
+
+    let fee_denominator = Uint128::from(FEE_DENOMINATOR);
+    let d = compute_d(amp, reserve_in, reserve_out)?;
+    let new_reserve_in = compute_y(amp, reserve_out - amount_out, d)?;
+    let amount_in_after_fee = new_reserve_in.checked_sub(reserve_in)? + Uint128::one();
+    let amount_in = ceil_multiply_ratio(
+        amount_in_after_fee,
+        fee_denominator,
+        fee_denominator.checked_sub(fee_rate)?,
+    )?;
+
+    let fee = amount_in - amount_in_after_fee;
+
+    Ok(ReverseSwapResult {
+        amount_in,
+        fee,
+        protocol_fee: fee.multiply_ratio(protocol_fee_share, fee_denominator),
+    })
+}
+
+// Shares minted for a StableSwap deposit, following Curve's add_liquidity:
+// the part of each deposit that moves the pool away from its current balance is
+// charged fee_rate / 2, so an imbalanced deposit followed by a proportional
+// withdrawal costs about as much as the equivalent swap.
+// liquidity = total_liquidity * (D(balances after fees) - D0) / D0
+pub fn compute_stable_deposit(
+    reserves: [Uint128; 2],
+    deposits: [Uint128; 2],
+    total_liquidity: Uint128,
+    fee_rate: Uint128,
+    amp: u64,
+) -> StdResult<Uint128> {
+    let d0 = compute_d(amp, reserves[0], reserves[1])?;
+    if d0.is_zero() {
+        return Err(StdError::generic_err("Insufficient pool liquidity"));
+    }
+
+    let new_reserves = [
+        reserves[0].checked_add(deposits[0])?,
+        reserves[1].checked_add(deposits[1])?,
+    ];
+    let d1 = compute_d(amp, new_reserves[0], new_reserves[1])?;
+
+    if d1 <= d0 {
+        return Ok(Uint128::zero());
+    }
+
+    // fee * n / (4 * (n - 1)) is fee / 2 for two coins
+    let imbalance_fee_denominator = Uint128::from(FEE_DENOMINATOR * 2);
+    let mut adjusted = new_reserves;
+    for i in 0..2 {
+        let ideal = Uint128::try_from(d1.full_mul(reserves[i]) / Uint256::from(d0))?;
+        adjusted[i] -= ideal
+            .abs_diff(new_reserves[i])
+            .multiply_ratio(fee_rate, imbalance_fee_denominator);
+    }
+    let d2 = compute_d(amp, adjusted[0], adjusted[1])?;
+
+    Ok(total_liquidity.multiply_ratio(d2.saturating_sub(d0), d0))
+}
+
+// Marginal price of token x in units of token y on the StableSwap curve, from the
+// partial derivatives of the invariant:
+// price = (4 * Ann * x * y + D^3 / x) / (4 * Ann * x * y + D^3 / y)
+pub fn compute_stable_price(amp: u64, x: Uint128, y: Uint128) -> StdResult<Decimal256> {
+    let d = Uint512::from(compute_d(amp, x, y)?);
+    let d_cubed = d * d * d;
+    let ann_xy =
+        Uint512::from(ann(amp)) * Uint512::from(4u128) * Uint512::from(x) * Uint512::from(y);
+
+    let numerator = ann_xy + d_cubed / Uint512::from(x);
+    let denominator = ann_xy + d_cubed / Uint512::from(y);
+    let atomics = numerator * Uint512::from(Decimal256::one().atomics()) / denominator;
+
+    Ok(Decimal256::new(Uint256::try_from(atomics)?))
+}
+
+// StableSwap invariant D for two reserves, found with Newton's method:
+// Ann * (x + y) + D = Ann * D + D^3 / (4 * x * y), where Ann = amp * n^n
+pub fn compute_d(amp: u64, x: Uint128, y: Uint128) -> StdResult<Uint128> {
+    if x.is_zero() || y.is_zero() {
+        return Ok(Uint128::zero());
+    }
+
+    let sum = Uint256::from(x) + Uint256::from(y);
+
+    let n = Uint256::from(STABLE_N_COINS);
+    let ann = Uint256::from(ann(amp));
+    let mut d = sum;
+
+    for _ in 0..STABLE_MAX_ITERATIONS {
+        let mut d_product = d;
+        for reserve in [x, y] {
+            d_product = d_product.checked_mul(d)? / (Uint256::from(reserve) * n);
+        }
+
+        let previous = d;
+        let numerator = (ann * sum + d_product * n).checked_mul(d)?;
+        let denominator = (ann - Uint256::one()) * d + (n + Uint256::one()) * d_product;
+        d = numerator / denominator;
+
+        if d.abs_diff(previous) <= Uint256::one() {
+            return Ok(Uint128::try_from(d)?);
+        }
+    }
+
+    Err(StdError::generic_err(
+        "StableSwap invariant did not converge",
+    ))
+}
+
+// The other reserve y for which (x, y) has invariant D, found with Newton's method
+pub fn compute_y(amp: u64, x: Uint128, d: Uint128) -> StdResult<Uint128> {
+    if x.is_zero() {
+        return Err(StdError::generic_err("Insufficient pool liquidity"));
+    }
+
+    let n = Uint256::from(STABLE_N_COINS);
+    let ann = Uint256::from(ann(amp));
+    let x = Uint256::from(x);
+    let d = Uint256::from(d);
+
+    // c = D^3 / (n^n * x * Ann), b = x + D / Ann
+    let c = d.checked_mul(d)? / (x * n);
+    let c = c.checked_mul(d)? / (ann * n);
+    let b = x + d / ann;
+    let mut y = d;
+
+    for _ in 0..STABLE_MAX_ITERATIONS {
+        let previous = y;
+        let denominator = (y * Uint256::from(2u128) + b)
+            .checked_sub(d)
+            .map_err(|_| StdError::generic_err("Insufficient pool liquidity"))?;
+        y = (y.checked_mul(y)? + c) / denominator;
+
+        if y.abs_diff(previous) <= Uint256::one() {
+            return Ok(Uint128::try_from(y)?);
+        }
+    }
+
+    Err(StdError::generic_err(
+        "StableSwap invariant did not converge",
+    ))
+}
+
+// Amplification scaled by n^n as used in the invariant
+fn ann(amp: u64) -> u128 {
+    amp as u128 * STABLE_N_COINS.pow(STABLE_N_COINS as u32)
+}
+
+// Balancer swap with the fee taken from the input:
+// amount_out = balance_out * (1 - (balance_in / (balance_in + amount_in_after_fee)) ^ (weight_in / weight_out))
+#[allow(clippy::too_many_arguments)]
+pub fn compute_weighted_swap(
+    balance_in: Uint128,
+    weight_in: Decimal256,
+    balance_out: Uint128,
+    weight_out: Decimal256,
+    amount_in: Uint128,
+    fee_rate: Uint128,
+    protocol_fee_share: Uint128,
+) -> StdResult<SwapResult> {
+    assert_max_ratio(amount_in, balance_in)?;
+
+    let fee_denominator = Uint128::from(FEE_DENOMINATOR);
+    let amount_in_after_fee =
+        amount_in.multiply_ratio(fee_denominator.checked_sub(fee_rate)?, fee_denominator);
+
+    let base = Decimal256::from_ratio(balance_in, balance_in.checked_add(amount_in_after_fee)?);
+    let power = pow_approx(base, weight_in / weight_out)?;
+    let amount_out = mul_floor(balance_out, Decimal256::one() - power)?;
+
+    let fee = amount_in - amount_in_after_fee;
+
+    Ok(SwapResult {
+        amount_out,
+        fee,
+        protocol_fee: fee.multiply_ratio(protocol_fee_share, fee_denominator),
+    })
+}
+
+// Shares minted for depositing amount_in of a single weighted pool asset. The part of
+// the deposit that is effectively swapped into the other assets, amount_in * (1 - weight),
+// pays the swap fee:
+// liquidity = total_liquidity * (((balance + amount_in_after_fee) / balance) ^ weight - 1)
+pub fn compute_weighted_single_join(
+    balance: Uint128,
+    weight: Decimal256,
+    total_liquidity: Uint128,
+    amount_in: Uint128,
+    fee_rate: Uint128,
+) -> StdResult<Uint128> {
+    assert_max_ratio(amount_in, balance)?;
+
+    let fee = fee_fraction(fee_rate);
+    let fee_amount = mul_floor(amount_in, (Decimal256::one() - weight) * fee)?;
+    let amount_in_after_fee = amount_in - fee_amount;
+
+    let balance_ratio = Decimal256::from_ratio(balance.checked_add(amount_in_after_fee)?, balance);
+    let invariant_ratio = pow_approx(balance_ratio, weight)?;
+
+    mul_floor(total_liquidity, invariant_ratio - Decimal256::one())
+}
+
+// Amount of a single weighted pool asset paid out for burning `liquidity` shares, with the
+// swap fee charged on the part taken from the other assets, amount * (1 - weight):
+// amount = balance * (1 - ((total_liquidity - liquidity) / total_liquidity) ^ (1 / weight))
+pub fn compute_weighted_single_exit(
+    balance: Uint128,
+    weight: Decimal256,
+    total_liquidity: Uint128,
+    liquidity: Uint128,
+    fee_rate: Uint128,
+) -> StdResult<Uint128> {
+    assert_max_ratio(liquidity, total_liquidity)?;
+
+    let invariant_ratio = Decimal256::from_ratio(total_liquidity - liquidity, total_liquidity);
+    let balance_ratio = pow_approx(invariant_ratio, Decimal256::one() / weight)?;
+    let amount_out = mul_floor(balance, Decimal256::one() - balance_ratio)?;
+
+    let fee = fee_fraction(fee_rate);
+    let fee_amount = mul_floor(amount_out, (Decimal256::one() - weight) * fee)?;
+
+    Ok(amount_out - fee_amount)
+}
+
+// Balancer's invariant, the weighted geometric mean of the reserves, used as the initial
+// share supply of a weighted pool: V = prod(reserve_i ^ weight_i) for weights summing to 1.
+// Each reserve is taken relative to the largest, V = largest * prod(ratio_i ^ weight_i),
+// and ratios are halved down into (0.5, 1] so pow_approx's series converges quickly:
+// ratio ^ weight = m ^ weight * 0.5 ^ (halvings * weight).
+pub fn compute_weighted_invariant(assets: &[(Uint128, Decimal256)]) -> StdResult<Uint128> {
+    let largest = assets
+        .iter()
+        .map(|(reserve, _)| *reserve)
+        .max()
+        .unwrap_or_default();
+    if largest.is_zero() {
+        return Ok(Uint128::zero());
+    }
+
+    let half = Decimal256::percent(50);
+    let mut product = Decimal256::one();
+
+    for (reserve, weight) in assets {
+        let mut ratio = Decimal256::from_ratio(*reserve, largest);
+        if ratio.is_zero() {
+            return Err(StdError::generic_err("Pool reserves are too unbalanced"));
+        }
+
+        let mut halvings = 0u128;
+        while ratio <= half {
+            ratio *= Decimal256::percent(200);
+            halvings += 1;
+        }
+
+        let halvings = Decimal256::from_ratio(halvings, 1u128);
+        product *= pow_approx(ratio, *weight)? * pow_approx(half, halvings * *weight)?;
+    }
+
+    mul_floor(largest, product)
+}
+
+// base ^ exp for base in (0, 2). The integer part of exp is applied with checked_pow and
+// the fractional part with the binomial series of (1 + (base - 1)) ^ frac, as Balancer
+// and Osmosis do.
+pub fn pow_approx(base: Decimal256, exp: Decimal256) -> StdResult<Decimal256> {
+    let one = Decimal256::one();
+    if base.is_zero() || base >= Decimal256::percent(200) {
+        return Err(StdError::generic_err("Power base must be between 0 and 2"));
+    }
+
+    let fraction = exp - exp.floor();
+    let whole = u32::try_from(Uint128::try_from(exp.to_uint_floor())?.u128())
+        .map_err(|_| StdError::generic_err("Power exponent too large"))?;
+    let whole_power = base.checked_pow(whole)?;
+
+    if fraction.is_zero() {
+        return Ok(whole_power);
+    }
+
+    let (x, x_negative) = if base >= one {
+        (base - one, false)
+    } else {
+        (one - base, true)
+    };
+    let precision = Decimal256::new(Uint256::from(POW_PRECISION));
+
+    let mut term = one;
+    let mut sum = one;
+    let mut negative = false;
+
+    for k in 1..=POW_MAX_ITERATIONS {
+        if term < precision {
+            break;
+        }
+
+        let previous_k = Decimal256::from_ratio(k - 1, 1u64);
+        let (c, c_negative) = if fraction >= previous_k {
+            (fraction - previous_k, false)
+        } else {
+            (previous_k - fraction, true)
+        };
+
+        term = term * c * x / Decimal256::from_ratio(k, 1u64);
+        if term.is_zero() {
+            break;
+        }
+
+        if x_negative {
+            negative = !negative;
+        }
+        if c_negative {
+            negative = !negative;
+        }
+
+        sum = if negative {
+            sum.checked_sub(term)?
+        } else {
+            sum.checked_add(term)?
+        };
+    }
+
+    Ok(whole_power.checked_mul(sum)?)
+}
+
+fn assert_max_ratio(amount: Uint128, balance: Uint128) -> StdResult<()> {
+    if amount > balance.multiply_ratio(WEIGHTED_MAX_RATIO_PERCENT, 100u64) {
+        return Err(StdError::generic_err(format!(
+            "Amount exceeds {}% of the pool balance",
+            WEIGHTED_MAX_RATIO_PERCENT
+        )));
+    }
+    Ok(())
+}
+
+// Fee rate in basis points as a fraction
+fn fee_fraction(fee_rate: Uint128) -> Decimal256 {
+    Decimal256::from_ratio(fee_rate, FEE_DENOMINATOR)
+}
+
+// amount * fraction, rounded down
+fn mul_floor(amount: Uint128, fraction: Decimal256) -> StdResult<Uint128> {
+    let result = (Decimal256::from_ratio(amount, 1u64) * fraction).to_uint_floor();
+    Ok(Uint128::try_from(result)?)
+}
+
+// Tick range of concentrated liquidity pools. The price at a tick is 1.0001^tick, so the
+// range covers prices from about 4e-18 to 2.4e17 while square root prices keep at least
+// nine significant digits as a Decimal256.
+pub const MIN_TICK: i32 = -400_000;
+pub const MAX_TICK: i32 = 400_000;
+
+// sqrt(1.0001), the ratio between the square root prices of neighbouring ticks
+const SQRT_TICK_BASE: u128 = 1_000_049_998_750_062_496; // Decimal256 atomics
+
+// One step of a concentrated liquidity swap, within a range of constant liquidity
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapStep {
+    pub sqrt_price: Decimal256, // Square root price at the end of the step
+    pub amount_in: Uint128,     // Input excluding the fee
+    pub amount_out: Uint128,
+    pub fee: Uint128,
+}
+
+// Square root of the price 1.0001^tick
+pub fn sqrt_price_at_tick(tick: i32) -> StdResult<Decimal256> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return Err(StdError::generic_err(format!(
+            "Tick {} is out of range",
+            tick
+        )));
+    }
+
+    let sqrt_price =
+        Decimal256::new(Uint256::from(SQRT_TICK_BASE)).checked_pow(tick.unsigned_abs())?;
+    if tick < 0 {
+        Ok(Decimal256::one() / sqrt_price)
+    } else {
+        Ok(sqrt_price)
+    }
+}
+
+// Largest tick whose square root price is at most sqrt_price, found by binary search
+pub fn tick_at_sqrt_price(sqrt_price: Decimal256) -> StdResult<i32> {
+    if sqrt_price < sqrt_price_at_tick(MIN_TICK)? || sqrt_price > sqrt_price_at_tick(MAX_TICK)? {
+        return Err(StdError::generic_err("Price is outside the tick range"));
+    }
+
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if sqrt_price_at_tick(mid)? <= sqrt_price {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    Ok(low)
+}
+
+// Amount of token_a held by `liquidity` between two square root prices:
+// amount_a = liquidity * (sqrt_upper - sqrt_lower) / (sqrt_lower * sqrt_upper)
+pub fn amount_a_delta(
+    sqrt_price_1: Decimal256,
+    sqrt_price_2: Decimal256,
+    liquidity: Uint128,
+    round_up: bool,
+) -> StdResult<Uint128> {
+    let (lower, upper) = sorted_atomics(sqrt_price_1, sqrt_price_2);
+    if lower.is_zero() {
+        return Err(StdError::generic_err("Division by zero"));
+    }
+
+    let numerator = Uint256::from(liquidity).checked_mul(upper - lower)?;
+    let scaled = mul_div(numerator, Decimal256::one().atomics(), upper, round_up)?;
+    Ok(Uint128::try_from(mul_div(
+        scaled,
+        Uint256::one(),
+        lower,
+        round_up,
+    )?)?)
+}
+
+// Amount of token_b held by `liquidity` between two square root prices:
+// amount_b = liquidity * (sqrt_upper - sqrt_lower)
+pub fn amount_b_delta(
+    sqrt_price_1: Decimal256,
+    sqrt_price_2: Decimal256,
+    liquidity: Uint128,
+    round_up: bool,
+) -> StdResult<Uint128> {
+    let (lower, upper) = sorted_atomics(sqrt_price_1, sqrt_price_2);
+    let amount = mul_div(
+        Uint256::from(liquidity),
+        upper - lower,
+        Decimal256::one().atomics(),
+        round_up,
+    )?;
+    Ok(Uint128::try_from(amount)?)
+}
+
+// Liquidity of a position over [sqrt_lower, sqrt_upper] funded by at most amount_a and
+// amount_b at sqrt_price. Below the range only token_a is used, above it only token_b.
+pub fn liquidity_for_amounts(
+    sqrt_price: Decimal256,
+    sqrt_lower: Decimal256,
+    sqrt_upper: Decimal256,
+    amount_a: Uint128,
+    amount_b: Uint128,
+) -> StdResult<Uint128> {
+    if sqrt_price <= sqrt_lower {
+        liquidity_for_a(sqrt_lower, sqrt_upper, amount_a)
+    } else if sqrt_price < sqrt_upper {
+        Ok(liquidity_for_a(sqrt_price, sqrt_upper, amount_a)?
+            .min(liquidity_for_b(sqrt_lower, sqrt_price, amount_b)?))
+    } else {
+        liquidity_for_b(sqrt_lower, sqrt_upper, amount_b)
+    }
+}
+
+// liquidity = amount_a * sqrt_lower * sqrt_upper / (sqrt_upper - sqrt_lower)
+fn liquidity_for_a(
+    sqrt_lower: Decimal256,
+    sqrt_upper: Decimal256,
+    amount_a: Uint128,
+) -> StdResult<Uint128> {
+    let (lower, upper) = sorted_atomics(sqrt_lower, sqrt_upper);
+    let numerator = Uint256::from(amount_a).checked_mul(lower)?;
+    let denominator = (upper - lower).checked_mul(Decimal256::one().atomics())?;
+    Ok(Uint128::try_from(mul_div(
+        numerator,
+        upper,
+        denominator,
+        false,
+    )?)?)
+}
+
+// liquidity = amount_b / (sqrt_upper - sqrt_lower)
+fn liquidity_for_b(
+    sqrt_lower: Decimal256,
+    sqrt_upper: Decimal256,
+    amount_b: Uint128,
+) -> StdResult<Uint128> {
+    let (lower, upper) = sorted_atomics(sqrt_lower, sqrt_upper);
+    let liquidity = mul_div(
+        Uint256::from(amount_b),
+        Decimal256::one().atomics(),
+        upper - lower,
+        false,
+    )?;
+    Ok(Uint128::try_from(liquidity)?)
+}
+
+// Square root price after amount_a is added to `liquidity`, lowering the price of token_a:
+// sqrt_next = liquidity * sqrt_price / (liquidity + amount_a * sqrt_price)
+// Rounded up so the price never moves further than the input pays for.
+pub fn next_sqrt_price_from_a(
+    sqrt_price: Decimal256,
+    liquidity: Uint128,
+    amount_a: Uint128,
+) -> StdResult<Decimal256> {
+    let one = Decimal256::one().atomics();
+    let liquidity = Uint256::from(liquidity);
+    let numerator = liquidity.checked_mul(sqrt_price.atomics())?;
+    let denominator = liquidity
+        .checked_mul(one)?
+        .checked_add(Uint256::from(amount_a).checked_mul(sqrt_price.atomics())?)?;
+    Ok(Decimal256::new(mul_div(numerator, one, denominator, true)?))
+}
+
+// Square root price after amount_b is added to `liquidity`, raising the price of token_a:
+// sqrt_next = sqrt_price + amount_b / liquidity, rounded down
+pub fn next_sqrt_price_from_b(
+    sqrt_price: Decimal256,
+    liquidity: Uint128,
+    amount_b: Uint128,
+) -> StdResult<Decimal256> {
+    let delta = mul_div(
+        Uint256::from(amount_b),
+        Decimal256::one().atomics(),
+        Uint256::from(liquidity),
+        false,
+    )?;
+    Ok(Decimal256::new(sqrt_price.atomics().checked_add(delta)?))
+}
+
+// Moves the price from sqrt_price towards sqrt_target as in Uniswap v3, spending at most
+// amount_remaining of input including the fee. A target below the current price sells
+// token_a, one above it sells token_b. The fee is taken from the input: when the target
+// is reached it is charged on the input used, otherwise all that remains is used and the
+// rest of it is the fee.
+pub fn compute_swap_step(
+    sqrt_price: Decimal256,
+    sqrt_target: Decimal256,
+    liquidity: Uint128,
+    amount_remaining: Uint128,
+    fee_rate: Uint128,
+) -> StdResult<SwapStep> {
+    let a_in = sqrt_target <= sqrt_price;
+    let fee_denominator = Uint128::from(FEE_DENOMINATOR);
+    let fee_complement = fee_denominator.checked_sub(fee_rate)?;
+    let remaining_after_fee = amount_remaining.multiply_ratio(fee_complement, fee_denominator);
+
+    let max_in = if a_in {
+        amount_a_delta(sqrt_target, sqrt_price, liquidity, true)?
+    } else {
+        amount_b_delta(sqrt_price, sqrt_target, liquidity, true)?
+    };
+
+    let (sqrt_next, amount_in) = if remaining_after_fee >= max_in {
+        (sqrt_target, max_in)
+    } else if a_in {
+        (
+            next_sqrt_price_from_a(sqrt_price, liquidity, remaining_after_fee)?,
+            remaining_after_fee,
+        )
+    } else {
+        (
+            next_sqrt_price_from_b(sqrt_price, liquidity, remaining_after_fee)?,
+            remaining_after_fee,
+        )
+    };
+
+    let amount_out = if a_in {
+        amount_b_delta(sqrt_next, sqrt_price, liquidity, false)?
+    } else {
+        amount_a_delta(sqrt_price, sqrt_next, liquidity, false)?
+    };
+
+    let fee = if sqrt_next == sqrt_target {
+        ceil_multiply_ratio(amount_in, fee_rate, fee_complement)?
+    } else {
+        amount_remaining - amount_in
+    };
+
+    Ok(SwapStep {
+        sqrt_price: sqrt_next,
+        amount_in,
+        amount_out,
+        fee,
+    })
+}
+
+// amount * price, rounded up
+pub fn mul_price_ceil(amount: Uint128, price: Decimal256) -> StdResult<Uint128> {
+    let result = mul_div(
+        Uint256::from(amount),
+        price.atomics(),
+        Decimal256::one().atomics(),
+        true,
+    )?;
+    Ok(Uint128::try_from(result)?)
+}
+
+// amount / price, rounded down
+pub fn div_price_floor(amount: Uint128, price: Decimal256) -> StdResult<Uint128> {
+    let result = mul_div(
+        Uint256::from(amount),
+        Decimal256::one().atomics(),
+        price.atomics(),
+        false,
+    )?;
+    Ok(Uint128::try_from(result)?)
+}
+
+// Fee earned per unit of liquidity, 18 decimal fixed point
+pub fn fee_growth(fee: Uint128, liquidity: Uint128) -> Uint256 {
+    Decimal256::from_ratio(fee, liquidity).atomics()
+}
+
+// Fees earned by `liquidity` over a fee growth difference
+pub fn fees_earned(liquidity: Uint128, fee_growth_delta: Uint256) -> StdResult<Uint128> {
+    let fees = mul_div(
+        Uint256::from(liquidity),
+        fee_growth_delta,
+        Decimal256::one().atomics(),
+        false,
+    )?;
+    Ok(Uint128::try_from(fees)?)
+}
+
+// liquidity + delta for a signed liquidity change
+pub fn add_liquidity_delta(liquidity: Uint128, delta: i128) -> StdResult<Uint128> {
+    let change = Uint128::from(delta.unsigned_abs());
+    if delta < 0 {
+        Ok(liquidity.checked_sub(change)?)
+    } else {
+        Ok(liquidity.checked_add(change)?)
+    }
+}
+
+fn sorted_atomics(sqrt_price_1: Decimal256, sqrt_price_2: Decimal256) -> (Uint256, Uint256) {
+    if sqrt_price_1 <= sqrt_price_2 {
+        (sqrt_price_1.atomics(), sqrt_price_2.atomics())
+    } else {
+        (sqrt_price_2.atomics(), sqrt_price_1.atomics())
+    }
+}
+
+// value * numerator / denominator in 512 bits, rounded down or up
+fn mul_div(
+    value: Uint256,
+    numerator: Uint256,
+    denominator: Uint256,
+    round_up: bool,
+) -> StdResult<Uint256> {
+    if denominator.is_zero() {
+        return Err(StdError::generic_err("Division by zero"));
+    }
+
+    let product = value.full_mul(numerator);
+    let denominator = Uint512::from(denominator);
+    let mut result = product / denominator;
+    if round_up && !(product % denominator).is_zero() {
+        result += Uint512::one();
+    }
+
+    Ok(Uint256::try_from(result)?)
+}
+
+// Cumulative price at `time` between two observations. The price is constant between
+// observations, so the cumulative price grows linearly from one to the next.
+pub fn interpolate_cumulative_price(
+    before: (u64, Uint256),
+    after: (u64, Uint256),
+    time: u64,
+) -> Uint256 {
+    let ((before_time, before), (after_time, after)) = (before, after);
+    before + (after - before).multiply_ratio(time - before_time, after_time - before_time)
+}
+
+// Time weighted average price over `seconds` between two cumulative prices
+pub fn average_price(
+    cumulative_start: Uint256,
+    cumulative_end: Uint256,
+    seconds: u64,
+) -> Decimal256 {
+    Decimal256::new((cumulative_end - cumulative_start) / Uint256::from(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn decimal(value: &str) -> Decimal256 {
+        Decimal256::from_str(value).unwrap()
+    }
+
+    fn u(amount: u128) -> Uint128 {
+        Uint128::new(amount)
+    }
+
+    // Equal to 9 decimal places
+    fn assert_close(actual: Decimal256, expected: Decimal256) {
+        let tolerance = Decimal256::from_ratio(1u128, 1_000_000_000u128);
+        let difference = if actual > expected {
+            actual - expected
+        } else {
+            expected - actual
+        };
+        assert!(difference <= tolerance, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn stable_invariant_of_balanced_reserves_is_their_sum() {
+        for amp in [1, 100, 5000] {
+            assert_eq!(
+                compute_d(amp, u(1_000_000), u(1_000_000)).unwrap(),
+                u(2_000_000)
+            );
+        }
+        assert_eq!(compute_d(100, u(0), u(1_000_000)).unwrap(), u(0));
+    }
+
+    #[test]
+    fn stable_invariant_lies_between_the_constant_sum_and_product() {
+        let (x, y) = (u(1_500_000_000), u(400_000_000));
+        let d = compute_d(100, x, y).unwrap();
+
+        // sum = 1.9e9, 2 * sqrt(x * y) ~ 1.549e9; a higher amp moves D towards the sum
+        assert!(d < x + y);
+        assert!(d > u(1_549_193_338));
+        assert!(compute_d(1000, x, y).unwrap() > d);
+        assert!(compute_d(1, x, y).unwrap() < d);
+    }
+
+    #[test]
+    fn stable_y_solves_the_invariant() {
+        for (amp, x, y) in [
+            (1, 1_000_000, 1_000_000),
+            (100, 1_500_000_000, 400_000_000),
+            (5000, 7, 3_000_000),
+        ] {
+            let d = compute_d(amp, u(x), u(y)).unwrap();
+            let solved = compute_y(amp, u(x), d).unwrap();
+            assert!(
+                solved.abs_diff(u(y)) <= u(1),
+                "amp {} x {} y {}: got {}",
+                amp,
+                x,
+                y,
+                solved
+            );
+            assert!(compute_d(amp, u(x), solved).unwrap().abs_diff(d) <= u(1));
+        }
+
+        assert!(compute_y(100, u(0), u(2_000_000)).is_err());
+    }
+
+    #[test]
+    fn stable_swap_keeps_the_invariant() {
+        let (reserve_in, reserve_out) = (u(10_000_000), u(10_000_000));
+        let amp = 100;
+        let d = compute_d(amp, reserve_in, reserve_out).unwrap();
+
+        let result =
+            compute_stable_swap(reserve_in, reserve_out, u(1_000_000), u(0), u(0), amp).unwrap();
+        let d_after = compute_d(
+            amp,
+            reserve_in + u(1_000_000),
+            reserve_out - result.amount_out,
+        )
+        .unwrap();
+        assert!(d_after >= d);
+
+        // Close to 1:1 near balance, and much better than the constant product curve
+        assert!(result.amount_out > u(990_000) && result.amount_out < u(1_000_000));
+        let constant_product =
+            compute_swap(reserve_in, reserve_out, u(1_000_000), u(0), u(0)).unwrap();
+        assert!(result.amount_out > constant_product.amount_out);
+    }
+
+    #[test]
+    fn stable_reverse_swap_covers_the_output() {
+        let (reserve_in, reserve_out) = (u(10_000_000), u(12_000_000));
+        let amp = 200;
+
+        let reverse =
+            compute_stable_reverse_swap(reserve_in, reserve_out, u(500_000), u(5), u(0), amp)
+                .unwrap();
+        let swap = compute_stable_swap(reserve_in, reserve_out, reverse.amount_in, u(5), u(0), amp)
+            .unwrap();
+        assert!(swap.amount_out >= u(500_000));
+
+        // A few units less is not enough
+        let short = compute_stable_swap(
+            reserve_in,
+            reserve_out,
+            reverse.amount_in - u(3),
+            u(5),
+            u(0),
+            amp,
+        )
+        .unwrap();
+        assert!(short.amount_out < u(500_000));
+
+        assert!(
+            compute_stable_reverse_swap(reserve_in, reserve_out, reserve_out, u(5), u(0), amp)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn pow_approx_of_whole_exponents_is_exact() {
+        assert_eq!(
+            pow_approx(decimal("1.5"), decimal("2")).unwrap(),
+            decimal("2.25")
+        );
+        assert_eq!(
+            pow_approx(decimal("0.5"), decimal("3")).unwrap(),
+            decimal("0.125")
+        );
+        assert_eq!(
+            pow_approx(decimal("0.7"), decimal("0")).unwrap(),
+            Decimal256::one()
+        );
+    }
+
+    #[test]
+    fn pow_approx_of_fractional_exponents() {
+        assert_close(
+            pow_approx(decimal("0.81"), decimal("0.5")).unwrap(),
+            decimal("0.9"),
+        );
+        assert_close(
+            pow_approx(decimal("1.21"), decimal("0.5")).unwrap(),
+            decimal("1.1"),
+        );
+        assert_close(
+            pow_approx(decimal("0.64"), decimal("0.25")).unwrap(),
+            decimal("0.894427190999915878"),
+        );
+        assert_close(
+            pow_approx(decimal("1.5"), decimal("1.5")).unwrap(),
+            decimal("1.837117307087383573"),
+        );
+        assert_close(
+            pow_approx(decimal("0.7"), decimal("4.2")).unwrap(),
+            decimal("0.223569094614270460"),
+        );
+    }
+
+    #[test]
+    fn pow_approx_rejects_bases_outside_the_series_range() {
+        assert!(pow_approx(Decimal256::zero(), decimal("0.5")).is_err());
+        assert!(pow_approx(decimal("2"), decimal("0.5")).is_err());
+    }
+
+    #[test]
+    fn weighted_invariant_is_the_weighted_geometric_mean() {
+        // Equal weights give sqrt(a * b), as for constant product pools
+        let half = decimal("0.5");
+        assert_eq!(
+            compute_weighted_invariant(&[(u(1_000_000), half), (u(4_000_000), half)]).unwrap(),
+            u(2_000_000)
+        );
+
+        // 8e6 ^ 0.8 * 2e6 ^ 0.2 = 8e6 * 0.25 ^ 0.2
+        let invariant = compute_weighted_invariant(&[
+            (u(8_000_000), decimal("0.8")),
+            (u(2_000_000), decimal("0.2")),
+        ]);
+        assert!(invariant.unwrap().abs_diff(u(6_062_866)) <= u(1));
+
+        // Reserves far apart still converge: 1e18 ^ 0.5 * 1 ^ 0.5
+        let invariant =
+            compute_weighted_invariant(&[(u(1_000_000_000_000_000_000), half), (u(1), half)])
+                .unwrap();
+        assert!(
+            invariant.abs_diff(u(1_000_000_000)) <= u(1),
+            "{}",
+            invariant
+        );
+    }
+
+    #[test]
+    fn weighted_swap_of_equal_weights_is_constant_product() {
+        let half = decimal("0.5");
+        let weighted = compute_weighted_swap(
+            u(1_000_000),
+            half,
+            u(4_000_000),
+            half,
+            u(10_000),
+            u(30),
+            u(0),
+        )
+        .unwrap();
+        let constant_product =
+            compute_swap(u(1_000_000), u(4_000_000), u(10_000), u(30), u(0)).unwrap();
+
+        assert_eq!(weighted.fee, constant_product.fee);
+        assert!(weighted.amount_out.abs_diff(constant_product.amount_out) <= u(1));
+    }
+
+    #[test]
+    fn weighted_swap_follows_the_weights() {
+        // 80/20 pool at a spot price of one: out = 8e6 * (1 - (2e6 / 2.01e6) ^ (0.2 / 0.8))
+        let result = compute_weighted_swap(
+            u(2_000_000),
+            decimal("0.2"),
+            u(8_000_000),
+            decimal("0.8"),
+            u(10_000),
+            u(0),
+            u(0),
+        )
+        .unwrap();
+        assert!(
+            result.amount_out.abs_diff(u(9_968)) <= u(1),
+            "{}",
+            result.amount_out
+        );
+
+        // Trades over 30% of the input balance are refused
+        assert!(compute_weighted_swap(
+            u(2_000_000),
+            decimal("0.2"),
+            u(8_000_000),
+            decimal("0.8"),
+            u(600_001),
+            u(0),
+            u(0)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn weighted_single_join_and_exit_round_trip_pays_the_fee() {
+        let (balance, weight, total_liquidity) = (u(2_000_000), decimal("0.2"), u(100_000_000));
+
+        let liquidity =
+            compute_weighted_single_join(balance, weight, total_liquidity, u(100_000), u(30))
+                .unwrap();
+        let amount = compute_weighted_single_exit(
+            balance + u(100_000),
+            weight,
+            total_liquidity + liquidity,
+            liquidity,
+            u(30),
+        )
+        .unwrap();
+        assert!(amount < u(100_000));
+        assert!(amount > u(99_000));
+
+        // Without a fee nothing is lost beyond rounding
+        let liquidity =
+            compute_weighted_single_join(balance, weight, total_liquidity, u(100_000), u(0))
+                .unwrap();
+        let amount = compute_weighted_single_exit(
+            balance + u(100_000),
+            weight,
+            total_liquidity + liquidity,
+            liquidity,
+            u(0),
+        )
+        .unwrap();
+        assert!(amount.abs_diff(u(100_000)) <= u(2), "{}", amount);
+    }
+
+    #[test]
+    fn ticks_and_square_root_prices_round_trip() {
+        assert_eq!(sqrt_price_at_tick(0).unwrap(), Decimal256::one());
+        for tick in [MIN_TICK, -23028, -1, 0, 1, 23027, MAX_TICK] {
+            assert_eq!(
+                tick_at_sqrt_price(sqrt_price_at_tick(tick).unwrap()).unwrap(),
+                tick
+            );
+        }
+
+        // Prices between two ticks belong to the lower one: 1.0001^23027 < 10 < 1.0001^23028
+        let sqrt_ten = Decimal256::from_ratio(10u128, 1u128).sqrt();
+        assert_eq!(tick_at_sqrt_price(sqrt_ten).unwrap(), 23027);
+        assert_eq!(
+            tick_at_sqrt_price(Decimal256::one() / sqrt_ten).unwrap(),
+            -23028
+        );
+
+        assert!(sqrt_price_at_tick(MAX_TICK + 1).is_err());
+        assert!(sqrt_price_at_tick(MIN_TICK - 1).is_err());
+        assert!(
+            tick_at_sqrt_price(sqrt_price_at_tick(MAX_TICK).unwrap() * decimal("1.0001")).is_err()
+        );
+    }
+
+    #[test]
+    fn swap_step_stops_at_the_target_tick() {
+        let (sqrt_price, liquidity) = (Decimal256::one(), u(1_000_000_000));
+        let sqrt_target = sqrt_price_at_tick(-100).unwrap();
+        let max_in = amount_a_delta(sqrt_target, sqrt_price, liquidity, true).unwrap();
+
+        // Enough input to reach the tick: the fee is charged on what was used
+        let step =
+            compute_swap_step(sqrt_price, sqrt_target, liquidity, u(10_000_000), u(30)).unwrap();
+        assert_eq!(step.sqrt_price, sqrt_target);
+        assert_eq!(step.amount_in, max_in);
+        assert_eq!(
+            step.fee,
+            ceil_multiply_ratio(max_in, u(30), u(9_970)).unwrap()
+        );
+        assert_eq!(
+            step.amount_out,
+            amount_b_delta(sqrt_target, sqrt_price, liquidity, false).unwrap()
+        );
+
+        // Not enough: all of the input is used and the price stays inside the range
+        let step =
+            compute_swap_step(sqrt_price, sqrt_target, liquidity, u(1_000_000), u(30)).unwrap();
+        assert!(step.sqrt_price > sqrt_target && step.sqrt_price < sqrt_price);
+        assert_eq!(step.amount_in + step.fee, u(1_000_000));
+        assert_eq!(step.fee, u(3_000));
+        assert!(step.amount_out < step.amount_in);
+    }
+
+    #[test]
+    fn swap_steps_through_a_tick_match_a_single_step() {
+        // Crossing a tick where the liquidity doesn't change only adds rounding
+        let (sqrt_price, liquidity) = (Decimal256::one(), u(1_000_000_000));
+        let sqrt_tick = sqrt_price_at_tick(-50).unwrap();
+        let sqrt_target = sqrt_price_at_tick(-100).unwrap();
+
+        for token_a_in in [true, false] {
+            let (sqrt_tick, sqrt_target) = if token_a_in {
+                (sqrt_tick, sqrt_target)
+            } else {
+                (
+                    Decimal256::one() / sqrt_tick,
+                    Decimal256::one() / sqrt_target,
+                )
+            };
+
+            let single =
+                compute_swap_step(sqrt_price, sqrt_target, liquidity, u(4_000_000), u(30)).unwrap();
+            assert_ne!(single.sqrt_price, sqrt_target);
+
+            let first =
+                compute_swap_step(sqrt_price, sqrt_tick, liquidity, u(4_000_000), u(30)).unwrap();
+            assert_eq!(first.sqrt_price, sqrt_tick);
+            let remaining = u(4_000_000) - first.amount_in - first.fee;
+            let second =
+                compute_swap_step(sqrt_tick, sqrt_target, liquidity, remaining, u(30)).unwrap();
+
+            assert!((first.amount_out + second.amount_out).abs_diff(single.amount_out) <= u(5));
+            assert!((first.fee + second.fee).abs_diff(single.fee) <= u(2));
+        }
+    }
+
+    #[test]
+    fn liquidity_for_amounts_uses_one_token_outside_the_range() {
+        let sqrt_lower = sqrt_price_at_tick(-1000).unwrap();
+        let sqrt_upper = sqrt_price_at_tick(1000).unwrap();
+
+        // Below the range only token_a counts, above it only token_b
+        let below = liquidity_for_amounts(
+            sqrt_price_at_tick(-2000).unwrap(),
+            sqrt_lower,
+            sqrt_upper,
+            u(1_000_000),
+            u(0),
+        )
+        .unwrap();
+        assert!(!below.is_zero());
+        let above = liquidity_for_amounts(
+            sqrt_price_at_tick(2000).unwrap(),
+            sqrt_lower,
+            sqrt_upper,
+            u(0),
+            u(1_000_000),
+        )
+        .unwrap();
+        assert!(!above.is_zero());
+
+        // Inside, the scarcer token limits the liquidity, which holds at most the amounts given
+        let liquidity = liquidity_for_amounts(
+            Decimal256::one(),
+            sqrt_lower,
+            sqrt_upper,
+            u(1_000_000),
+            u(500_000),
+        )
+        .unwrap();
+        let amount_a = amount_a_delta(Decimal256::one(), sqrt_upper, liquidity, true).unwrap();
+        let amount_b = amount_b_delta(sqrt_lower, Decimal256::one(), liquidity, true).unwrap();
+        assert!(amount_a <= u(1_000_000) && amount_b <= u(500_000));
+        assert!(amount_b.abs_diff(u(500_000)) <= u(1));
+    }
+
+    #[test]
+    fn fee_growth_pays_out_what_was_collected() {
+        // 30 fee over 1000 liquidity is 0.03 per unit
+        assert_eq!(fee_growth(u(30), u(1_000)), decimal("0.03").atomics());
+        assert_eq!(
+            fees_earned(u(1_000), fee_growth(u(30), u(1_000))).unwrap(),
+            u(30)
+        );
+        assert_eq!(
+            fees_earned(u(250), fee_growth(u(30), u(1_000))).unwrap(),
+            u(7)
+        );
+
+        // Rounded down, so positions never claim more than was collected
+        assert_eq!(fees_earned(u(3), fee_growth(u(10), u(3))).unwrap(), u(9));
+
+        // Accumulators wrap; differences taken with wrapping_sub stay correct across it
+        let snapshot = Uint256::MAX - fee_growth(u(5), u(100));
+        let global = snapshot.wrapping_add(fee_growth(u(35), u(100)));
+        assert!(global < snapshot);
+        assert_eq!(
+            fees_earned(u(100), global.wrapping_sub(snapshot)).unwrap(),
+            u(35)
+        );
+    }
+
+    #[test]
+    fn liquidity_deltas_are_signed() {
+        assert_eq!(add_liquidity_delta(u(100), 50).unwrap(), u(150));
+        assert_eq!(add_liquidity_delta(u(100), -100).unwrap(), u(0));
+        assert!(add_liquidity_delta(u(100), -101).is_err());
+    }
+
+    #[test]
+    fn amount_in_to_price_stops_at_the_marginal_price() {
+        let (reserve_in, reserve_out) = (u(1_000_000_000), u(4_000_000_000));
+        let (fee_rate, protocol_fee_share) = (u(30), u(2_000));
+        let marginal_price = |amount_in: Uint128| {
+            let result = compute_swap(
+                reserve_in,
+                reserve_out,
+                amount_in,
+                fee_rate,
+                protocol_fee_share,
+            )
+            .unwrap();
+            let reserve_in = reserve_in + amount_in - result.protocol_fee;
+            let reserve_out = reserve_out - result.amount_out;
+            Decimal256::from_ratio(reserve_out, reserve_in) * decimal("0.997")
+        };
+
+        let price = decimal("3");
+        let amount_in = compute_amount_in_to_price(
+            reserve_in,
+            reserve_out,
+            price,
+            fee_rate,
+            protocol_fee_share,
+        )
+        .unwrap();
+        assert!(marginal_price(amount_in) >= price);
+        assert!(marginal_price(amount_in + u(1_000)) < price);
+
+        // Nothing when the pool already pays less than the price
+        let amount_in =
+            compute_amount_in_to_price(reserve_in, reserve_out, decimal("3.99"), fee_rate, u(0))
+                .unwrap();
+        assert_eq!(amount_in, Uint128::zero());
+    }
+
+    #[test]
+    fn cumulative_prices_interpolate_linearly() {
+        // A price of 4 for 100 seconds, then 1 for 100 seconds
+        let price = |value: u128| Decimal256::from_ratio(value, 1u128).atomics();
+        let observations = [
+            (1_000, Uint256::zero()),
+            (1_100, price(400)),
+            (1_200, price(500)),
+        ];
+
+        let at = |time: u64| {
+            let before = observations
+                .iter()
+                .rev()
+                .find(|(observed, _)| *observed <= time)
+                .unwrap();
+            let after = observations
+                .iter()
+                .find(|(observed, _)| *observed > time)
+                .unwrap();
+            interpolate_cumulative_price(*before, *after, time)
+        };
+        assert_eq!(at(1_000), Uint256::zero());
+        assert_eq!(at(1_050), price(200));
+        assert_eq!(at(1_150), price(450));
+
+        // Windows over one price see that price, windows across both a weighted mix
+        assert_eq!(average_price(at(1_150), price(500), 50), decimal("1"));
+        assert_eq!(average_price(at(1_050), at(1_100), 50), decimal("4"));
+        assert_eq!(average_price(at(1_050), price(500), 150), decimal("2"));
+        assert_eq!(
+            average_price(Uint256::zero(), price(500), 200),
+            decimal("2.5")
+        );
+    }
+}
@@ -0,0 +1,331 @@
+use cosmwasm_std::{Addr, Api, Env, Order, StdResult, Storage, Uint128, Uint256};
+use cw_storage_plus::{Item, Map};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::contract::{
+    DEFAULT_FEE_CHANGE_DELAY, DEFAULT_FEE_TIERS, DEFAULT_MAX_FEE_RATE, DEFAULT_NATIVE_DENOMS,
+    DEFAULT_NATIVE_DENOM_PREFIXES,
+};
+use crate::error::ContractError;
+use crate::execute::{save_pool, validate_fee_tiers, validate_max_fee_rate};
+use crate::state::{pool_key, AssetInfo, AssetListMode, Config, Pool, PoolType, CONFIG, LIQUIDITY};
+
+type Migration = fn(&mut dyn Storage, &dyn Api, &Env) -> Result<(), ContractError>;
+
+// State migrations with the version each one migrates from and to, oldest first. Only
+// released versions are listed: every schema change between two releases is folded into
+// the migration to the later one, so 0.1.0 is the only version 0.2.0 migrates from, and
+// the next schema change gets its own step from 0.2.0
+const MIGRATIONS: [(&str, &str, Migration); 1] = [("0.1.0", "0.2.0", migrate_to_0_2_0)];
+
+// Runs every migration to a version above the stored one, in order, and returns the
+// versions migrated to. State of an unreleased version in between is rejected
+pub fn migrate_state(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    stored: &Version,
+) -> Result<Vec<String>, ContractError> {
+    let mut applied = vec![];
+
+    let mut from = stored.clone();
+
+    for (source, version, migration) in MIGRATIONS {
+        let version = Version::parse(version)?;
+        if from >= version {
+            continue;
+        }
+        if from != Version::parse(source)? {
+            return Err(ContractError::UnsupportedMigrationSource {
+                stored: stored.to_string(),
+            });
+        }
+
+        migration(storage, api, env)?;
+        applied.push(version.to_string());
+        from = version;
+    }
+
+    Ok(applied)
+}
+
+// Config as stored by 0.1.0
+#[derive(Serialize, Deserialize)]
+struct ConfigV010 {
+    admin: Addr,
+    fee_rate: Uint128,
+    lp_token_code_id: u64,
+}
+
+// Pool as stored by 0.1.0, with tokens as plain strings. See legacy_asset
+#[derive(Serialize, Deserialize)]
+struct PoolV010 {
+    token_a: String,
+    token_b: String,
+    reserve_a: Uint128,
+    reserve_b: Uint128,
+    total_liquidity: Uint128,
+    lp_token_address: Option<String>,
+}
+
+const CONFIG_V010: Item<ConfigV010> = Item::new("config");
+const POOLS_V010: Map<(String, String), PoolV010> = Map::new("pools");
+
+// 0.1.0 -> 0.2.0: fee tiers and their bounds, protocol fee, the native denom allow-list,
+// the asset list mode and the pauser join the config, pools get typed assets, their own
+// fee rate and TWAP accumulators, and emptied liquidity positions are dropped
+fn migrate_to_0_2_0(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+) -> Result<(), ContractError> {
+    let legacy_config = CONFIG_V010.load(storage)?;
+
+    // Keep the old global fee rate usable as a tier
+    let mut fee_tiers: Vec<Uint128> = DEFAULT_FEE_TIERS
+        .iter()
+        .map(|tier| Uint128::from(*tier))
+        .collect();
+    fee_tiers.push(legacy_config.fee_rate);
+    let max_fee_rate = validate_max_fee_rate(
+        legacy_config
+            .fee_rate
+            .max(Uint128::from(DEFAULT_MAX_FEE_RATE)),
+    )?;
+
+    let config = Config {
+        admin: Some(legacy_config.admin.clone()),
+        fee_rate: legacy_config.fee_rate,
+        fee_tiers: validate_fee_tiers(fee_tiers, max_fee_rate)?,
+        lp_token_code_id: legacy_config.lp_token_code_id,
+        treasury: legacy_config.admin.clone(),
+        protocol_fee_share: Uint128::zero(),
+        native_denoms: DEFAULT_NATIVE_DENOMS
+            .iter()
+            .map(|denom| denom.to_string())
+            .collect(),
+        native_denom_prefixes: DEFAULT_NATIVE_DENOM_PREFIXES
+            .iter()
+            .map(|prefix| prefix.to_string())
+            .collect(),
+        pauser: legacy_config.admin,
+        max_fee_rate,
+        fee_change_delay: DEFAULT_FEE_CHANGE_DELAY,
+        pool_creation_fee: None,
+        asset_list_mode: AssetListMode::DenyList {},
+        restrict_swaps: false,
+    };
+    CONFIG.save(storage, &config)?;
+
+    // Every pool charged the global fee rate, and its price accumulators start from the
+    // migration block
+    let legacy_pools = POOLS_V010
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let now = env.block.time.seconds();
+
+    for (_, legacy_pool) in legacy_pools {
+        let token_a = legacy_asset(api, &legacy_pool.token_a)?;
+        let token_b = legacy_asset(api, &legacy_pool.token_b)?;
+
+        // Reserves are ordered by pool_key. An asset's key is its 0.1.0 token string, so
+        // pools and liquidity positions keep their keys
+        let key = pool_key(&token_a, &token_b);
+        let ((token_a, reserve_a), (token_b, reserve_b)) = if token_a.to_string() == key.0 {
+            (
+                (token_a, legacy_pool.reserve_a),
+                (token_b, legacy_pool.reserve_b),
+            )
+        } else {
+            (
+                (token_b, legacy_pool.reserve_b),
+                (token_a, legacy_pool.reserve_a),
+            )
+        };
+
+        let pool = Pool {
+            token_a,
+            token_b,
+            reserve_a,
+            reserve_b,
+            total_liquidity: legacy_pool.total_liquidity,
+            lp_token_address: legacy_pool.lp_token_address,
+            fee_rate: config.fee_rate,
+            price_a_cumulative: Uint256::zero(),
+            price_b_cumulative: Uint256::zero(),
+            block_time_last: now,
+            pool_type: PoolType::ConstantProduct {},
+            amp_ramp: None,
+        };
+        save_pool(storage, key, &pool)?;
+    }
+
+    // 0.1.0 saved positions again after withdrawing all of their liquidity
+    let empty_positions = LIQUIDITY
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, position)| position.liquidity.is_zero())
+        })
+        .map(|item| item.map(|(key, _)| key))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for key in empty_positions {
+        LIQUIDITY.remove(storage, key);
+    }
+
+    Ok(())
+}
+
+// 0.1.0 treated uatom and IBC denoms as bank tokens and every other token as a CW20
+// contract address
+fn legacy_asset(api: &dyn Api, token: &str) -> StdResult<AssetInfo> {
+    if token == "uatom" || token.starts_with("ibc/") {
+        Ok(AssetInfo::Native {
+            denom: token.to_string(),
+        })
+    } else {
+        Ok(AssetInfo::Cw20 {
+            contract_addr: api.addr_validate(token)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+    use cw2::{get_contract_version, set_contract_version};
+
+    use super::*;
+    use crate::contract::{migrate, CONTRACT_NAME, CONTRACT_VERSION};
+    use crate::msg::MigrateMsg;
+    use crate::state::{LiquidityPosition, POOLS};
+
+    fn legacy_pool(token_a: &str, token_b: &str, reserve_a: u128, reserve_b: u128) -> PoolV010 {
+        PoolV010 {
+            token_a: token_a.to_string(),
+            token_b: token_b.to_string(),
+            reserve_a: Uint128::new(reserve_a),
+            reserve_b: Uint128::new(reserve_b),
+            total_liquidity: Uint128::new(1_000),
+            lp_token_address: None,
+        }
+    }
+
+    #[test]
+    fn migrates_0_1_0_state() {
+        let mut deps = mock_dependencies();
+        let user = Addr::unchecked("user");
+
+        // State as 0.1.0 wrote it: keys and tokens sorted as strings
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        CONFIG_V010
+            .save(
+                deps.as_mut().storage,
+                &ConfigV010 {
+                    admin: Addr::unchecked("admin"),
+                    fee_rate: Uint128::new(30),
+                    lp_token_code_id: 1,
+                },
+            )
+            .unwrap();
+        let pools = [
+            legacy_pool(
+                "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+                "uatom",
+                100,
+                200,
+            ),
+            legacy_pool("cw20token", "uatom", 300, 400),
+        ];
+        for pool in &pools {
+            let key = (pool.token_a.clone(), pool.token_b.clone());
+            POOLS_V010
+                .save(deps.as_mut().storage, key.clone(), pool)
+                .unwrap();
+            LIQUIDITY
+                .save(
+                    deps.as_mut().storage,
+                    (user.clone(), key.0, key.1),
+                    &LiquidityPosition {
+                        liquidity: Uint128::new(1_000),
+                    },
+                )
+                .unwrap();
+        }
+        LIQUIDITY
+            .save(
+                deps.as_mut().storage,
+                (
+                    Addr::unchecked("gone"),
+                    "cw20token".to_string(),
+                    "uatom".to_string(),
+                ),
+                &LiquidityPosition {
+                    liquidity: Uint128::zero(),
+                },
+            )
+            .unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(config.admin, Some(Addr::unchecked("admin")));
+        assert!(config.fee_tiers.contains(&Uint128::new(30)));
+
+        let cw20 = AssetInfo::Cw20 {
+            contract_addr: Addr::unchecked("cw20token"),
+        };
+        let atom = AssetInfo::Native {
+            denom: "uatom".to_string(),
+        };
+        let pool = POOLS
+            .load(deps.as_ref().storage, pool_key(&cw20, &atom))
+            .unwrap();
+        assert_eq!(pool.token_a, cw20);
+        assert_eq!(pool.token_b, atom);
+        assert_eq!(pool.reserve_a, Uint128::new(300));
+        assert_eq!(pool.reserve_b, Uint128::new(400));
+        assert_eq!(pool.fee_rate, Uint128::new(30));
+
+        let ibc = AssetInfo::Native {
+            denom: pools[0].token_a.clone(),
+        };
+        let pool = POOLS
+            .load(deps.as_ref().storage, pool_key(&ibc, &atom))
+            .unwrap();
+        assert_eq!(pool.token_a, ibc);
+        assert_eq!(pool.reserve_a, Uint128::new(100));
+
+        let position = LIQUIDITY
+            .load(
+                deps.as_ref().storage,
+                crate::state::liquidity_key(&user, &cw20, &atom),
+            )
+            .unwrap();
+        assert_eq!(position.liquidity, Uint128::new(1_000));
+        assert!(!LIQUIDITY.has(
+            deps.as_ref().storage,
+            (
+                Addr::unchecked("gone"),
+                "cw20token".to_string(),
+                "uatom".to_string()
+            )
+        ));
+    }
+
+    #[test]
+    fn rejects_unreleased_versions() {
+        let mut deps = mock_dependencies();
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.5").unwrap();
+
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert!(
+            matches!(err, ContractError::UnsupportedMigrationSource { stored } if stored == "0.1.5")
+        );
+    }
+}
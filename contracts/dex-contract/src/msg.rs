@@ -1,57 +1,414 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Coin, Decimal256, Int128, Uint128};
+use cw20::Cw20ReceiveMsg;
+
+use crate::state::{
+    AdminProposal, AssetInfo, AssetListMode, AssetListing, PauseInfo, PendingFeeChange,
+    PoolCreationFee, PoolId, PoolType, RewardSchedule, WeightedAsset,
+};
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub admin: Option<String>,
-    pub fee_rate: Uint128, // Fee rate in basis points (e.g., 30 = 0.3%)
+    pub fee_rate: Uint128, // Default fee rate in basis points (e.g., 30 = 0.3%)
+    pub fee_tiers: Option<Vec<Uint128>>, // Allowed pool fee rates, defaults to 1, 5, 30 and 100 bps
     pub lp_token_code_id: u64, // Code ID for CW20 LP token contracts
+    pub treasury: Option<String>, // Receives protocol fees, defaults to the admin
+    pub protocol_fee_share: Option<Uint128>, // Basis points of each swap fee kept for the protocol, defaults to 0
+    pub native_denoms: Option<Vec<String>>,  // Bank denoms pools may use, defaults to uatom
+    pub native_denom_prefixes: Option<Vec<String>>, // Allowed bank denom prefixes, defaults to ibc/ and factory/
+    pub pauser: Option<String>,                     // May pause trading, defaults to the admin
+    pub max_fee_rate: Option<Uint128>, // Highest fee tier in basis points, defaults to 100; cannot be changed later
+    pub fee_change_delay: Option<u64>, // Seconds fee changes are queued for, at least one hour, defaults to one day
+    pub pool_creation_fee: Option<PoolCreationFeeParams>, // Pool creation is free if None
+    pub asset_list_mode: Option<AssetListMode>, // Defaults to deny-list mode
+    pub restrict_swaps: Option<bool>,  // Apply the asset list to swaps too, defaults to false
 }
 
+// State is migrated from the stored contract version, so no parameters are needed
+#[cw_serde]
+pub struct MigrateMsg {}
+
 #[cw_serde]
 pub enum ExecuteMsg {
     // Create a new trading pair pool
     CreatePool {
-        token_a: String,
-        token_b: String,
+        token_a: AssetInfo,
+        token_b: AssetInfo,
         initial_a: Uint128,
         initial_b: Uint128,
+        fee_rate: Option<Uint128>, // One of the allowed fee tiers, defaults to the config fee rate
+        pool_type: Option<PoolType>, // Defaults to constant product
     },
     // Add liquidity to an existing pool
     AddLiquidity {
-        token_a: String,
-        token_b: String,
+        token_a: AssetInfo,
+        token_b: AssetInfo,
         amount_a: Uint128,
         amount_b: Uint128,
         min_liquidity: Uint128,
     },
-    // Remove liquidity from a pool
+    // Remove liquidity from a pool. For pools with an LP token the DEX burns the tokens
+    // with BurnFrom, so it needs an allowance on the LP token first; the
+    // WithdrawLiquidity hook burns sent LP tokens without one
     RemoveLiquidity {
-        token_a: String,
-        token_b: String,
+        token_a: AssetInfo,
+        token_b: AssetInfo,
         liquidity: Uint128,
         min_a: Uint128,
         min_b: Uint128,
     },
+    // Add liquidity from a single asset, swapping part of it for the other side of a pair
+    // pool against the pool first; amounts over the pool ratio are refunded
+    ZapIn {
+        pool: PoolId, // Pair or weighted pool
+        asset: AssetInfo,
+        amount: Uint128,
+        min_liquidity: Uint128,
+    },
+    // Remove liquidity from a pair or weighted pool into a single asset, swapping the
+    // other side against the pool
+    ZapOut {
+        pool: PoolId, // Pair or weighted pool
+        liquidity: Uint128,
+        asset: AssetInfo,
+        min_out: Uint128,
+    },
     // Swap tokens
     Swap {
-        token_in: String,
-        token_out: String,
+        token_in: AssetInfo,
+        token_out: AssetInfo,
+        amount_in: Uint128,
+        min_amount_out: Uint128,
+    },
+    // Buy exactly amount_out of token_out, paying at most max_amount_in; trades against the
+    // pool only, limit orders are not matched
+    SwapExactOut {
+        token_in: AssetInfo,
+        token_out: AssetInfo,
+        amount_out: Uint128,
+        max_amount_in: Uint128,
+    },
+    // Create a weighted pool of 2 to 8 assets; weights are normalized to sum to 1
+    CreateWeightedPool {
+        assets: Vec<WeightedAssetParams>,
+        fee_rate: Option<Uint128>, // One of the allowed fee tiers, defaults to the config fee rate
+    },
+    // Add every asset of a weighted pool; amounts over the pool ratios are refunded
+    JoinWeightedPool {
+        pool_id: u64,
+        assets: Vec<TokenAmount>,
+        min_liquidity: Uint128,
+    },
+    // Add a single asset to a weighted pool
+    JoinWeightedPoolSingle {
+        pool_id: u64,
+        token: AssetInfo,
+        amount: Uint128,
+        min_liquidity: Uint128,
+    },
+    // Burn LP tokens for a share of every asset of a weighted pool
+    ExitWeightedPool {
+        pool_id: u64,
+        liquidity: Uint128,
+        min_amounts: Vec<TokenAmount>, // Tokens left out have no minimum
+    },
+    // Burn LP tokens for a single asset of a weighted pool
+    ExitWeightedPoolSingle {
+        pool_id: u64,
+        liquidity: Uint128,
+        token_out: AssetInfo,
+        min_amount_out: Uint128,
+    },
+    // Swap tokens in a weighted pool
+    WeightedSwap {
+        pool_id: u64,
+        token_in: AssetInfo,
+        token_out: AssetInfo,
+        amount_in: Uint128,
+        min_amount_out: Uint128,
+    },
+    // Create a concentrated liquidity pool at initial_price (token_b per token_a); it starts
+    // without liquidity
+    CreateConcentratedPool {
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+        fee_rate: Option<Uint128>, // One of the allowed fee tiers, defaults to the config fee rate
+        initial_price: Decimal256,
+    },
+    // Provide liquidity to a concentrated pool between two ticks; amounts the range does
+    // not use at the current price are refunded
+    OpenPosition {
+        pool_id: u64,
+        lower_tick: i32,
+        upper_tick: i32,
+        amount_a: Uint128,
+        amount_b: Uint128,
+        min_liquidity: Uint128,
+    },
+    // Remove liquidity from a position, also paying out its uncollected fees
+    WithdrawPosition {
+        position_id: u64,
+        liquidity: Uint128,
+        min_a: Uint128,
+        min_b: Uint128,
+    },
+    // Pay out the fees earned by a position
+    CollectFees {
+        position_id: u64,
+    },
+    // Swap tokens in a concentrated pool
+    ConcentratedSwap {
+        pool_id: u64,
+        token_in: AssetInfo,
         amount_in: Uint128,
         min_amount_out: Uint128,
     },
-    // Update contract admin
-    UpdateAdmin {
-        admin: String,
+    // Rest an order selling offer_token at price ask_token each; the pair must have a pool
+    PlaceLimitOrder {
+        offer_token: AssetInfo,
+        ask_token: AssetInfo,
+        offer_amount: Uint128,
+        price: Decimal256,
+    },
+    // Cancel a limit order, refunding its unsold part
+    CancelLimitOrder {
+        order_id: u64,
+    },
+    // Offer the admin role to new_admin until expires_in seconds from now (admin only)
+    ProposeNewAdmin {
+        new_admin: String,
+        expires_in: u64,
     },
-    // Update fee rate (admin only)
+    // Take the admin role offered by ProposeNewAdmin (proposed admin only)
+    AcceptAdmin {},
+    // Withdraw the pending admin proposal (admin only)
+    CancelAdminProposal {},
+    // Give up the admin role for good, freezing the config (admin only)
+    RenounceAdmin {},
+    // Queue a new default fee rate for new pools (admin only)
     UpdateFeeRate {
         fee_rate: Uint128,
     },
+    // Replace the set of allowed fee tiers, each at most the max fee rate (admin only)
+    UpdateFeeTiers {
+        fee_tiers: Vec<Uint128>,
+    },
+    // Queue moving a pool to another fee tier (admin only)
+    UpdatePoolFee {
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+        fee_rate: Uint128,
+    },
+    // Apply a queued fee change once its delay has passed
+    ApplyFeeChange {
+        change_id: u64,
+    },
+    // Drop a queued fee change (admin only)
+    CancelFeeChange {
+        change_id: u64,
+    },
+    // Ramp a stable pool's amp linearly to future_amp by future_time (admin only)
+    RampAmp {
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+        future_amp: u64,
+        future_time: u64,
+    },
+    // Stop an amp ramp at the current amp (admin only)
+    StopRampAmp {
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+    },
+    // Queue a new protocol share of swap fees and/or update the treasury (admin only)
+    UpdateProtocolFee {
+        protocol_fee_share: Option<Uint128>,
+        treasury: Option<String>,
+    },
+    // List, delist or remove assets from the asset list (admin only)
+    UpdateAssetList {
+        list: Vec<AssetInfo>,
+        delist: Vec<AssetInfo>,
+        remove: Vec<AssetInfo>,
+    },
+    // Change the asset list mode and/or whether it applies to swaps (admin only)
+    UpdateAssetListMode {
+        mode: Option<AssetListMode>,
+        restrict_swaps: Option<bool>,
+    },
+    // Set the pool creation fee, or waive it with None (admin only)
+    UpdatePoolCreationFee {
+        pool_creation_fee: Option<PoolCreationFeeParams>,
+    },
+    // Replace the native denoms and/or denom prefixes new pools may use (admin only)
+    UpdateNativeDenoms {
+        native_denoms: Option<Vec<String>>,
+        native_denom_prefixes: Option<Vec<String>>,
+    },
+    // Send accumulated protocol fees to the treasury, all tokens if none given (admin or treasury)
+    CollectProtocolFees {
+        tokens: Option<Vec<AssetInfo>>,
+    },
+    // Stop swaps, deposits and pool creation contract-wide, or swaps and deposits in one
+    // pool; withdrawals keep working (pauser or admin)
+    Pause {
+        pool: Option<PoolId>,
+        reason: String,
+    },
+    // Lift a contract-wide or pool pause (pauser or admin)
+    Unpause {
+        pool: Option<PoolId>,
+    },
+    // Update the pauser (admin only)
+    UpdatePauser {
+        pauser: String,
+    },
     // Update LP token code ID (admin only)
     UpdateLpTokenCodeId {
         lp_token_code_id: u64,
     },
+    // Pay rate of reward per second to the bonded shares of a pair pool from start_time
+    // to end_time; the whole emission is paid in (admin only)
+    CreateRewardSchedule {
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+        reward: AssetInfo,
+        start_time: u64,
+        end_time: u64,
+        rate: Uint128,
+    },
+    // Bond shares of the sender's liquidity position in a pool without an LP token; LP
+    // tokens are bonded with the Bond hook
+    Bond {
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+        amount: Uint128,
+    },
+    // Return bonded shares or LP tokens
+    Unbond {
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+        amount: Uint128,
+    },
+    // Pay out the rewards earned in a pool
+    Claim {
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+    },
+    // Take back the part of an ended schedule's emission that was due while nothing was
+    // bonded (admin only)
+    ReclaimRewards {
+        schedule_id: u64,
+    },
+    // Swap along a route of pools, e.g. CW20 -> uatom -> ibc/USDC
+    ExecuteSwapOperations {
+        operations: Vec<SwapOperation>,
+        amount_in: Uint128,
+        minimum_receive: Uint128,
+    },
+    // Receive CW20 tokens sent with a Cw20HookMsg
+    Receive(Cw20ReceiveMsg),
+}
+
+#[cw_serde]
+pub struct PoolCreationFeeParams {
+    pub fee: Coin,                 // Any bank denom
+    pub collector: Option<String>, // Receives the fee; it is burned if None
+}
+
+#[cw_serde]
+pub struct WeightedAssetParams {
+    pub token: AssetInfo,
+    pub weight: Uint128, // Relative weight, e.g. 80 and 20 for an 80/20 pool
+    pub amount: Uint128, // Initial reserve
+}
+
+#[cw_serde]
+pub struct TokenAmount {
+    pub token: AssetInfo,
+    pub amount: Uint128,
+}
+
+// A single hop of a multi-hop swap
+#[cw_serde]
+pub struct SwapOperation {
+    pub token_in: AssetInfo,
+    pub token_out: AssetInfo,
+}
+
+// Messages embedded in a CW20 Send to the DEX. The sent token is the input asset;
+// a second asset, if any, must be a CW20 the DEX is allowed to transfer.
+#[cw_serde]
+pub enum Cw20HookMsg {
+    // Swap the sent tokens for token_out
+    Swap {
+        token_out: AssetInfo,
+        min_amount_out: Uint128,
+    },
+    // Buy exactly amount_out of token_out; the unused part of the sent tokens is refunded
+    SwapExactOut {
+        token_out: AssetInfo,
+        amount_out: Uint128,
+    },
+    // Add the sent tokens to the pool with token_b
+    ProvideLiquidity {
+        token_b: AssetInfo,
+        amount_b: Uint128,
+        min_liquidity: Uint128,
+    },
+    // Burn the sent LP tokens of the pool of token_a and token_b for their share of the
+    // reserves
+    WithdrawLiquidity {
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+        min_a: Uint128,
+        min_b: Uint128,
+    },
+    // Create a pool seeded with the sent tokens and initial_b of token_b. Fails with
+    // PoolCreationFeeNotPayable while a pool creation fee is set, since no native funds
+    // can come with a CW20 Send; use ExecuteMsg::CreatePool with a CW20 allowance instead
+    CreatePool {
+        token_b: AssetInfo,
+        initial_b: Uint128,
+        fee_rate: Option<Uint128>,
+        pool_type: Option<PoolType>,
+    },
+    // Swap the sent tokens along a route starting with the sent token
+    ExecuteSwapOperations {
+        operations: Vec<SwapOperation>,
+        minimum_receive: Uint128,
+    },
+    // Swap the sent tokens for token_out in a weighted pool
+    WeightedSwap {
+        pool_id: u64,
+        token_out: AssetInfo,
+        min_amount_out: Uint128,
+    },
+    // Add liquidity to a pair or weighted pool from the sent tokens alone
+    ZapIn {
+        pool: PoolId,
+        min_liquidity: Uint128,
+    },
+    // Add the sent tokens to a weighted pool as a single asset
+    JoinWeightedPoolSingle {
+        pool_id: u64,
+        min_liquidity: Uint128,
+    },
+    // Swap the sent tokens for the other token of a concentrated pool
+    ConcentratedSwap {
+        pool_id: u64,
+        min_amount_out: Uint128,
+    },
+    // Bond the sent LP tokens of the pair pool of token_a and token_b
+    Bond {
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+    },
+    // Rest an order selling the sent tokens at price ask_token each
+    PlaceLimitOrder {
+        ask_token: AssetInfo,
+        price: Decimal256,
+    },
 }
 
 #[cw_serde]
@@ -62,44 +419,199 @@ pub enum QueryMsg {
     Config {},
     // Get pool information
     #[returns(PoolInfo)]
-    Pool { token_a: String, token_b: String },
+    Pool {
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+    },
     // Get all pools
     #[returns(Vec<PoolInfo>)]
     Pools {
-        start_after: Option<String>,
+        start_after: Option<(AssetInfo, AssetInfo)>, // Tokens of the last pool returned
         limit: Option<u32>,
     },
     // Get user's liquidity position (now queries CW20 balance)
     #[returns(LiquidityInfo)]
     Liquidity {
         user: String,
-        token_a: String,
-        token_b: String,
+        token_a: AssetInfo,
+        token_b: AssetInfo,
     },
     // Get swap simulation
     #[returns(SimulationResponse)]
     Simulation {
-        token_in: String,
-        token_out: String,
+        token_in: AssetInfo,
+        token_out: AssetInfo,
+        amount_in: Uint128,
+    },
+    // Get the input needed to receive an exact output from the pool, as SwapExactOut
+    #[returns(ReverseSimulationResponse)]
+    ReverseSimulation {
+        token_in: AssetInfo,
+        token_out: AssetInfo,
+        amount_out: Uint128,
+    },
+    // Get protocol fees awaiting collection
+    #[returns(Vec<ProtocolFeeInfo>)]
+    ProtocolFees {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // Get multi-hop swap simulation
+    #[returns(SimulateSwapOperationsResponse)]
+    SimulateSwapOperations {
+        amount_in: Uint128,
+        operations: Vec<SwapOperation>,
+    },
+    // Get the reward schedules of a pair pool that have not ended and its bonded shares
+    #[returns(RewardSchedulesResponse)]
+    RewardSchedules {
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+    },
+    // Get a reward schedule, ended or not
+    #[returns(RewardSchedule)]
+    RewardSchedule { schedule_id: u64 },
+    // Get a staker's bonded shares and unclaimed rewards in a pair pool
+    #[returns(PendingRewardsResponse)]
+    PendingRewards {
+        user: String,
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+    },
+    // Get the single asset paid out by ZapOut
+    #[returns(ZapOutSimulationResponse)]
+    SimulateZapOut {
+        pool: PoolId,
+        liquidity: Uint128,
+        asset: AssetInfo,
+    },
+    // Get time-weighted average prices over the last window_seconds
+    #[returns(TwapResponse)]
+    Twap {
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+        window_seconds: u64,
+    },
+    // Get weighted pool information
+    #[returns(WeightedPoolInfo)]
+    WeightedPool { pool_id: u64 },
+    // List weighted pools
+    #[returns(Vec<WeightedPoolInfo>)]
+    WeightedPools {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // Get weighted pool swap simulation
+    #[returns(SimulationResponse)]
+    WeightedSimulation {
+        pool_id: u64,
+        token_in: AssetInfo,
+        token_out: AssetInfo,
+        amount_in: Uint128,
+    },
+    // Get concentrated pool information
+    #[returns(ConcentratedPoolInfo)]
+    ConcentratedPool { pool_id: u64 },
+    // List concentrated pools
+    #[returns(Vec<ConcentratedPoolInfo>)]
+    ConcentratedPools {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // Get the active liquidity of a concentrated pool and its initialized ticks
+    #[returns(ActiveLiquidityResponse)]
+    ActiveLiquidity {
+        pool_id: u64,
+        start_after: Option<i32>,
+        limit: Option<u32>,
+    },
+    // Get a concentrated liquidity position with its current amounts and uncollected fees
+    #[returns(PositionInfo)]
+    Position { position_id: u64 },
+    // List the positions of an owner
+    #[returns(Vec<PositionInfo>)]
+    Positions {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // Get concentrated pool swap simulation
+    #[returns(SimulationResponse)]
+    ConcentratedSimulation {
+        pool_id: u64,
+        token_in: AssetInfo,
         amount_in: Uint128,
     },
+    // Get a limit order
+    #[returns(LimitOrderInfo)]
+    LimitOrder { order_id: u64 },
+    // List the orders selling offer_token for ask_token, best price first
+    #[returns(Vec<LimitOrderInfo>)]
+    LimitOrders {
+        offer_token: AssetInfo,
+        ask_token: AssetInfo,
+        start_after: Option<u64>, // order_id
+        limit: Option<u32>,
+    },
+    // List the limit orders of an owner
+    #[returns(Vec<LimitOrderInfo>)]
+    LimitOrdersByOwner {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // What is paused and why
+    #[returns(StatusResponse)]
+    Status {},
+    // Pending admin transfer, if any
+    #[returns(Option<AdminProposal>)]
+    AdminProposal {},
+    // Asset list entry of an asset, if any
+    #[returns(Option<AssetListing>)]
+    AssetListing { asset: AssetInfo },
+    // Asset list entries
+    #[returns(Vec<AssetListing>)]
+    AssetList {
+        start_after: Option<AssetInfo>,
+        limit: Option<u32>,
+    },
+    // Fee changes waiting to be applied, oldest first
+    #[returns(Vec<PendingFeeChange>)]
+    PendingFeeChanges {
+        start_after: Option<u64>, // change_id of the last change returned
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
 pub struct Config {
-    pub admin: Addr,
+    pub admin: Option<Addr>,
     pub fee_rate: Uint128,
+    pub fee_tiers: Vec<Uint128>,
     pub lp_token_code_id: u64,
+    pub treasury: Addr,
+    pub protocol_fee_share: Uint128,
+    pub native_denoms: Vec<String>,
+    pub native_denom_prefixes: Vec<String>,
+    pub pauser: Addr,
+    pub max_fee_rate: Uint128,
+    pub fee_change_delay: u64,
+    pub pool_creation_fee: Option<PoolCreationFee>,
+    pub asset_list_mode: AssetListMode,
+    pub restrict_swaps: bool,
 }
 
 #[cw_serde]
 pub struct PoolInfo {
-    pub token_a: String,
-    pub token_b: String,
+    pub token_a: AssetInfo,
+    pub token_b: AssetInfo,
     pub reserve_a: Uint128,
     pub reserve_b: Uint128,
     pub total_liquidity: Uint128,
     pub lp_token_address: Option<String>, // Address of the CW20 LP token (optional)
+    pub fee_rate: Uint128,                // Swap fee in basis points
+    pub pool_type: PoolType,
+    pub amp: Option<u64>, // Current amp of a stable pool, following any ramp in progress
 }
 
 #[cw_serde]
@@ -115,4 +627,127 @@ pub struct SimulationResponse {
     pub amount_out: Uint128,
     pub fee: Uint128,
     pub price_impact: String,
-}
\ No newline at end of file
+}
+
+#[cw_serde]
+pub struct RewardSchedulesResponse {
+    pub total_bonded: Uint128,
+    pub schedules: Vec<RewardSchedule>,
+}
+
+#[cw_serde]
+pub struct PendingRewardsResponse {
+    pub bonded: Uint128,
+    pub rewards: Vec<PendingReward>,
+}
+
+#[cw_serde]
+pub struct PendingReward {
+    pub schedule_id: u64,
+    pub reward: AssetInfo,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct ZapOutSimulationResponse {
+    pub amount_out: Uint128,
+}
+
+#[cw_serde]
+pub struct ReverseSimulationResponse {
+    pub amount_in: Uint128,
+    pub fee: Uint128,
+    pub price_impact: String,
+}
+
+#[cw_serde]
+pub struct ProtocolFeeInfo {
+    pub token: AssetInfo,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct SimulateSwapOperationsResponse {
+    pub amount_out: Uint128,
+}
+
+#[cw_serde]
+pub struct WeightedPoolInfo {
+    pub pool_id: u64,
+    pub assets: Vec<WeightedAsset>,
+    pub total_liquidity: Uint128,
+    pub lp_token_address: Option<String>,
+    pub fee_rate: Uint128,
+}
+
+#[cw_serde]
+pub struct ConcentratedPoolInfo {
+    pub pool_id: u64,
+    pub token_a: AssetInfo,
+    pub token_b: AssetInfo,
+    pub fee_rate: Uint128,
+    pub tick_spacing: i32,
+    pub price: Decimal256, // Amount of token_b per token_a
+    pub sqrt_price: Decimal256,
+    pub current_tick: i32,
+    pub liquidity: Uint128, // Active liquidity at the current price
+}
+
+#[cw_serde]
+pub struct ActiveLiquidityResponse {
+    pub current_tick: i32,
+    pub sqrt_price: Decimal256,
+    pub liquidity: Uint128,
+    pub ticks: Vec<TickLiquidity>, // Initialized ticks in ascending order
+}
+
+#[cw_serde]
+pub struct TickLiquidity {
+    pub tick: i32,
+    pub liquidity_net: Int128, // Change in active liquidity when the price crosses the tick upwards
+    pub liquidity_gross: Uint128,
+}
+
+#[cw_serde]
+pub struct PositionInfo {
+    pub position_id: u64,
+    pub owner: Addr,
+    pub pool_id: u64,
+    pub lower_tick: i32,
+    pub upper_tick: i32,
+    pub liquidity: Uint128,
+    pub amount_a: Uint128, // Withdrawable at the current price
+    pub amount_b: Uint128,
+    pub fees_a: Uint128, // Uncollected fees
+    pub fees_b: Uint128,
+}
+
+#[cw_serde]
+pub struct LimitOrderInfo {
+    pub order_id: u64,
+    pub owner: Addr,
+    pub offer_token: AssetInfo,
+    pub ask_token: AssetInfo,
+    pub price: Decimal256, // Amount of ask_token asked per offer_token
+    pub offer_amount: Uint128,
+    pub remaining: Uint128, // Not sold yet
+    pub received: Uint128,  // ask_token paid to the owner so far
+}
+
+#[cw_serde]
+pub struct TwapResponse {
+    pub price_a: Decimal256, // Average amount of token_b per token_a
+    pub price_b: Decimal256, // Average amount of token_a per token_b
+    pub window_seconds: u64,
+}
+#[cw_serde]
+pub struct StatusResponse {
+    pub paused: Option<PauseInfo>, // Contract-wide pause
+    pub paused_pools: Vec<PausedPoolInfo>,
+}
+
+#[cw_serde]
+pub struct PausedPoolInfo {
+    pub pool: PoolId,
+    pub pause: PauseInfo,
+}
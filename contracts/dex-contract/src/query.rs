@@ -1,22 +1,134 @@
-use cosmwasm_std::{Deps, Order, StdResult, Uint128};
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{Decimal256, Deps, Env, Order, StdError, StdResult, Uint128, Uint256};
+use cw20::{BalanceResponse, Cw20QueryMsg};
 use cw_storage_plus::Bound;
 
-use crate::msg::{Config, LiquidityInfo, PoolInfo, SimulationResponse};
-use crate::state::{liquidity_key, pool_key, CONFIG, LIQUIDITY, POOLS};
+use crate::execute::{validate_swap_operations, MAX_TWAP_WINDOW};
+use crate::math::{average_price, fees_earned, interpolate_cumulative_price};
+use crate::msg::{
+    ActiveLiquidityResponse, ConcentratedPoolInfo, Config, LimitOrderInfo, LiquidityInfo,
+    PausedPoolInfo, PendingReward, PendingRewardsResponse, PoolInfo, PositionInfo, ProtocolFeeInfo,
+    ReverseSimulationResponse, RewardSchedulesResponse, SimulateSwapOperationsResponse,
+    SimulationResponse, StatusResponse, SwapOperation, TickLiquidity, TwapResponse,
+    WeightedPoolInfo, ZapOutSimulationResponse,
+};
+use crate::staking::{pool_reward_schedules, settle_rewards, staker_pool_rewards};
+use crate::state::{
+    liquidity_key, order_book_key, pool_key, AdminProposal, AssetInfo, AssetListing,
+    ConcentratedPool, ConcentratedPosition, LimitOrder, Observation, PendingFeeChange, Pool,
+    PoolId, RewardSchedule, ADMIN_PROPOSAL, ASSET_LIST, CONCENTRATED_POOLS, CONFIG, LIQUIDITY,
+    OBSERVATIONS, ORDERS, ORDER_BOOK, OWNER_ORDERS, OWNER_POSITIONS, PAUSE,
+    PAUSED_CONCENTRATED_POOLS, PAUSED_PAIRS, PAUSED_WEIGHTED_POOLS, PENDING_FEE_CHANGES, POOLS,
+    POSITIONS, PROTOCOL_FEES, REWARD_SCHEDULES, TICKS, TOTAL_BONDED, WEIGHTED_POOLS,
+};
 
 pub fn query_config(deps: Deps) -> StdResult<Config> {
     let config = CONFIG.load(deps.storage)?;
     Ok(Config {
         admin: config.admin,
         fee_rate: config.fee_rate,
+        fee_tiers: config.fee_tiers,
         lp_token_code_id: config.lp_token_code_id,
+        treasury: config.treasury,
+        protocol_fee_share: config.protocol_fee_share,
+        native_denoms: config.native_denoms,
+        native_denom_prefixes: config.native_denom_prefixes,
+        pauser: config.pauser,
+        max_fee_rate: config.max_fee_rate,
+        fee_change_delay: config.fee_change_delay,
+        pool_creation_fee: config.pool_creation_fee,
+        asset_list_mode: config.asset_list_mode,
+        restrict_swaps: config.restrict_swaps,
+    })
+}
+
+pub fn query_admin_proposal(deps: Deps) -> StdResult<Option<AdminProposal>> {
+    ADMIN_PROPOSAL.may_load(deps.storage)
+}
+
+pub fn query_asset_listing(deps: Deps, asset: AssetInfo) -> StdResult<Option<AssetListing>> {
+    ASSET_LIST.may_load(deps.storage, asset.to_string())
+}
+
+pub fn query_asset_list(
+    deps: Deps,
+    start_after: Option<AssetInfo>,
+    limit: Option<u32>,
+) -> StdResult<Vec<AssetListing>> {
+    let limit = limit.unwrap_or(10).min(30) as usize;
+    let start = start_after.map(|asset| Bound::exclusive(asset.to_string()));
+
+    ASSET_LIST
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, listing)| listing))
+        .collect()
+}
+
+pub fn query_pending_fee_changes(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<PendingFeeChange>> {
+    let limit = limit.unwrap_or(10).min(30) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    PENDING_FEE_CHANGES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, pending)| pending))
+        .collect()
+}
+
+pub fn query_status(deps: Deps) -> StdResult<StatusResponse> {
+    let mut paused_pools = PAUSED_PAIRS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (key, pause) = item?;
+            let pool = POOLS.load(deps.storage, key)?;
+            Ok(PausedPoolInfo {
+                pool: PoolId::Pair {
+                    token_a: pool.token_a,
+                    token_b: pool.token_b,
+                },
+                pause,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for item in PAUSED_WEIGHTED_POOLS.range(deps.storage, None, None, Order::Ascending) {
+        let (pool_id, pause) = item?;
+        paused_pools.push(PausedPoolInfo {
+            pool: PoolId::Weighted { pool_id },
+            pause,
+        });
+    }
+
+    for item in PAUSED_CONCENTRATED_POOLS.range(deps.storage, None, None, Order::Ascending) {
+        let (pool_id, pause) = item?;
+        paused_pools.push(PausedPoolInfo {
+            pool: PoolId::Concentrated { pool_id },
+            pause,
+        });
+    }
+
+    Ok(StatusResponse {
+        paused: PAUSE.may_load(deps.storage)?,
+        paused_pools,
     })
 }
 
-pub fn query_pool(deps: Deps, token_a: String, token_b: String) -> StdResult<PoolInfo> {
+pub fn query_pool(
+    deps: Deps,
+    env: Env,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
+) -> StdResult<PoolInfo> {
     let key = pool_key(&token_a, &token_b);
     let pool = POOLS.load(deps.storage, key)?;
-    
+    let amp = pool.amp(env.block.time.seconds());
+
     Ok(PoolInfo {
         token_a: pool.token_a,
         token_b: pool.token_b,
@@ -24,23 +136,29 @@ pub fn query_pool(deps: Deps, token_a: String, token_b: String) -> StdResult<Poo
         reserve_b: pool.reserve_b,
         total_liquidity: pool.total_liquidity,
         lp_token_address: pool.lp_token_address,
+        fee_rate: pool.fee_rate,
+        pool_type: pool.pool_type,
+        amp,
     })
 }
 
 pub fn query_pools(
     deps: Deps,
-    start_after: Option<String>,
+    env: Env,
+    start_after: Option<(AssetInfo, AssetInfo)>,
     limit: Option<u32>,
 ) -> StdResult<Vec<PoolInfo>> {
     let limit = limit.unwrap_or(10).min(30) as usize;
-    
-    let start = start_after.map(|s| Bound::exclusive(pool_key(&s, "")));
-    
+
+    let start =
+        start_after.map(|(token_a, token_b)| Bound::exclusive(pool_key(&token_a, &token_b)));
+
     POOLS
         .range(deps.storage, start, None, Order::Ascending)
         .take(limit)
         .map(|item| {
             let (_, pool) = item?;
+            let amp = pool.amp(env.block.time.seconds());
             Ok(PoolInfo {
                 token_a: pool.token_a,
                 token_b: pool.token_b,
@@ -48,6 +166,9 @@ pub fn query_pools(
                 reserve_b: pool.reserve_b,
                 total_liquidity: pool.total_liquidity,
                 lp_token_address: pool.lp_token_address,
+                fee_rate: pool.fee_rate,
+                pool_type: pool.pool_type,
+                amp,
             })
         })
         .collect()
@@ -56,18 +177,32 @@ pub fn query_pools(
 pub fn query_liquidity(
     deps: Deps,
     user: String,
-    token_a: String,
-    token_b: String,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
 ) -> StdResult<LiquidityInfo> {
     let user_addr = deps.api.addr_validate(&user)?;
     let key = pool_key(&token_a, &token_b);
     let pool = POOLS.load(deps.storage, key.clone())?;
-    
-    let liq_key = liquidity_key(&user_addr, &key.0, &key.1);
-    let position = LIQUIDITY.may_load(deps.storage, liq_key)?
-        .unwrap_or_else(|| crate::state::LiquidityPosition {
-            liquidity: Uint128::zero(),
-        });
+
+    let position = if let Some(lp_token) = &pool.lp_token_address {
+        // LP shares are held as CW20 balances
+        let balance: BalanceResponse = deps.querier.query_wasm_smart(
+            lp_token,
+            &Cw20QueryMsg::Balance {
+                address: user_addr.to_string(),
+            },
+        )?;
+        crate::state::LiquidityPosition {
+            liquidity: balance.balance,
+        }
+    } else {
+        let liq_key = liquidity_key(&user_addr, &pool.token_a, &pool.token_b);
+        LIQUIDITY
+            .may_load(deps.storage, liq_key)?
+            .unwrap_or_else(|| crate::state::LiquidityPosition {
+                liquidity: Uint128::zero(),
+            })
+    };
 
     // Calculate user's share of the pool
     let share_a = if pool.total_liquidity.is_zero() {
@@ -75,7 +210,7 @@ pub fn query_liquidity(
     } else {
         position.liquidity * pool.reserve_a / pool.total_liquidity
     };
-    
+
     let share_b = if pool.total_liquidity.is_zero() {
         Uint128::zero()
     } else {
@@ -92,42 +227,610 @@ pub fn query_liquidity(
 
 pub fn query_simulation(
     deps: Deps,
-    token_in: String,
-    token_out: String,
+    env: Env,
+    token_in: AssetInfo,
+    token_out: AssetInfo,
     amount_in: Uint128,
 ) -> StdResult<SimulationResponse> {
     let key = pool_key(&token_in, &token_out);
     let pool = POOLS.load(deps.storage, key)?;
     let config = CONFIG.load(deps.storage)?;
 
-    // Determine which reserves to use
-    let (reserve_in, reserve_out) = if token_in == pool.token_a {
-        (pool.reserve_a, pool.reserve_b)
-    } else {
-        (pool.reserve_b, pool.reserve_a)
+    let now = env.block.time.seconds();
+
+    // Calculate output amount with fee on a copy of the pool, filling the resting orders
+    // that pay better, to compare prices after
+    let mut pool_after = pool.clone();
+    let (result, _) = pool_after.swap_with_orders(
+        deps.storage,
+        &token_in,
+        amount_in,
+        config.protocol_fee_share,
+        now,
+    )?;
+
+    Ok(SimulationResponse {
+        amount_out: result.amount_out,
+        fee: result.fee,
+        price_impact: price_impact(
+            pool.spot_price(&token_in, now)?,
+            pool_after.spot_price(&token_in, now)?,
+        )?,
+    })
+}
+
+pub fn query_reward_schedules(
+    deps: Deps,
+    env: Env,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
+) -> StdResult<RewardSchedulesResponse> {
+    let key = pool_key(&token_a, &token_b);
+    let total_bonded = TOTAL_BONDED
+        .may_load(deps.storage, key.clone())?
+        .unwrap_or_default();
+    let now = env.block.time.seconds();
+
+    // Accrue up to now so reward_per_share is current
+    let mut schedules = pool_reward_schedules(deps.storage, &key, now)?;
+    for schedule in &mut schedules {
+        schedule.accrue(total_bonded, now)?;
+    }
+
+    Ok(RewardSchedulesResponse {
+        total_bonded,
+        schedules,
+    })
+}
+
+pub fn query_reward_schedule(deps: Deps, env: Env, schedule_id: u64) -> StdResult<RewardSchedule> {
+    let mut schedule = REWARD_SCHEDULES.load(deps.storage, schedule_id)?;
+    let key = pool_key(&schedule.token_a, &schedule.token_b);
+    let total_bonded = TOTAL_BONDED
+        .may_load(deps.storage, key)?
+        .unwrap_or_default();
+
+    schedule.accrue(total_bonded, env.block.time.seconds())?;
+    Ok(schedule)
+}
+
+pub fn query_pending_rewards(
+    deps: Deps,
+    env: Env,
+    user: String,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
+) -> StdResult<PendingRewardsResponse> {
+    let user = deps.api.addr_validate(&user)?;
+    let key = pool_key(&token_a, &token_b);
+
+    // Schedules settled up to now, then ended ones with rewards left from earlier
+    let (stake, mut settled) = settle_rewards(deps.storage, &key, &user, env.block.time.seconds())?;
+    for (schedule, staker_reward) in staker_pool_rewards(deps.storage, &key, &user)? {
+        if !settled
+            .iter()
+            .any(|(settled, _)| settled.schedule_id == schedule.schedule_id)
+        {
+            settled.push((schedule, staker_reward));
+        }
+    }
+    settled.sort_by_key(|(schedule, _)| schedule.schedule_id);
+
+    let rewards = settled
+        .into_iter()
+        .map(|(schedule, staker_reward)| PendingReward {
+            schedule_id: schedule.schedule_id,
+            reward: schedule.reward,
+            amount: staker_reward.pending,
+        })
+        .collect();
+
+    Ok(PendingRewardsResponse {
+        bonded: stake.bonded,
+        rewards,
+    })
+}
+
+pub fn query_simulate_zap_out(
+    deps: Deps,
+    env: Env,
+    pool: PoolId,
+    liquidity: Uint128,
+    asset: AssetInfo,
+) -> StdResult<ZapOutSimulationResponse> {
+    let amount_out = match pool {
+        PoolId::Pair { token_a, token_b } => {
+            if asset != token_a && asset != token_b {
+                return Err(StdError::generic_err(format!("Invalid asset {}", asset)));
+            }
+            let mut pool = POOLS.load(deps.storage, pool_key(&token_a, &token_b))?;
+            let config = CONFIG.load(deps.storage)?;
+            let (amount_out, _) = pool.zap_out(
+                &asset,
+                liquidity,
+                config.protocol_fee_share,
+                env.block.time.seconds(),
+            )?;
+            amount_out
+        }
+        PoolId::Weighted { pool_id } => WEIGHTED_POOLS
+            .load(deps.storage, pool_id)?
+            .exit_single(&asset, liquidity)?,
+        PoolId::Concentrated { .. } => {
+            return Err(StdError::generic_err(
+                "Zaps are not supported for concentrated pools",
+            ));
+        }
+    };
+
+    Ok(ZapOutSimulationResponse { amount_out })
+}
+
+pub fn query_reverse_simulation(
+    deps: Deps,
+    env: Env,
+    token_in: AssetInfo,
+    token_out: AssetInfo,
+    amount_out: Uint128,
+) -> StdResult<ReverseSimulationResponse> {
+    let key = pool_key(&token_in, &token_out);
+    let pool = POOLS.load(deps.storage, key)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    let now = env.block.time.seconds();
+
+    // Calculate required input with fee, rounded in the pool's favour
+    let mut pool_after = pool.clone();
+    let result = pool_after.reverse_swap(&token_in, amount_out, config.protocol_fee_share, now)?;
+
+    Ok(ReverseSimulationResponse {
+        amount_in: result.amount_in,
+        fee: result.fee,
+        price_impact: price_impact(
+            pool.spot_price(&token_in, now)?,
+            pool_after.spot_price(&token_in, now)?,
+        )?,
+    })
+}
+
+// Relative change of the spot price caused by a trade, formatted as a percentage
+fn price_impact(price_before: Decimal256, price_after: Decimal256) -> StdResult<String> {
+    let price_impact_bps = (price_before.abs_diff(price_after)
+        * Decimal256::from_ratio(10000u128, 1u128)
+        / price_before)
+        .to_uint_floor();
+    let price_impact_bps = Uint128::try_from(price_impact_bps)?;
+
+    Ok(format!("{}%", price_impact_bps.u128() as f64 / 100.0))
+}
+
+pub fn query_simulate_swap_operations(
+    deps: Deps,
+    env: Env,
+    amount_in: Uint128,
+    operations: Vec<SwapOperation>,
+) -> StdResult<SimulateSwapOperationsResponse> {
+    validate_swap_operations(&operations).map_err(|e| StdError::generic_err(e.to_string()))?;
+    let config = CONFIG.load(deps.storage)?;
+
+    // Pools touched by earlier hops, so routes that revisit a pool see updated reserves.
+    // Orders are matched as ExecuteSwapOperations does; a route that crosses the same
+    // orders twice sees them unfilled both times
+    let mut pools: BTreeMap<(String, String), Pool> = BTreeMap::new();
+
+    let mut amount = amount_in;
+    for operation in operations {
+        let key = pool_key(&operation.token_in, &operation.token_out);
+        let pool = match pools.get_mut(&key) {
+            Some(pool) => pool,
+            None => {
+                let pool = POOLS.load(deps.storage, key.clone())?;
+                pools.entry(key).or_insert(pool)
+            }
+        };
+
+        let (result, _) = pool.swap_with_orders(
+            deps.storage,
+            &operation.token_in,
+            amount,
+            config.protocol_fee_share,
+            env.block.time.seconds(),
+        )?;
+        amount = result.amount_out;
+    }
+
+    Ok(SimulateSwapOperationsResponse { amount_out: amount })
+}
+
+pub fn query_protocol_fees(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<ProtocolFeeInfo>> {
+    let limit = limit.unwrap_or(10).min(30) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    PROTOCOL_FEES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, fee) = item?;
+            Ok(ProtocolFeeInfo {
+                token: fee.asset,
+                amount: fee.amount,
+            })
+        })
+        .collect()
+}
+
+// Time-weighted average prices over the last window_seconds, in the caller's token order.
+// Prices are constant between two observations, so the cumulative prices at the start of
+// the window are interpolated exactly from the observations on either side of it.
+pub fn query_twap(
+    deps: Deps,
+    env: Env,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
+    window_seconds: u64,
+) -> StdResult<TwapResponse> {
+    if window_seconds == 0 || window_seconds > MAX_TWAP_WINDOW {
+        return Err(StdError::generic_err(format!(
+            "TWAP window must be between 1 and {} seconds",
+            MAX_TWAP_WINDOW
+        )));
+    }
+
+    let key = pool_key(&token_a, &token_b);
+    let mut pool = POOLS.load(deps.storage, key.clone())?;
+
+    let now = env.block.time.seconds();
+    pool.accumulate_prices(now)?;
+    let current = Observation {
+        price_a_cumulative: pool.price_a_cumulative,
+        price_b_cumulative: pool.price_b_cumulative,
     };
 
-    // Calculate output amount with fee
-    let fee_denominator = Uint128::from(10000u128);
-    let amount_in_after_fee = amount_in * (fee_denominator - config.fee_rate) / fee_denominator;
-    let amount_out = amount_in_after_fee * reserve_out / (reserve_in + amount_in_after_fee);
-    let fee = amount_in - amount_in_after_fee;
-
-    // Calculate price impact
-    let price_before = reserve_out * Uint128::from(10000u128) / reserve_in;
-    let new_reserve_in = reserve_in + amount_in;
-    let new_reserve_out = reserve_out - amount_out;
-    let price_after = new_reserve_out * Uint128::from(10000u128) / new_reserve_in;
-    
-    let price_impact_bps = if price_before > price_after {
-        (price_before - price_after) * Uint128::from(10000u128) / price_before
+    let start = now.saturating_sub(window_seconds);
+    let observations = OBSERVATIONS.prefix(key);
+    let (before_time, before) = observations
+        .range(
+            deps.storage,
+            None,
+            Some(Bound::inclusive(start)),
+            Order::Descending,
+        )
+        .next()
+        .transpose()?
+        .ok_or_else(|| {
+            StdError::generic_err(format!(
+                "Not enough price history for a {} second TWAP",
+                window_seconds
+            ))
+        })?;
+    let (after_time, after) = observations
+        .range(
+            deps.storage,
+            Some(Bound::exclusive(start)),
+            None,
+            Order::Ascending,
+        )
+        .next()
+        .transpose()?
+        .unwrap_or((now, current.clone()));
+
+    let at_start = |before: Uint256, after: Uint256| {
+        interpolate_cumulative_price((before_time, before), (after_time, after), start)
+    };
+
+    let price_a = average_price(
+        at_start(before.price_a_cumulative, after.price_a_cumulative),
+        current.price_a_cumulative,
+        window_seconds,
+    );
+    let price_b = average_price(
+        at_start(before.price_b_cumulative, after.price_b_cumulative),
+        current.price_b_cumulative,
+        window_seconds,
+    );
+
+    let (price_a, price_b) = if token_a == pool.token_a {
+        (price_a, price_b)
     } else {
-        (price_after - price_before) * Uint128::from(10000u128) / price_before
+        (price_b, price_a)
     };
 
+    Ok(TwapResponse {
+        price_a,
+        price_b,
+        window_seconds,
+    })
+}
+
+pub fn query_weighted_pool(deps: Deps, pool_id: u64) -> StdResult<WeightedPoolInfo> {
+    let pool = WEIGHTED_POOLS.load(deps.storage, pool_id)?;
+
+    Ok(WeightedPoolInfo {
+        pool_id: pool.pool_id,
+        assets: pool.assets,
+        total_liquidity: pool.total_liquidity,
+        lp_token_address: pool.lp_token_address,
+        fee_rate: pool.fee_rate,
+    })
+}
+
+pub fn query_weighted_pools(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<WeightedPoolInfo>> {
+    let limit = limit.unwrap_or(10).min(30) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    WEIGHTED_POOLS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, pool) = item?;
+            Ok(WeightedPoolInfo {
+                pool_id: pool.pool_id,
+                assets: pool.assets,
+                total_liquidity: pool.total_liquidity,
+                lp_token_address: pool.lp_token_address,
+                fee_rate: pool.fee_rate,
+            })
+        })
+        .collect()
+}
+
+pub fn query_weighted_simulation(
+    deps: Deps,
+    pool_id: u64,
+    token_in: AssetInfo,
+    token_out: AssetInfo,
+    amount_in: Uint128,
+) -> StdResult<SimulationResponse> {
+    let pool = WEIGHTED_POOLS.load(deps.storage, pool_id)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut pool_after = pool.clone();
+    let result = pool_after.swap(&token_in, &token_out, amount_in, config.protocol_fee_share)?;
+
     Ok(SimulationResponse {
-        amount_out,
-        fee,
-        price_impact: format!("{}%", price_impact_bps.u128() as f64 / 100.0),
+        amount_out: result.amount_out,
+        fee: result.fee,
+        price_impact: price_impact(
+            pool.spot_price(&token_in, &token_out)?,
+            pool_after.spot_price(&token_in, &token_out)?,
+        )?,
     })
-}
\ No newline at end of file
+}
+pub fn query_concentrated_pool(deps: Deps, pool_id: u64) -> StdResult<ConcentratedPoolInfo> {
+    let pool = CONCENTRATED_POOLS.load(deps.storage, pool_id)?;
+    Ok(concentrated_pool_info(pool))
+}
+
+pub fn query_concentrated_pools(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<ConcentratedPoolInfo>> {
+    let limit = limit.unwrap_or(10).min(30) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    CONCENTRATED_POOLS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| Ok(concentrated_pool_info(item?.1)))
+        .collect()
+}
+
+fn concentrated_pool_info(pool: ConcentratedPool) -> ConcentratedPoolInfo {
+    ConcentratedPoolInfo {
+        pool_id: pool.pool_id,
+        token_a: pool.token_a,
+        token_b: pool.token_b,
+        fee_rate: pool.fee_rate,
+        tick_spacing: pool.tick_spacing,
+        price: pool.sqrt_price * pool.sqrt_price,
+        sqrt_price: pool.sqrt_price,
+        current_tick: pool.tick,
+        liquidity: pool.liquidity,
+    }
+}
+
+// Active liquidity at the current price, and the liquidity change at every initialized
+// tick so the liquidity over any price range can be reconstructed
+pub fn query_active_liquidity(
+    deps: Deps,
+    pool_id: u64,
+    start_after: Option<i32>,
+    limit: Option<u32>,
+) -> StdResult<ActiveLiquidityResponse> {
+    let pool = CONCENTRATED_POOLS.load(deps.storage, pool_id)?;
+    let limit = limit.unwrap_or(10).min(30) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let ticks = TICKS
+        .prefix(pool_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (tick, info) = item?;
+            Ok(TickLiquidity {
+                tick,
+                liquidity_net: info.liquidity_net,
+                liquidity_gross: info.liquidity_gross,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(ActiveLiquidityResponse {
+        current_tick: pool.tick,
+        sqrt_price: pool.sqrt_price,
+        liquidity: pool.liquidity,
+        ticks,
+    })
+}
+
+pub fn query_position(deps: Deps, position_id: u64) -> StdResult<PositionInfo> {
+    let position = POSITIONS.load(deps.storage, position_id)?;
+    position_info(deps, position_id, position)
+}
+
+pub fn query_positions(
+    deps: Deps,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<PositionInfo>> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(10).min(30) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    OWNER_POSITIONS
+        .prefix(owner)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|position_id| {
+            let position_id = position_id?;
+            position_info(
+                deps,
+                position_id,
+                POSITIONS.load(deps.storage, position_id)?,
+            )
+        })
+        .collect()
+}
+
+// Position with its withdrawable amounts and fees, including those earned since it was
+// last updated
+fn position_info(
+    deps: Deps,
+    position_id: u64,
+    position: ConcentratedPosition,
+) -> StdResult<PositionInfo> {
+    let pool = CONCENTRATED_POOLS.load(deps.storage, position.pool_id)?;
+    let lower = TICKS.load(deps.storage, (pool.pool_id, position.lower_tick))?;
+    let upper = TICKS.load(deps.storage, (pool.pool_id, position.upper_tick))?;
+
+    let (inside_a, inside_b) =
+        pool.fee_growth_inside(position.lower_tick, &lower, position.upper_tick, &upper);
+    let fees_a = position.tokens_owed_a
+        + fees_earned(
+            position.liquidity,
+            inside_a.wrapping_sub(position.fee_growth_inside_a_last),
+        )?;
+    let fees_b = position.tokens_owed_b
+        + fees_earned(
+            position.liquidity,
+            inside_b.wrapping_sub(position.fee_growth_inside_b_last),
+        )?;
+
+    let (amount_a, amount_b) = pool.amounts_for_liquidity(
+        position.lower_tick,
+        position.upper_tick,
+        position.liquidity,
+        false,
+    )?;
+
+    Ok(PositionInfo {
+        position_id,
+        owner: position.owner,
+        pool_id: position.pool_id,
+        lower_tick: position.lower_tick,
+        upper_tick: position.upper_tick,
+        liquidity: position.liquidity,
+        amount_a,
+        amount_b,
+        fees_a,
+        fees_b,
+    })
+}
+
+pub fn query_concentrated_simulation(
+    deps: Deps,
+    pool_id: u64,
+    token_in: AssetInfo,
+    amount_in: Uint128,
+) -> StdResult<SimulationResponse> {
+    let pool = CONCENTRATED_POOLS.load(deps.storage, pool_id)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut pool_after = pool.clone();
+    let (result, _) = pool_after.swap(
+        deps.storage,
+        &token_in,
+        amount_in,
+        config.protocol_fee_share,
+    )?;
+
+    Ok(SimulationResponse {
+        amount_out: result.amount_out,
+        fee: result.fee,
+        price_impact: price_impact(
+            pool.spot_price(&token_in)?,
+            pool_after.spot_price(&token_in)?,
+        )?,
+    })
+}
+
+pub fn query_limit_order(deps: Deps, order_id: u64) -> StdResult<LimitOrderInfo> {
+    Ok(limit_order_info(ORDERS.load(deps.storage, order_id)?))
+}
+
+pub fn query_limit_orders(
+    deps: Deps,
+    offer_token: AssetInfo,
+    ask_token: AssetInfo,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<LimitOrderInfo>> {
+    let limit = limit.unwrap_or(10).min(30) as usize;
+    let start = match start_after {
+        Some(order_id) => Some(Bound::exclusive(
+            order_book_key(&ORDERS.load(deps.storage, order_id)?).2,
+        )),
+        None => None,
+    };
+
+    ORDER_BOOK
+        .prefix((offer_token.to_string(), ask_token.to_string()))
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, order_id) = item?;
+            Ok(limit_order_info(ORDERS.load(deps.storage, order_id)?))
+        })
+        .collect()
+}
+
+pub fn query_limit_orders_by_owner(
+    deps: Deps,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<LimitOrderInfo>> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(10).min(30) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    OWNER_ORDERS
+        .prefix(owner)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|order_id| Ok(limit_order_info(ORDERS.load(deps.storage, order_id?)?)))
+        .collect()
+}
+
+fn limit_order_info(order: LimitOrder) -> LimitOrderInfo {
+    LimitOrderInfo {
+        order_id: order.order_id,
+        owner: order.owner,
+        offer_token: order.offer_token,
+        ask_token: order.ask_token,
+        price: order.price,
+        offer_amount: order.offer_amount,
+        remaining: order.remaining,
+        received: order.received,
+    }
+}
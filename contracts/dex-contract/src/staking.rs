@@ -0,0 +1,470 @@
+use cosmwasm_std::{
+    Addr, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult, Storage, Uint128,
+    Uint256,
+};
+use cw20::Cw20Coin;
+use cw_storage_plus::Bound;
+
+use crate::error::ContractError;
+use crate::execute::{collect_funds, transfer_msg};
+use crate::math::fees_earned;
+use crate::state::{
+    liquidity_key, pool_key, pool_reward_schedule_key, AssetInfo, LiquidityPosition,
+    RewardSchedule, Stake, StakerReward, CONFIG, LIQUIDITY, POOLS, POOL_REWARD_SCHEDULES,
+    REWARDS_LAST_UPDATE, REWARD_SCHEDULES, REWARD_SCHEDULE_COUNT, STAKER_REWARDS, STAKES,
+    TOTAL_BONDED,
+};
+
+// Most reward schedules of a pool that may not have ended yet at the same time
+pub const MAX_ACTIVE_REWARD_SCHEDULES: usize = 10;
+
+// Longest reward schedule, in seconds
+pub const MAX_REWARD_DURATION: u64 = 365 * 24 * 60 * 60;
+
+// Starts paying `rate` of `reward` per second to the bonded shares of a pair pool between
+// start_time and end_time. The whole emission is paid in up front (admin only).
+#[allow(clippy::too_many_arguments)]
+pub fn execute_create_reward_schedule(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    received: Option<Cw20Coin>,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
+    reward: AssetInfo,
+    start_time: u64,
+    end_time: u64,
+    rate: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let now = env.block.time.seconds();
+    if start_time < now || end_time <= start_time || end_time - start_time > MAX_REWARD_DURATION {
+        return Err(ContractError::InvalidRewardSchedule {
+            max_duration: MAX_REWARD_DURATION,
+        });
+    }
+    if rate.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+    if let AssetInfo::Cw20 { contract_addr } = &reward {
+        deps.api.addr_validate(contract_addr.as_str())?;
+    }
+
+    let key = pool_key(&token_a, &token_b);
+    let pool = POOLS
+        .may_load(deps.storage, key.clone())?
+        .ok_or(ContractError::PoolNotFound {})?;
+
+    let active = pool_reward_schedules(deps.storage, &key, now)?.len();
+    if active >= MAX_ACTIVE_REWARD_SCHEDULES {
+        return Err(ContractError::TooManyRewardSchedules {
+            max: MAX_ACTIVE_REWARD_SCHEDULES,
+        });
+    }
+
+    let total = rate
+        .checked_mul(Uint128::from(end_time - start_time))
+        .map_err(StdError::from)?;
+
+    let schedule_id = REWARD_SCHEDULE_COUNT
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    REWARD_SCHEDULE_COUNT.save(deps.storage, &schedule_id)?;
+
+    let schedule = RewardSchedule {
+        schedule_id,
+        token_a: pool.token_a,
+        token_b: pool.token_b,
+        reward: reward.clone(),
+        start_time,
+        end_time,
+        rate,
+        reward_per_share: Uint256::zero(),
+        last_update: now,
+        undistributed: Uint128::zero(),
+    };
+    REWARD_SCHEDULES.save(deps.storage, schedule_id, &schedule)?;
+    POOL_REWARD_SCHEDULES.save(
+        deps.storage,
+        pool_reward_schedule_key(&schedule),
+        &schedule_id,
+    )?;
+
+    // Take custody of the whole emission
+    let messages = collect_funds(&env, &info, &received, &[(&reward, total)])?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "create_reward_schedule")
+        .add_attribute("schedule_id", schedule_id.to_string())
+        .add_attribute("reward", reward.to_string())
+        .add_attribute("start_time", start_time.to_string())
+        .add_attribute("end_time", end_time.to_string())
+        .add_attribute("rate", rate)
+        .add_attribute("total", total))
+}
+
+// Bonds pool shares to earn the pool's rewards. Pools with an LP token bond LP tokens
+// sent through the Receive hook; other pools bond shares of the sender's LIQUIDITY
+// position.
+pub fn execute_bond(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    received: Option<Cw20Coin>,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    let key = pool_key(&token_a, &token_b);
+    let pool = POOLS
+        .may_load(deps.storage, key.clone())?
+        .ok_or(ContractError::PoolNotFound {})?;
+
+    match (&pool.lp_token_address, &received) {
+        // LP tokens already received by the hook
+        (Some(lp_token), Some(received)) if received.address == *lp_token => {}
+        (None, None) => {
+            let liq_key = liquidity_key(&info.sender, &pool.token_a, &pool.token_b);
+            let mut position = LIQUIDITY
+                .may_load(deps.storage, liq_key.clone())?
+                .unwrap_or(LiquidityPosition {
+                    liquidity: Uint128::zero(),
+                });
+
+            if position.liquidity < amount {
+                return Err(ContractError::InsufficientFunds {});
+            }
+
+            position.liquidity -= amount;
+            LIQUIDITY.save(deps.storage, liq_key, &position)?;
+        }
+        _ => return Err(ContractError::InvalidLpToken {}),
+    }
+
+    let (stake, _) = update_stake(
+        deps.storage,
+        &key,
+        &info.sender,
+        env.block.time.seconds(),
+        |bonded| Ok(bonded.checked_add(amount).map_err(StdError::from)?),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "bond")
+        .add_attribute("token_a", pool.token_a.to_string())
+        .add_attribute("token_b", pool.token_b.to_string())
+        .add_attribute("amount", amount)
+        .add_attribute("bonded", stake.bonded))
+}
+
+// Returns bonded shares, as LP tokens or to the LIQUIDITY position. Rewards earned so far
+// stay claimable.
+pub fn execute_unbond(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::ZeroAmount {});
+    }
+
+    let key = pool_key(&token_a, &token_b);
+    let pool = POOLS
+        .may_load(deps.storage, key.clone())?
+        .ok_or(ContractError::PoolNotFound {})?;
+
+    let (stake, _) = update_stake(
+        deps.storage,
+        &key,
+        &info.sender,
+        env.block.time.seconds(),
+        |bonded| {
+            bonded
+                .checked_sub(amount)
+                .map_err(|_| ContractError::InsufficientFunds {})
+        },
+    )?;
+
+    let mut messages = vec![];
+
+    if let Some(lp_token) = &pool.lp_token_address {
+        let lp_token = AssetInfo::Cw20 {
+            contract_addr: Addr::unchecked(lp_token),
+        };
+        messages.push(transfer_msg(&lp_token, &info.sender, amount)?);
+    } else {
+        let liq_key = liquidity_key(&info.sender, &pool.token_a, &pool.token_b);
+        let mut position = LIQUIDITY
+            .may_load(deps.storage, liq_key.clone())?
+            .unwrap_or(LiquidityPosition {
+                liquidity: Uint128::zero(),
+            });
+
+        position.liquidity += amount;
+        LIQUIDITY.save(deps.storage, liq_key, &position)?;
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "unbond")
+        .add_attribute("token_a", pool.token_a.to_string())
+        .add_attribute("token_b", pool.token_b.to_string())
+        .add_attribute("amount", amount)
+        .add_attribute("bonded", stake.bonded))
+}
+
+// Pays out every reward the sender has earned in a pool
+pub fn execute_claim(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_a: AssetInfo,
+    token_b: AssetInfo,
+) -> Result<Response, ContractError> {
+    let key = pool_key(&token_a, &token_b);
+    if !POOLS.has(deps.storage, key.clone()) {
+        return Err(ContractError::PoolNotFound {});
+    }
+
+    let now = env.block.time.seconds();
+    let (stake, _) = update_stake(deps.storage, &key, &info.sender, now, Ok)?;
+
+    let mut messages = vec![];
+    let mut claimed = vec![];
+
+    for (schedule, mut staker_reward) in staker_pool_rewards(deps.storage, &key, &info.sender)? {
+        if staker_reward.pending.is_zero() {
+            continue;
+        }
+
+        messages.push(transfer_msg(
+            &schedule.reward,
+            &info.sender,
+            staker_reward.pending,
+        )?);
+        claimed.push(format!("{}{}", staker_reward.pending, schedule.reward));
+
+        staker_reward.pending = Uint128::zero();
+        save_staker_reward(
+            deps.storage,
+            &info.sender,
+            &schedule,
+            &staker_reward,
+            stake.bonded,
+            now,
+        )?;
+    }
+
+    if claimed.is_empty() {
+        return Err(ContractError::NoRewards {});
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "claim")
+        .add_attribute("claimed", claimed.join(",")))
+}
+
+// Pays the admin the part of an ended schedule's emission that fell in periods with
+// nothing bonded, which no staker can earn
+pub fn execute_reclaim_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    schedule_id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut schedule = REWARD_SCHEDULES
+        .may_load(deps.storage, schedule_id)?
+        .ok_or(ContractError::RewardScheduleNotFound { schedule_id })?;
+
+    let now = env.block.time.seconds();
+    if now < schedule.end_time {
+        return Err(ContractError::RewardScheduleActive {
+            end_time: schedule.end_time,
+        });
+    }
+
+    let key = pool_key(&schedule.token_a, &schedule.token_b);
+    let total_bonded = TOTAL_BONDED
+        .may_load(deps.storage, key)?
+        .unwrap_or_default();
+    schedule.accrue(total_bonded, now)?;
+
+    let amount = schedule.undistributed;
+    if amount.is_zero() {
+        return Err(ContractError::NoRewards {});
+    }
+
+    schedule.undistributed = Uint128::zero();
+    REWARD_SCHEDULES.save(deps.storage, schedule_id, &schedule)?;
+
+    Ok(Response::new()
+        .add_message(transfer_msg(&schedule.reward, &info.sender, amount)?)
+        .add_attribute("method", "reclaim_rewards")
+        .add_attribute("schedule_id", schedule_id.to_string())
+        .add_attribute("amount", amount))
+}
+
+// Brings the pool's schedules and the staker's rewards up to `now`, then changes the
+// staker's bond with `change`. The new stake and the settled schedules with the staker's
+// rewards in them are returned.
+fn update_stake<F>(
+    storage: &mut dyn Storage,
+    key: &(String, String),
+    staker: &Addr,
+    now: u64,
+    change: F,
+) -> Result<(Stake, Vec<(RewardSchedule, StakerReward)>), ContractError>
+where
+    F: FnOnce(Uint128) -> Result<Uint128, ContractError>,
+{
+    let (mut stake, rewards) = settle_rewards(storage, key, staker, now)?;
+    let total_bonded = TOTAL_BONDED
+        .may_load(storage, key.clone())?
+        .unwrap_or_default();
+
+    let bonded = change(stake.bonded)?;
+    let total_bonded = (total_bonded + bonded)
+        .checked_sub(stake.bonded)
+        .map_err(StdError::from)?;
+    stake.bonded = bonded;
+    stake.last_update = now;
+
+    for (schedule, staker_reward) in &rewards {
+        REWARD_SCHEDULES.save(storage, schedule.schedule_id, schedule)?;
+        save_staker_reward(storage, staker, schedule, staker_reward, stake.bonded, now)?;
+    }
+
+    TOTAL_BONDED.save(storage, key.clone(), &total_bonded)?;
+    REWARDS_LAST_UPDATE.save(storage, key.clone(), &now)?;
+
+    let stake_key = (staker.clone(), key.0.clone(), key.1.clone());
+    if stake.bonded.is_zero() {
+        STAKES.remove(storage, stake_key);
+    } else {
+        STAKES.save(storage, stake_key, &stake)?;
+    }
+
+    Ok((stake, rewards))
+}
+
+// Keeps a staker's progress in a schedule while it can still earn or has rewards to claim
+fn save_staker_reward(
+    storage: &mut dyn Storage,
+    staker: &Addr,
+    schedule: &RewardSchedule,
+    staker_reward: &StakerReward,
+    bonded: Uint128,
+    now: u64,
+) -> StdResult<()> {
+    let key = (staker.clone(), schedule.schedule_id);
+    if staker_reward.pending.is_zero() && (bonded.is_zero() || schedule.end_time <= now) {
+        STAKER_REWARDS.remove(storage, key);
+        Ok(())
+    } else {
+        STAKER_REWARDS.save(storage, key, staker_reward)
+    }
+}
+
+// The staker's stake in a pool and the pool's schedules that may have changed since the
+// staker's or the pool's last update, accrued up to `now` with the staker's rewards in
+// them. Schedules that ended before both updates are already settled. Nothing is saved.
+pub fn settle_rewards(
+    storage: &dyn Storage,
+    key: &(String, String),
+    staker: &Addr,
+    now: u64,
+) -> StdResult<(Stake, Vec<(RewardSchedule, StakerReward)>)> {
+    let stake = STAKES
+        .may_load(storage, (staker.clone(), key.0.clone(), key.1.clone()))?
+        .unwrap_or(Stake {
+            bonded: Uint128::zero(),
+            last_update: now,
+        });
+    let total_bonded = TOTAL_BONDED
+        .may_load(storage, key.clone())?
+        .unwrap_or_default();
+    let pool_last_update = REWARDS_LAST_UPDATE
+        .may_load(storage, key.clone())?
+        .unwrap_or(now);
+
+    let mut rewards = vec![];
+
+    for mut schedule in
+        pool_reward_schedules(storage, key, stake.last_update.min(pool_last_update))?
+    {
+        schedule.accrue(total_bonded, now)?;
+
+        let mut staker_reward = STAKER_REWARDS
+            .may_load(storage, (staker.clone(), schedule.schedule_id))?
+            .unwrap_or_default();
+        let earned = fees_earned(
+            stake.bonded,
+            schedule.reward_per_share - staker_reward.reward_per_share_paid,
+        )?;
+        staker_reward.pending += earned;
+        staker_reward.reward_per_share_paid = schedule.reward_per_share;
+
+        rewards.push((schedule, staker_reward));
+    }
+
+    Ok((stake, rewards))
+}
+
+// The staker's saved progress in the schedules of a pool, including ended schedules with
+// rewards left to claim
+pub fn staker_pool_rewards(
+    storage: &dyn Storage,
+    key: &(String, String),
+    staker: &Addr,
+) -> StdResult<Vec<(RewardSchedule, StakerReward)>> {
+    let mut rewards = vec![];
+
+    for item in STAKER_REWARDS
+        .prefix(staker.clone())
+        .range(storage, None, None, Order::Ascending)
+    {
+        let (schedule_id, staker_reward) = item?;
+        let schedule = REWARD_SCHEDULES.load(storage, schedule_id)?;
+        if pool_key(&schedule.token_a, &schedule.token_b) == *key {
+            rewards.push((schedule, staker_reward));
+        }
+    }
+
+    Ok(rewards)
+}
+
+// Schedules of a pool ending after `after`, soonest first
+pub fn pool_reward_schedules(
+    storage: &dyn Storage,
+    key: &(String, String),
+    after: u64,
+) -> StdResult<Vec<RewardSchedule>> {
+    let start = Bound::inclusive(after.saturating_add(1).to_be_bytes().to_vec());
+
+    POOL_REWARD_SCHEDULES
+        .prefix((key.0.clone(), key.1.clone()))
+        .range(storage, Some(start), None, Order::Ascending)
+        .map(|item| REWARD_SCHEDULES.load(storage, item?.1))
+        .collect()
+}
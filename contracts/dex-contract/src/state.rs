@@ -1,23 +1,1076 @@
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, Map};
+use std::fmt;
+
+use cosmwasm_std::{
+    Addr, Coin, Decimal256, Empty, Int128, Order, StdError, StdResult, Storage, Uint128, Uint256,
+};
+use cw_storage_plus::{Bound, Item, Map};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::math::{
+    add_liquidity_delta, amount_a_delta, amount_b_delta, ceil_multiply_ratio,
+    compute_amount_in_to_price, compute_reverse_swap, compute_stable_deposit, compute_stable_price,
+    compute_stable_reverse_swap, compute_stable_swap, compute_swap, compute_swap_step,
+    compute_weighted_single_exit, compute_weighted_single_join, compute_weighted_swap,
+    compute_zap_swap_amount, div_price_floor, fee_growth, mul_price_ceil, sqrt_price_at_tick,
+    tick_at_sqrt_price, ReverseSwapResult, SwapResult, FEE_DENOMINATOR, MAX_TICK, MIN_TICK,
+};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
-    pub admin: Addr,
-    pub fee_rate: Uint128, // Fee rate in basis points
-    pub lp_token_code_id: u64, // Code ID for CW20 LP token contracts
+    pub admin: Option<Addr>,         // None once the admin role is renounced
+    pub fee_rate: Uint128,           // Default fee rate in basis points for new pools
+    pub fee_tiers: Vec<Uint128>,     // Fee rates (basis points) pools may use
+    pub lp_token_code_id: u64,       // Code ID for CW20 LP token contracts
+    pub treasury: Addr,              // Receives the collected protocol fees
+    pub protocol_fee_share: Uint128, // Share of each swap fee kept for the protocol, in basis points of the fee
+    pub native_denoms: Vec<String>,  // Bank denoms new pools may use
+    pub native_denom_prefixes: Vec<String>, // New pools may also use any bank denom starting with one of these
+    pub pauser: Addr, // May pause and unpause the contract or single pools, as may the admin
+    pub max_fee_rate: Uint128, // Highest fee tier allowed, in basis points; fixed at instantiation
+    pub fee_change_delay: u64, // Seconds a queued fee change waits before it can be applied
+    pub pool_creation_fee: Option<PoolCreationFee>, // None while pool creation is free
+    pub asset_list_mode: AssetListMode, // Which assets new pools may use
+    pub restrict_swaps: bool, // Also refuse swaps of assets the asset list keeps out of new pools
+}
+
+impl Config {
+    pub fn is_admin(&self, addr: &Addr) -> bool {
+        self.admin.as_ref() == Some(addr)
+    }
+}
+
+// How the asset list governs which assets may be pooled
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetListMode {
+    // Any valid asset; the asset list is ignored
+    Permissionless {},
+    // Only listed assets
+    AllowList {},
+    // Any valid asset that is not delisted
+    DenyList {},
+}
+
+impl fmt::Display for AssetListMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetListMode::Permissionless {} => write!(f, "permissionless"),
+            AssetListMode::AllowList {} => write!(f, "allow_list"),
+            AssetListMode::DenyList {} => write!(f, "deny_list"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ListingStatus {
+    Listed,
+    Delisted,
+}
+
+// Entry of the admin-curated asset list
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetListing {
+    pub asset: AssetInfo,
+    pub status: ListingStatus,
+    pub updated_at: u64, // Block time in seconds
+}
+
+// Native coins charged for creating a pool of any type
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PoolCreationFee {
+    pub fee: Coin,
+    pub collector: Option<Addr>, // Receives the fee; it is burned if None
+}
+
+// Admin role offered to new_admin until it accepts or the proposal expires
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdminProposal {
+    pub new_admin: Addr,
+    pub expires_at: u64, // Block time in seconds
+}
+
+// A fee setting change waiting out Config.fee_change_delay
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeChange {
+    // Default fee rate for new pools
+    FeeRate {
+        fee_rate: Uint128,
+    },
+    PoolFee {
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+        fee_rate: Uint128,
+    },
+    ProtocolFeeShare {
+        protocol_fee_share: Uint128,
+    },
+}
+
+impl FeeChange {
+    // Whether both changes are to the same setting
+    pub fn same_setting(&self, other: &FeeChange) -> bool {
+        match (self, other) {
+            (FeeChange::FeeRate { .. }, FeeChange::FeeRate { .. }) => true,
+            (FeeChange::ProtocolFeeShare { .. }, FeeChange::ProtocolFeeShare { .. }) => true,
+            (
+                FeeChange::PoolFee {
+                    token_a, token_b, ..
+                },
+                FeeChange::PoolFee {
+                    token_a: other_a,
+                    token_b: other_b,
+                    ..
+                },
+            ) => pool_key(token_a, token_b) == pool_key(other_a, other_b),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingFeeChange {
+    pub change_id: u64,
+    pub change: FeeChange,
+    pub queued_at: u64,    // Block time in seconds
+    pub effective_at: u64, // Earliest block time, in seconds, it can be applied at
+}
+
+// A token traded by the DEX: a bank denom (including IBC and tokenfactory denoms) or a
+// CW20 contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetInfo {
+    Native { denom: String },
+    Cw20 { contract_addr: Addr },
+}
+
+impl AssetInfo {
+    pub fn is_native(&self) -> bool {
+        matches!(self, AssetInfo::Native { .. })
+    }
+}
+
+// The denom or contract address, which also identifies the asset in storage keys
+impl fmt::Display for AssetInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetInfo::Native { denom } => write!(f, "{}", denom),
+            AssetInfo::Cw20 { contract_addr } => write!(f, "{}", contract_addr),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolType {
+    // x * y = k
+    ConstantProduct {},
+    // Curve StableSwap invariant; while ramping, amp is the value being ramped to
+    Stable { amp: u64 },
+}
+
+// Linear change of a stable pool's amp from initial_amp at initial_time to the
+// PoolType::Stable amp at future_time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AmpRamp {
+    pub initial_amp: u64,
+    pub initial_time: u64,
+    pub future_time: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Pool {
-    pub token_a: String,
-    pub token_b: String,
+    pub token_a: AssetInfo,
+    pub token_b: AssetInfo,
     pub reserve_a: Uint128,
     pub reserve_b: Uint128,
     pub total_liquidity: Uint128,
     pub lp_token_address: Option<String>, // Address of the CW20 LP token contract (optional for backward compatibility)
+    pub fee_rate: Uint128,                // Swap fee in basis points, one of Config.fee_tiers
+    pub price_a_cumulative: Uint256, // Sum of (reserve_b / reserve_a) * seconds, 18 decimal fixed point
+    pub price_b_cumulative: Uint256, // Sum of (reserve_a / reserve_b) * seconds, 18 decimal fixed point
+    pub block_time_last: u64,        // Block time (seconds) the accumulators were last updated at
+    pub pool_type: PoolType,
+    pub amp_ramp: Option<AmpRamp>, // Last amp ramp of a stable pool
+}
+
+impl Pool {
+    // Reserves as (reserve of token_in, reserve of the other token)
+    pub fn reserves(&self, token_in: &AssetInfo) -> (Uint128, Uint128) {
+        if *token_in == self.token_a {
+            (self.reserve_a, self.reserve_b)
+        } else {
+            (self.reserve_b, self.reserve_a)
+        }
+    }
+
+    // Amplification of a stable pool at `now`, following any ramp in progress
+    pub fn amp(&self, now: u64) -> Option<u64> {
+        match self.pool_type {
+            PoolType::ConstantProduct {} => None,
+            PoolType::Stable { amp } => Some(self.ramped_amp(amp, now)),
+        }
+    }
+
+    fn ramped_amp(&self, future_amp: u64, now: u64) -> u64 {
+        match &self.amp_ramp {
+            Some(ramp) if now < ramp.future_time => {
+                let elapsed = now.saturating_sub(ramp.initial_time) as u128;
+                let duration = (ramp.future_time - ramp.initial_time) as u128;
+                let (initial, future) = (ramp.initial_amp as u128, future_amp as u128);
+                let amp = if future > initial {
+                    initial + (future - initial) * elapsed / duration
+                } else {
+                    initial - (initial - future) * elapsed / duration
+                };
+                amp as u64
+            }
+            _ => future_amp,
+        }
+    }
+
+    // Adds the current spot prices, weighted by the seconds since the last update, to the
+    // cumulative prices. Must run before the reserves change so the old prices are used.
+    pub fn accumulate_prices(&mut self, now: u64) -> StdResult<()> {
+        let elapsed = now.saturating_sub(self.block_time_last);
+        if elapsed > 0 && !self.reserve_a.is_zero() && !self.reserve_b.is_zero() {
+            let price_a = self.spot_price(&self.token_a, now)?;
+            let price_b = self.spot_price(&self.token_b, now)?;
+            self.price_a_cumulative += price_a.atomics() * Uint256::from(elapsed);
+            self.price_b_cumulative += price_b.atomics() * Uint256::from(elapsed);
+        }
+        self.block_time_last = now;
+        Ok(())
+    }
+
+    // Marginal price of token_in in units of the other token at `now`
+    pub fn spot_price(&self, token_in: &AssetInfo, now: u64) -> StdResult<Decimal256> {
+        let (reserve_in, reserve_out) = self.reserves(token_in);
+        match self.pool_type {
+            PoolType::ConstantProduct {} => Ok(Decimal256::from_ratio(reserve_out, reserve_in)),
+            PoolType::Stable { amp } => {
+                compute_stable_price(self.ramped_amp(amp, now), reserve_in, reserve_out)
+            }
+        }
+    }
+
+    // Output of swapping amount_in of token_in at `now`, without changing the reserves
+    pub fn simulate_swap(
+        &self,
+        token_in: &AssetInfo,
+        amount_in: Uint128,
+        protocol_fee_share: Uint128,
+        now: u64,
+    ) -> StdResult<SwapResult> {
+        let (reserve_in, reserve_out) = self.reserves(token_in);
+        match self.pool_type {
+            PoolType::ConstantProduct {} => compute_swap(
+                reserve_in,
+                reserve_out,
+                amount_in,
+                self.fee_rate,
+                protocol_fee_share,
+            ),
+            PoolType::Stable { amp } => compute_stable_swap(
+                reserve_in,
+                reserve_out,
+                amount_in,
+                self.fee_rate,
+                protocol_fee_share,
+                self.ramped_amp(amp, now),
+            ),
+        }
+    }
+
+    // Input of token_in needed to take amount_out of the other token at `now`, without
+    // changing the reserves
+    pub fn simulate_reverse_swap(
+        &self,
+        token_in: &AssetInfo,
+        amount_out: Uint128,
+        protocol_fee_share: Uint128,
+        now: u64,
+    ) -> StdResult<ReverseSwapResult> {
+        let (reserve_in, reserve_out) = self.reserves(token_in);
+        match self.pool_type {
+            PoolType::ConstantProduct {} => compute_reverse_swap(
+                reserve_in,
+                reserve_out,
+                amount_out,
+                self.fee_rate,
+                protocol_fee_share,
+            ),
+            PoolType::Stable { amp } => compute_stable_reverse_swap(
+                reserve_in,
+                reserve_out,
+                amount_out,
+                self.fee_rate,
+                protocol_fee_share,
+                self.ramped_amp(amp, now),
+            ),
+        }
+    }
+
+    // Amounts taken from an offer of (offer_a, offer_b) and the shares they mint, as
+    // (deposit_a, deposit_b, liquidity). Constant product pools only take the amounts
+    // matching the pool ratio; stable pools take the whole offer.
+    pub fn deposit(
+        &self,
+        offer_a: Uint128,
+        offer_b: Uint128,
+        now: u64,
+    ) -> StdResult<(Uint128, Uint128, Uint128)> {
+        match self.pool_type {
+            PoolType::ConstantProduct {} => {
+                let optimal_b = offer_a.multiply_ratio(self.reserve_b, self.reserve_a);
+                let (deposit_a, deposit_b) = if optimal_b <= offer_b {
+                    (offer_a, optimal_b)
+                } else {
+                    (
+                        offer_b.multiply_ratio(self.reserve_a, self.reserve_b),
+                        offer_b,
+                    )
+                };
+
+                let liquidity_a = deposit_a.multiply_ratio(self.total_liquidity, self.reserve_a);
+                let liquidity_b = deposit_b.multiply_ratio(self.total_liquidity, self.reserve_b);
+                Ok((deposit_a, deposit_b, liquidity_a.min(liquidity_b)))
+            }
+            PoolType::Stable { amp } => {
+                let liquidity = compute_stable_deposit(
+                    [self.reserve_a, self.reserve_b],
+                    [offer_a, offer_b],
+                    self.total_liquidity,
+                    self.fee_rate,
+                    self.ramped_amp(amp, now),
+                )?;
+                Ok((offer_a, offer_b, liquidity))
+            }
+        }
+    }
+
+    // Reserves paid out for burning `liquidity` shares, as (share_a, share_b). Both pool
+    // types pay out in proportion to the reserves; for stable pools this is Curve's
+    // balanced remove_liquidity, which needs no fee as it leaves the price unchanged.
+    pub fn withdraw(&self, liquidity: Uint128) -> (Uint128, Uint128) {
+        (
+            liquidity.multiply_ratio(self.reserve_a, self.total_liquidity),
+            liquidity.multiply_ratio(self.reserve_b, self.total_liquidity),
+        )
+    }
+
+    // Swaps the part of a single-sided deposit of amount_in of token_in that makes the
+    // rest and the output a balanced deposit, and returns that part with the swap result.
+    // Stable pools take imbalanced deposits, so nothing is swapped there.
+    pub fn zap_swap(
+        &mut self,
+        token_in: &AssetInfo,
+        amount_in: Uint128,
+        protocol_fee_share: Uint128,
+        now: u64,
+    ) -> StdResult<(Uint128, SwapResult)> {
+        let swap_amount = match self.pool_type {
+            PoolType::ConstantProduct {} => {
+                let (reserve_in, _) = self.reserves(token_in);
+                compute_zap_swap_amount(reserve_in, amount_in, self.fee_rate)?
+            }
+            PoolType::Stable { .. } => Uint128::zero(),
+        };
+
+        if swap_amount.is_zero() {
+            return Ok((swap_amount, SwapResult::default()));
+        }
+
+        let result = self.swap(token_in, swap_amount, protocol_fee_share, now)?;
+        Ok((swap_amount, result))
+    }
+
+    // Burns `liquidity` shares and swaps the share of the other token for token_out
+    // against the reserves left. Returns the whole amount of token_out paid out and the
+    // swap result.
+    pub fn zap_out(
+        &mut self,
+        token_out: &AssetInfo,
+        liquidity: Uint128,
+        protocol_fee_share: Uint128,
+        now: u64,
+    ) -> StdResult<(Uint128, SwapResult)> {
+        let (share_a, share_b) = self.withdraw(liquidity);
+        self.total_liquidity = self.total_liquidity.checked_sub(liquidity)?;
+        self.reserve_a -= share_a;
+        self.reserve_b -= share_b;
+
+        let (share_out, share_in, token_in) = if *token_out == self.token_a {
+            (share_a, share_b, self.token_b.clone())
+        } else {
+            (share_b, share_a, self.token_a.clone())
+        };
+
+        let result = if share_in.is_zero() {
+            SwapResult::default()
+        } else {
+            self.swap(&token_in, share_in, protocol_fee_share, now)?
+        };
+
+        Ok((share_out + result.amount_out, result))
+    }
+
+    // Swaps amount_in of token_in against the reserves and returns the output.
+    // The protocol's cut of the fee is not added to the reserves.
+    pub fn swap(
+        &mut self,
+        token_in: &AssetInfo,
+        amount_in: Uint128,
+        protocol_fee_share: Uint128,
+        now: u64,
+    ) -> StdResult<SwapResult> {
+        let result = self.simulate_swap(token_in, amount_in, protocol_fee_share, now)?;
+
+        if *token_in == self.token_a {
+            self.reserve_a += amount_in - result.protocol_fee;
+            self.reserve_b -= result.amount_out;
+        } else {
+            self.reserve_b += amount_in - result.protocol_fee;
+            self.reserve_a -= result.amount_out;
+        }
+
+        Ok(result)
+    }
+
+    // Takes exactly amount_out of the other token out of the reserves and returns the
+    // amount_in of token_in it costs
+    pub fn reverse_swap(
+        &mut self,
+        token_in: &AssetInfo,
+        amount_out: Uint128,
+        protocol_fee_share: Uint128,
+        now: u64,
+    ) -> StdResult<ReverseSwapResult> {
+        let result = self.simulate_reverse_swap(token_in, amount_out, protocol_fee_share, now)?;
+
+        if *token_in == self.token_a {
+            self.reserve_a += result.amount_in - result.protocol_fee;
+            self.reserve_b -= amount_out;
+        } else {
+            self.reserve_b += result.amount_in - result.protocol_fee;
+            self.reserve_a -= amount_out;
+        }
+
+        Ok(result)
+    }
+
+    // Swaps amount_in of token_in against both the reserves and the resting orders selling
+    // the other token, always taking the better price: before each order (best price first)
+    // the pool takes the input it can absorb while still paying at least the order's price.
+    // Orders are not changed in storage; the fills are returned for the caller to apply.
+    // The fee and protocol fee of the result are those of the pool part only.
+    pub fn swap_with_orders(
+        &mut self,
+        storage: &dyn Storage,
+        token_in: &AssetInfo,
+        amount_in: Uint128,
+        protocol_fee_share: Uint128,
+        now: u64,
+    ) -> StdResult<(SwapResult, Vec<OrderFill>)> {
+        let token_out = if *token_in == self.token_a {
+            &self.token_b
+        } else {
+            &self.token_a
+        };
+
+        let mut total = SwapResult {
+            amount_out: Uint128::zero(),
+            fee: Uint128::zero(),
+            protocol_fee: Uint128::zero(),
+        };
+        let mut fills = vec![];
+        let mut remaining = amount_in;
+
+        let order_ids = ORDER_BOOK
+            .prefix((token_out.to_string(), token_in.to_string()))
+            .range(storage, None, None, Order::Ascending)
+            .take(MAX_ORDER_FILLS)
+            .map(|item| item.map(|(_, order_id)| order_id))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        for order_id in order_ids {
+            let order = ORDERS.load(storage, order_id)?;
+
+            // Output per unit of input the order pays
+            let order_price = Decimal256::one()
+                .checked_div(order.price)
+                .map_err(|e| StdError::generic_err(e.to_string()))?;
+            let pool_amount =
+                self.amount_in_to_price(token_in, order_price, remaining, protocol_fee_share, now)?;
+            if !pool_amount.is_zero() {
+                let result = self.swap(token_in, pool_amount, protocol_fee_share, now)?;
+                total.amount_out += result.amount_out;
+                total.fee += result.fee;
+                total.protocol_fee += result.protocol_fee;
+                remaining -= pool_amount;
+            }
+
+            if remaining.is_zero() {
+                break;
+            }
+
+            // Take the whole order if the input covers it, otherwise as much as it buys
+            let cost = mul_price_ceil(order.remaining, order.price)?;
+            let (ask_amount, offer_amount) = if remaining >= cost {
+                (cost, order.remaining)
+            } else {
+                (remaining, div_price_floor(remaining, order.price)?)
+            };
+            if offer_amount.is_zero() {
+                break;
+            }
+
+            fills.push(OrderFill {
+                order_id,
+                owner: order.owner,
+                offer_amount,
+                ask_amount,
+            });
+            total.amount_out += offer_amount;
+            remaining -= ask_amount;
+        }
+
+        if !remaining.is_zero() {
+            let result = self.swap(token_in, remaining, protocol_fee_share, now)?;
+            total.amount_out += result.amount_out;
+            total.fee += result.fee;
+            total.protocol_fee += result.protocol_fee;
+        }
+
+        Ok((total, fills))
+    }
+
+    // Largest input of token_in, up to max_amount_in, after which the pool still pays at
+    // least output_price of the other token per token_in at the margin, after the fee.
+    // Constant product pools solve it in closed form. Stable pools bisect, and each step
+    // clones the pool and solves the StableSwap invariant (up to STABLE_MAX_ITERATIONS
+    // Newton steps), so a swap costs up to MAX_ORDER_FILLS * ORDER_PRICE_BISECTIONS solves.
+    fn amount_in_to_price(
+        &self,
+        token_in: &AssetInfo,
+        output_price: Decimal256,
+        max_amount_in: Uint128,
+        protocol_fee_share: Uint128,
+        now: u64,
+    ) -> StdResult<Uint128> {
+        if let PoolType::ConstantProduct {} = self.pool_type {
+            let (reserve_in, reserve_out) = self.reserves(token_in);
+            let amount_in = compute_amount_in_to_price(
+                reserve_in,
+                reserve_out,
+                output_price,
+                self.fee_rate,
+                protocol_fee_share,
+            )?;
+            return Ok(amount_in.min(max_amount_in));
+        }
+
+        let fee_complement =
+            Decimal256::one() - Decimal256::from_ratio(self.fee_rate, FEE_DENOMINATOR);
+        let pays_at_least = |amount_in: Uint128| -> StdResult<bool> {
+            let mut pool = self.clone();
+            pool.swap(token_in, amount_in, protocol_fee_share, now)?;
+            Ok(pool.spot_price(token_in, now)? * fee_complement >= output_price)
+        };
+
+        if self.spot_price(token_in, now)? * fee_complement < output_price {
+            return Ok(Uint128::zero());
+        }
+        if pays_at_least(max_amount_in)? {
+            return Ok(max_amount_in);
+        }
+
+        let (mut low, mut high) = (Uint128::zero(), max_amount_in);
+        for _ in 0..ORDER_PRICE_BISECTIONS {
+            if high - low <= Uint128::one() {
+                break;
+            }
+            let mid = low + (high - low) / Uint128::from(2u8);
+            if pays_at_least(mid)? {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        Ok(low)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WeightedAsset {
+    pub token: AssetInfo,
+    pub weight: Decimal256, // Normalized, the weights of a pool sum to 1
+    pub reserve: Uint128,
+}
+
+// Balancer-style pool of 2 to 8 assets, identified by pool_id instead of a token pair
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WeightedPool {
+    pub pool_id: u64,
+    pub assets: Vec<WeightedAsset>,
+    pub total_liquidity: Uint128,
+    pub lp_token_address: Option<String>, // Set by the reply once the LP token is instantiated
+    pub fee_rate: Uint128,                // Swap fee in basis points, one of Config.fee_tiers
+}
+
+impl WeightedPool {
+    // Position of token in assets
+    pub fn asset_index(&self, token: &AssetInfo) -> StdResult<usize> {
+        self.assets
+            .iter()
+            .position(|asset| asset.token == *token)
+            .ok_or_else(|| {
+                StdError::generic_err(format!(
+                    "Token {} is not in weighted pool {}",
+                    token, self.pool_id
+                ))
+            })
+    }
+
+    // Marginal price of token_in in units of token_out:
+    // (reserve_out / weight_out) / (reserve_in / weight_in)
+    pub fn spot_price(&self, token_in: &AssetInfo, token_out: &AssetInfo) -> StdResult<Decimal256> {
+        let asset_in = &self.assets[self.asset_index(token_in)?];
+        let asset_out = &self.assets[self.asset_index(token_out)?];
+        Ok(
+            Decimal256::from_ratio(asset_out.reserve, asset_in.reserve) * asset_in.weight
+                / asset_out.weight,
+        )
+    }
+
+    // Output of swapping amount_in of token_in for token_out, without changing the reserves
+    pub fn simulate_swap(
+        &self,
+        token_in: &AssetInfo,
+        token_out: &AssetInfo,
+        amount_in: Uint128,
+        protocol_fee_share: Uint128,
+    ) -> StdResult<SwapResult> {
+        let asset_in = &self.assets[self.asset_index(token_in)?];
+        let asset_out = &self.assets[self.asset_index(token_out)?];
+        if asset_in.token == asset_out.token {
+            return Err(StdError::generic_err("Cannot swap a token for itself"));
+        }
+
+        compute_weighted_swap(
+            asset_in.reserve,
+            asset_in.weight,
+            asset_out.reserve,
+            asset_out.weight,
+            amount_in,
+            self.fee_rate,
+            protocol_fee_share,
+        )
+    }
+
+    // Swaps amount_in of token_in for token_out and returns the output. The protocol's
+    // cut of the fee is not added to the reserves.
+    pub fn swap(
+        &mut self,
+        token_in: &AssetInfo,
+        token_out: &AssetInfo,
+        amount_in: Uint128,
+        protocol_fee_share: Uint128,
+    ) -> StdResult<SwapResult> {
+        let result = self.simulate_swap(token_in, token_out, amount_in, protocol_fee_share)?;
+
+        let index_in = self.asset_index(token_in)?;
+        let index_out = self.asset_index(token_out)?;
+        self.assets[index_in].reserve += amount_in - result.protocol_fee;
+        self.assets[index_out].reserve -= result.amount_out;
+
+        Ok(result)
+    }
+
+    // Deposits taken from `offers` (in asset order) and the shares they mint. Only the
+    // amounts matching the current reserve ratios are taken, rounded up.
+    pub fn join(&self, offers: &[Uint128]) -> StdResult<(Vec<Uint128>, Uint128)> {
+        let liquidity = self
+            .assets
+            .iter()
+            .zip(offers)
+            .map(|(asset, offer)| offer.multiply_ratio(self.total_liquidity, asset.reserve))
+            .min()
+            .unwrap_or_default();
+
+        let deposits = self
+            .assets
+            .iter()
+            .map(|asset| ceil_multiply_ratio(liquidity, asset.reserve, self.total_liquidity))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok((deposits, liquidity))
+    }
+
+    // Shares minted for depositing amount_in of a single asset
+    pub fn join_single(&self, token: &AssetInfo, amount_in: Uint128) -> StdResult<Uint128> {
+        let asset = &self.assets[self.asset_index(token)?];
+        compute_weighted_single_join(
+            asset.reserve,
+            asset.weight,
+            self.total_liquidity,
+            amount_in,
+            self.fee_rate,
+        )
+    }
+
+    // Reserves paid out, in asset order, for burning `liquidity` shares
+    pub fn exit(&self, liquidity: Uint128) -> Vec<Uint128> {
+        self.assets
+            .iter()
+            .map(|asset| {
+                asset
+                    .reserve
+                    .multiply_ratio(liquidity, self.total_liquidity)
+            })
+            .collect()
+    }
+
+    // Amount of a single asset paid out for burning `liquidity` shares
+    pub fn exit_single(&self, token: &AssetInfo, liquidity: Uint128) -> StdResult<Uint128> {
+        let asset = &self.assets[self.asset_index(token)?];
+        compute_weighted_single_exit(
+            asset.reserve,
+            asset.weight,
+            self.total_liquidity,
+            liquidity,
+            self.fee_rate,
+        )
+    }
+}
+
+// Uniswap-v3-style pool where liquidity is provided over tick ranges. The price is the
+// amount of token_b per token_a.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConcentratedPool {
+    pub pool_id: u64,
+    pub token_a: AssetInfo,
+    pub token_b: AssetInfo,
+    pub fee_rate: Uint128, // Swap fee in basis points, one of Config.fee_tiers
+    pub tick_spacing: i32, // Position ticks must be multiples of this
+    pub sqrt_price: Decimal256, // Square root of the current price
+    pub tick: i32,         // Largest tick at or below the current price
+    pub liquidity: Uint128, // Liquidity of the positions whose range contains the current price
+    pub fee_growth_global_a: Uint256, // Fees per unit of liquidity since creation, 18 decimal fixed point, wrapping
+    pub fee_growth_global_b: Uint256,
+}
+
+// Tick referenced by at least one position of a concentrated pool
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TickInfo {
+    pub liquidity_gross: Uint128, // Liquidity of all positions starting or ending at the tick
+    pub liquidity_net: Int128,    // Liquidity added when the price crosses the tick upwards
+    pub fee_growth_outside_a: Uint256, // Fee growth on the other side of the tick from the current price
+    pub fee_growth_outside_b: Uint256,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConcentratedPosition {
+    pub owner: Addr,
+    pub pool_id: u64,
+    pub lower_tick: i32,
+    pub upper_tick: i32,
+    pub liquidity: Uint128,
+    pub fee_growth_inside_a_last: Uint256, // Fee growth inside the range when fees were last credited
+    pub fee_growth_inside_b_last: Uint256,
+    pub tokens_owed_a: Uint128, // Credited fees not yet collected
+    pub tokens_owed_b: Uint128,
+}
+
+// A tick crossed by a swap and the global fee growth at that moment
+#[derive(Clone, Debug, PartialEq)]
+pub struct TickCrossing {
+    pub tick: i32,
+    pub fee_growth_global_a: Uint256,
+    pub fee_growth_global_b: Uint256,
+}
+
+impl ConcentratedPool {
+    // Whether a position over [lower_tick, upper_tick) is providing liquidity at the current price
+    pub fn is_active(&self, lower_tick: i32, upper_tick: i32) -> bool {
+        lower_tick <= self.tick && self.tick < upper_tick
+    }
+
+    // Marginal price of token_in in units of the other token
+    pub fn spot_price(&self, token_in: &AssetInfo) -> StdResult<Decimal256> {
+        let price = self.sqrt_price * self.sqrt_price;
+        if *token_in == self.token_a {
+            Ok(price)
+        } else if *token_in == self.token_b {
+            Ok(Decimal256::one()
+                .checked_div(price)
+                .map_err(|e| StdError::generic_err(e.to_string()))?)
+        } else {
+            Err(StdError::generic_err(format!(
+                "Token {} is not in concentrated pool {}",
+                token_in, self.pool_id
+            )))
+        }
+    }
+
+    // Token amounts held by `liquidity` over [lower_tick, upper_tick) at the current price,
+    // rounded up for deposits and down for withdrawals
+    pub fn amounts_for_liquidity(
+        &self,
+        lower_tick: i32,
+        upper_tick: i32,
+        liquidity: Uint128,
+        round_up: bool,
+    ) -> StdResult<(Uint128, Uint128)> {
+        let sqrt_lower = sqrt_price_at_tick(lower_tick)?;
+        let sqrt_upper = sqrt_price_at_tick(upper_tick)?;
+
+        if self.sqrt_price <= sqrt_lower {
+            Ok((
+                amount_a_delta(sqrt_lower, sqrt_upper, liquidity, round_up)?,
+                Uint128::zero(),
+            ))
+        } else if self.sqrt_price < sqrt_upper {
+            Ok((
+                amount_a_delta(self.sqrt_price, sqrt_upper, liquidity, round_up)?,
+                amount_b_delta(sqrt_lower, self.sqrt_price, liquidity, round_up)?,
+            ))
+        } else {
+            Ok((
+                Uint128::zero(),
+                amount_b_delta(sqrt_lower, sqrt_upper, liquidity, round_up)?,
+            ))
+        }
+    }
+
+    // Fee growth per unit of liquidity inside [lower_tick, upper_tick) as (token_a, token_b).
+    // Only differences between two readings are meaningful, so the arithmetic wraps.
+    pub fn fee_growth_inside(
+        &self,
+        lower_tick: i32,
+        lower: &TickInfo,
+        upper_tick: i32,
+        upper: &TickInfo,
+    ) -> (Uint256, Uint256) {
+        let inside = |global: Uint256, lower_outside: Uint256, upper_outside: Uint256| {
+            let below = if self.tick >= lower_tick {
+                lower_outside
+            } else {
+                global.wrapping_sub(lower_outside)
+            };
+            let above = if self.tick < upper_tick {
+                upper_outside
+            } else {
+                global.wrapping_sub(upper_outside)
+            };
+            global.wrapping_sub(below).wrapping_sub(above)
+        };
+
+        (
+            inside(
+                self.fee_growth_global_a,
+                lower.fee_growth_outside_a,
+                upper.fee_growth_outside_a,
+            ),
+            inside(
+                self.fee_growth_global_b,
+                lower.fee_growth_outside_b,
+                upper.fee_growth_outside_b,
+            ),
+        )
+    }
+
+    // Swaps amount_in of token_in, stepping through the initialized ticks and changing the
+    // active liquidity at each one. The crossed ticks are returned so the caller can flip
+    // their fee growth outside; the ticks in storage are only read.
+    pub fn swap(
+        &mut self,
+        storage: &dyn Storage,
+        token_in: &AssetInfo,
+        amount_in: Uint128,
+        protocol_fee_share: Uint128,
+    ) -> StdResult<(SwapResult, Vec<TickCrossing>)> {
+        let a_in = if *token_in == self.token_a {
+            true
+        } else if *token_in == self.token_b {
+            false
+        } else {
+            return Err(StdError::generic_err(format!(
+                "Token {} is not in concentrated pool {}",
+                token_in, self.pool_id
+            )));
+        };
+
+        let fee_denominator = Uint128::from(FEE_DENOMINATOR);
+        let limit_tick = if a_in { MIN_TICK } else { MAX_TICK };
+        let sqrt_limit = sqrt_price_at_tick(limit_tick)?;
+
+        let mut result = SwapResult {
+            amount_out: Uint128::zero(),
+            fee: Uint128::zero(),
+            protocol_fee: Uint128::zero(),
+        };
+        let mut crossings = vec![];
+        let mut remaining = amount_in;
+
+        while !remaining.is_zero() {
+            if self.sqrt_price == sqrt_limit {
+                return Err(StdError::generic_err("Insufficient pool liquidity"));
+            }
+
+            let next_tick = self.next_initialized_tick(storage, a_in)?;
+            let sqrt_target = match next_tick {
+                Some(tick) => sqrt_price_at_tick(tick)?,
+                None => sqrt_limit,
+            };
+
+            let step = compute_swap_step(
+                self.sqrt_price,
+                sqrt_target,
+                self.liquidity,
+                remaining,
+                self.fee_rate,
+            )?;
+            remaining -= step.amount_in + step.fee;
+
+            let protocol_fee = step.fee.multiply_ratio(protocol_fee_share, fee_denominator);
+            result.amount_out += step.amount_out;
+            result.fee += step.fee;
+            result.protocol_fee += protocol_fee;
+
+            if !self.liquidity.is_zero() {
+                let growth = fee_growth(step.fee - protocol_fee, self.liquidity);
+                if a_in {
+                    self.fee_growth_global_a = self.fee_growth_global_a.wrapping_add(growth);
+                } else {
+                    self.fee_growth_global_b = self.fee_growth_global_b.wrapping_add(growth);
+                }
+            }
+
+            self.sqrt_price = step.sqrt_price;
+
+            if step.sqrt_price != sqrt_target {
+                self.tick = tick_at_sqrt_price(self.sqrt_price)?;
+                continue;
+            }
+
+            match next_tick {
+                Some(tick) => {
+                    crossings.push(TickCrossing {
+                        tick,
+                        fee_growth_global_a: self.fee_growth_global_a,
+                        fee_growth_global_b: self.fee_growth_global_b,
+                    });
+
+                    // Moving down leaves the ranges starting at the tick and enters those ending at it
+                    let liquidity_net = TICKS
+                        .load(storage, (self.pool_id, tick))?
+                        .liquidity_net
+                        .i128();
+                    self.liquidity = if a_in {
+                        add_liquidity_delta(self.liquidity, -liquidity_net)?
+                    } else {
+                        add_liquidity_delta(self.liquidity, liquidity_net)?
+                    };
+                    self.tick = if a_in { tick - 1 } else { tick };
+                }
+                None => self.tick = limit_tick,
+            }
+        }
+
+        Ok((result, crossings))
+    }
+
+    // Nearest tick with liquidity in the direction of a swap: at or below the current tick
+    // when token_a is sold, above it otherwise
+    fn next_initialized_tick(&self, storage: &dyn Storage, a_in: bool) -> StdResult<Option<i32>> {
+        let ticks = TICKS.prefix(self.pool_id);
+        let next = if a_in {
+            ticks
+                .keys(
+                    storage,
+                    None,
+                    Some(Bound::inclusive(self.tick)),
+                    Order::Descending,
+                )
+                .next()
+        } else {
+            ticks
+                .keys(
+                    storage,
+                    Some(Bound::exclusive(self.tick)),
+                    None,
+                    Order::Ascending,
+                )
+                .next()
+        };
+        next.transpose()
+    }
+}
+
+// Resting order selling offer_token for ask_token, for the pair of an existing pool
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LimitOrder {
+    pub order_id: u64,
+    pub owner: Addr,
+    pub offer_token: AssetInfo,
+    pub ask_token: AssetInfo,
+    pub price: Decimal256,     // Amount of ask_token asked per offer_token
+    pub offer_amount: Uint128, // Amount offered when the order was placed
+    pub remaining: Uint128,    // Amount of offer_token not sold yet
+    pub received: Uint128,     // Amount of ask_token paid to the owner so far
+}
+
+// Part of a limit order taken by a swap
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderFill {
+    pub order_id: u64,
+    pub owner: Addr,
+    pub offer_amount: Uint128, // Sold to the taker
+    pub ask_amount: Uint128,   // Paid to the owner
+}
+
+// Snapshot of a pool's cumulative prices, taken whenever its reserves change
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Observation {
+    pub price_a_cumulative: Uint256,
+    pub price_b_cumulative: Uint256,
+}
+
+// A pool of any type
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolId {
+    // Constant product or stable pool
+    Pair {
+        token_a: AssetInfo,
+        token_b: AssetInfo,
+    },
+    Weighted {
+        pool_id: u64,
+    },
+    Concentrated {
+        pool_id: u64,
+    },
+}
+
+// Why and by whom the contract or a pool was paused
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PauseInfo {
+    pub reason: String,
+    pub paused_by: Addr,
+    pub paused_at: u64, // Block time in seconds
+}
+
+// Protocol fees of one asset awaiting collection
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProtocolFee {
+    pub asset: AssetInfo,
+    pub amount: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -25,26 +1078,470 @@ pub struct LiquidityPosition {
     pub liquidity: Uint128,
 }
 
+// Rewards paid to the bonded shares of a pair pool at `rate` per second from start_time
+// to end_time, in proportion to each staker's bond
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardSchedule {
+    pub schedule_id: u64,
+    pub token_a: AssetInfo, // Pool tokens, in pool order
+    pub token_b: AssetInfo,
+    pub reward: AssetInfo,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub rate: Uint128,             // Reward emitted per second
+    pub reward_per_share: Uint256, // Reward per bonded share so far, 18 decimal fixed point
+    pub last_update: u64,          // Block time (seconds) reward_per_share was last updated at
+    pub undistributed: Uint128,    // Emitted while nothing was bonded, reclaimable by the admin
+}
+
+impl RewardSchedule {
+    // Adds the rewards emitted since the last update, shared by `total_bonded`, to
+    // reward_per_share. Emissions while nothing is bonded are set aside as undistributed.
+    pub fn accrue(&mut self, total_bonded: Uint128, now: u64) -> StdResult<()> {
+        let from = self.last_update.max(self.start_time);
+        let to = now.min(self.end_time);
+
+        if to > from {
+            let emitted = self.rate.checked_mul(Uint128::from(to - from))?;
+            if total_bonded.is_zero() {
+                self.undistributed += emitted;
+            } else {
+                self.reward_per_share += fee_growth(emitted, total_bonded);
+            }
+        }
+        self.last_update = self.last_update.max(now);
+
+        Ok(())
+    }
+}
+
+// Shares a staker has bonded in a pair pool
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Stake {
+    pub bonded: Uint128,
+    pub last_update: u64, // Block time (seconds) rewards were last settled at
+}
+
+// A staker's progress in one reward schedule
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct StakerReward {
+    pub reward_per_share_paid: Uint256, // reward_per_share the pending amount is counted up to
+    pub pending: Uint128,               // Earned and not yet claimed
+}
+
 // Contract configuration
 pub const CONFIG: Item<Config> = Item::new("config");
 
+// Asset list: denom or contract address -> AssetListing
+pub const ASSET_LIST: Map<String, AssetListing> = Map::new("asset_list");
+
+// Pending admin transfer
+pub const ADMIN_PROPOSAL: Item<AdminProposal> = Item::new("admin_proposal");
+
+// Queued fee changes: change_id -> PendingFeeChange
+pub const PENDING_FEE_CHANGES: Map<u64, PendingFeeChange> = Map::new("pending_fee_changes");
+
+// Number of fee changes queued, the last change_id handed out
+pub const FEE_CHANGE_COUNT: Item<u64> = Item::new("fee_change_count");
+
 // Pool storage: (token_a, token_b) -> Pool
 pub const POOLS: Map<(String, String), Pool> = Map::new("pools");
 
-// Liquidity positions: (user, token_a, token_b) -> LiquidityPosition
+// Price observations: (token_a, token_b, block time in seconds) -> Observation
+pub const OBSERVATIONS: Map<(String, String, u64), Observation> = Map::new("observations");
+
+// Pool awaiting its LP token address from the instantiate reply: (token_a, token_b)
+pub const PENDING_LP_TOKEN: Item<(String, String)> = Item::new("pending_lp_token");
+
+// Weighted pool storage: pool_id -> WeightedPool
+pub const WEIGHTED_POOLS: Map<u64, WeightedPool> = Map::new("weighted_pools");
+
+// Number of weighted pools created, the last pool_id handed out
+pub const WEIGHTED_POOL_COUNT: Item<u64> = Item::new("weighted_pool_count");
+
+// Weighted pool awaiting its LP token address from the instantiate reply
+pub const PENDING_WEIGHTED_LP_TOKEN: Item<u64> = Item::new("pending_weighted_lp_token");
+
+// Concentrated pool storage: pool_id -> ConcentratedPool
+pub const CONCENTRATED_POOLS: Map<u64, ConcentratedPool> = Map::new("concentrated_pools");
+
+// Number of concentrated pools created, the last pool_id handed out
+pub const CONCENTRATED_POOL_COUNT: Item<u64> = Item::new("concentrated_pool_count");
+
+// Initialized ticks of concentrated pools: (pool_id, tick) -> TickInfo
+pub const TICKS: Map<(u64, i32), TickInfo> = Map::new("ticks");
+
+// Concentrated liquidity positions: position_id -> ConcentratedPosition
+pub const POSITIONS: Map<u64, ConcentratedPosition> = Map::new("positions");
+
+// Number of positions opened, the last position_id handed out
+pub const POSITION_COUNT: Item<u64> = Item::new("position_count");
+
+// Positions by owner: (owner, position_id) -> Empty
+pub const OWNER_POSITIONS: Map<(Addr, u64), Empty> = Map::new("owner_positions");
+
+// Reward schedules: schedule_id -> RewardSchedule
+pub const REWARD_SCHEDULES: Map<u64, RewardSchedule> = Map::new("reward_schedules");
+
+// Number of reward schedules created, the last schedule_id handed out
+pub const REWARD_SCHEDULE_COUNT: Item<u64> = Item::new("reward_schedule_count");
+
+// Reward schedules by pool, ending soonest first:
+// (token_a, token_b, end_time big-endian ++ schedule_id big-endian) -> schedule_id
+pub const POOL_REWARD_SCHEDULES: Map<(String, String, Vec<u8>), u64> =
+    Map::new("pool_reward_schedules");
+
+// Shares bonded per pair pool: (token_a, token_b) -> total bonded
+pub const TOTAL_BONDED: Map<(String, String), Uint128> = Map::new("total_bonded");
+
+// Block time (seconds) the bonded shares of a pair pool last changed at; schedules that
+// ended by then are fully accrued: (token_a, token_b) -> seconds
+pub const REWARDS_LAST_UPDATE: Map<(String, String), u64> = Map::new("rewards_last_update");
+
+// Bonded shares: (staker, token_a, token_b) -> Stake
+pub const STAKES: Map<(Addr, String, String), Stake> = Map::new("stakes");
+
+// Staker progress per schedule, kept while the schedule runs or rewards are unclaimed:
+// (staker, schedule_id) -> StakerReward
+pub const STAKER_REWARDS: Map<(Addr, u64), StakerReward> = Map::new("staker_rewards");
+
+// Limit orders: order_id -> LimitOrder
+pub const ORDERS: Map<u64, LimitOrder> = Map::new("limit_orders");
+
+// Number of limit orders placed, the last order_id handed out
+pub const ORDER_COUNT: Item<u64> = Item::new("limit_order_count");
+
+// Resting orders by price: (offer_token, ask_token, price key) -> order_id. The price key
+// is the big-endian price followed by the order_id, so ascending iteration visits the
+// cheapest order first and, at equal prices, the oldest.
+pub const ORDER_BOOK: Map<(String, String, Vec<u8>), u64> = Map::new("order_book");
+
+// Limit orders by owner: (owner, order_id) -> Empty
+pub const OWNER_ORDERS: Map<(Addr, u64), Empty> = Map::new("owner_orders");
+
+// Most resting orders a single swap fills
+pub const MAX_ORDER_FILLS: usize = 10;
+
+// Bisection steps allowed per order when matching against a stable pool. The amount found
+// errs low by at most max_amount_in / 2^64, leaving the rest to the order.
+const ORDER_PRICE_BISECTIONS: usize = 64;
+
+// Protocol fees accumulated outside the pool reserves: token -> ProtocolFee
+pub const PROTOCOL_FEES: Map<String, ProtocolFee> = Map::new("protocol_fees");
+
+// Liquidity positions for pools created without an LP token: (user, token_a, token_b) -> LiquidityPosition
 pub const LIQUIDITY: Map<(Addr, String, String), LiquidityPosition> = Map::new("liquidity");
 
+// Set while the whole contract is paused
+pub const PAUSE: Item<PauseInfo> = Item::new("pause");
+
+// Paused constant product and stable pools: (token_a, token_b) -> PauseInfo
+pub const PAUSED_PAIRS: Map<(String, String), PauseInfo> = Map::new("paused_pairs");
+
+// Paused weighted pools: pool_id -> PauseInfo
+pub const PAUSED_WEIGHTED_POOLS: Map<u64, PauseInfo> = Map::new("paused_weighted_pools");
+
+// Paused concentrated pools: pool_id -> PauseInfo
+pub const PAUSED_CONCENTRATED_POOLS: Map<u64, PauseInfo> = Map::new("paused_concentrated_pools");
+
 // Helper function to create pool key (ensures consistent ordering)
-pub fn pool_key(token_a: &str, token_b: &str) -> (String, String) {
+pub fn pool_key(token_a: &AssetInfo, token_b: &AssetInfo) -> (String, String) {
+    let (token_a, token_b) = (token_a.to_string(), token_b.to_string());
     if token_a < token_b {
-        (token_a.to_string(), token_b.to_string())
+        (token_a, token_b)
     } else {
-        (token_b.to_string(), token_a.to_string())
+        (token_b, token_a)
     }
 }
 
+// Key of a reward schedule in POOL_REWARD_SCHEDULES
+pub fn pool_reward_schedule_key(schedule: &RewardSchedule) -> (String, String, Vec<u8>) {
+    let (a, b) = pool_key(&schedule.token_a, &schedule.token_b);
+    let mut end_key = schedule.end_time.to_be_bytes().to_vec();
+    end_key.extend_from_slice(&schedule.schedule_id.to_be_bytes());
+    (a, b, end_key)
+}
+
+// Order book key of a limit order
+pub fn order_book_key(order: &LimitOrder) -> (String, String, Vec<u8>) {
+    let mut price_key = order.price.atomics().to_be_bytes().to_vec();
+    price_key.extend_from_slice(&order.order_id.to_be_bytes());
+    (
+        order.offer_token.to_string(),
+        order.ask_token.to_string(),
+        price_key,
+    )
+}
+
 // Helper function to create liquidity key
-pub fn liquidity_key(user: &Addr, token_a: &str, token_b: &str) -> (Addr, String, String) {
+pub fn liquidity_key(
+    user: &Addr,
+    token_a: &AssetInfo,
+    token_b: &AssetInfo,
+) -> (Addr, String, String) {
     let (a, b) = pool_key(token_a, token_b);
     (user.clone(), a, b)
-}
\ No newline at end of file
+}
+
+// Pause of a single pool, if any
+pub fn pool_pause(storage: &dyn Storage, pool: &PoolId) -> StdResult<Option<PauseInfo>> {
+    match pool {
+        PoolId::Pair { token_a, token_b } => {
+            PAUSED_PAIRS.may_load(storage, pool_key(token_a, token_b))
+        }
+        PoolId::Weighted { pool_id } => PAUSED_WEIGHTED_POOLS.may_load(storage, *pool_id),
+        PoolId::Concentrated { pool_id } => PAUSED_CONCENTRATED_POOLS.may_load(storage, *pool_id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::MockStorage;
+
+    use super::*;
+    use crate::math::fees_earned;
+
+    fn native(denom: &str) -> AssetInfo {
+        AssetInfo::Native {
+            denom: denom.to_string(),
+        }
+    }
+
+    fn pair_pool(reserve_a: u128, reserve_b: u128) -> Pool {
+        Pool {
+            token_a: native("ibc/usdc"),
+            token_b: native("uatom"),
+            reserve_a: Uint128::new(reserve_a),
+            reserve_b: Uint128::new(reserve_b),
+            total_liquidity: Uint128::new(2_000_000),
+            lp_token_address: None,
+            fee_rate: Uint128::new(30),
+            price_a_cumulative: Uint256::zero(),
+            price_b_cumulative: Uint256::zero(),
+            block_time_last: 1_000,
+            pool_type: PoolType::ConstantProduct {},
+            amp_ramp: None,
+        }
+    }
+
+    #[test]
+    fn prices_accumulate_over_time() {
+        let mut pool = pair_pool(4_000_000, 1_000_000);
+
+        // 100 seconds at 0.25 uatom per usdc and 4 usdc per uatom
+        pool.accumulate_prices(1_100).unwrap();
+        assert_eq!(
+            pool.price_a_cumulative,
+            Decimal256::from_ratio(25u128, 1u128).atomics()
+        );
+        assert_eq!(
+            pool.price_b_cumulative,
+            Decimal256::from_ratio(400u128, 1u128).atomics()
+        );
+        assert_eq!(pool.block_time_last, 1_100);
+
+        // Nothing more within the same block
+        pool.accumulate_prices(1_100).unwrap();
+        assert_eq!(
+            pool.price_a_cumulative,
+            Decimal256::from_ratio(25u128, 1u128).atomics()
+        );
+
+        // Then 50 seconds at 1:1
+        pool.reserve_b = Uint128::new(4_000_000);
+        pool.accumulate_prices(1_150).unwrap();
+        assert_eq!(
+            pool.price_a_cumulative,
+            Decimal256::from_ratio(75u128, 1u128).atomics()
+        );
+        assert_eq!(
+            pool.price_b_cumulative,
+            Decimal256::from_ratio(450u128, 1u128).atomics()
+        );
+
+        // An empty pool has no price
+        let mut pool = pair_pool(0, 0);
+        pool.accumulate_prices(1_100).unwrap();
+        assert!(pool.price_a_cumulative.is_zero());
+        assert_eq!(pool.block_time_last, 1_100);
+    }
+
+    #[test]
+    fn rewards_accrue_per_bonded_share() {
+        let mut schedule = RewardSchedule {
+            schedule_id: 1,
+            token_a: native("ibc/usdc"),
+            token_b: native("uatom"),
+            reward: native("ustake"),
+            start_time: 1_000,
+            end_time: 1_100,
+            rate: Uint128::new(1_000),
+            reward_per_share: Uint256::zero(),
+            last_update: 900,
+            undistributed: Uint128::zero(),
+        };
+        let per_share = |value: u128| Decimal256::from_ratio(value, 1u128).atomics();
+
+        // Nothing is emitted before the start
+        schedule.accrue(Uint128::new(4_000), 950).unwrap();
+        assert!(schedule.reward_per_share.is_zero());
+        assert_eq!(schedule.last_update, 950);
+
+        // Emissions while nothing is bonded are set aside
+        schedule.accrue(Uint128::zero(), 1_010).unwrap();
+        assert!(schedule.reward_per_share.is_zero());
+        assert_eq!(schedule.undistributed, Uint128::new(10_000));
+
+        // 40 seconds shared by 4000 shares, then 50 more by 1000 until the end
+        schedule.accrue(Uint128::new(4_000), 1_050).unwrap();
+        assert_eq!(schedule.reward_per_share, per_share(10));
+        schedule.accrue(Uint128::new(1_000), 1_200).unwrap();
+        assert_eq!(schedule.reward_per_share, per_share(60));
+        assert_eq!(schedule.last_update, 1_200);
+
+        // Nothing after the end
+        schedule.accrue(Uint128::new(1_000), 1_300).unwrap();
+        assert_eq!(schedule.reward_per_share, per_share(60));
+
+        // A share bonded throughout earned 60, one bonded from 1050 earned 50
+        assert_eq!(
+            fees_earned(Uint128::new(1_000), per_share(60)).unwrap(),
+            Uint128::new(60_000)
+        );
+        assert_eq!(
+            fees_earned(Uint128::new(1_000), per_share(60) - per_share(10)).unwrap(),
+            Uint128::new(50_000)
+        );
+    }
+
+    // A pool at price 1 with 1e9 liquidity over ticks [-1000, 1000] and 1e10 more over
+    // [-100, 100]
+    fn concentrated_pool(storage: &mut dyn Storage) -> ConcentratedPool {
+        let ranges = [
+            (-1000, 1000, 1_000_000_000i128),
+            (-100, 100, 10_000_000_000i128),
+        ];
+        for (lower, upper, liquidity) in ranges {
+            for (tick, net) in [(lower, liquidity), (upper, -liquidity)] {
+                let info = TickInfo {
+                    liquidity_gross: Uint128::new(liquidity as u128),
+                    liquidity_net: Int128::new(net),
+                    fee_growth_outside_a: Uint256::zero(),
+                    fee_growth_outside_b: Uint256::zero(),
+                };
+                TICKS.save(storage, (1, tick), &info).unwrap();
+            }
+        }
+
+        ConcentratedPool {
+            pool_id: 1,
+            token_a: native("ibc/usdc"),
+            token_b: native("uatom"),
+            fee_rate: Uint128::new(30),
+            tick_spacing: 10,
+            sqrt_price: Decimal256::one(),
+            tick: 0,
+            liquidity: Uint128::new(11_000_000_000),
+            fee_growth_global_a: Uint256::zero(),
+            fee_growth_global_b: Uint256::zero(),
+        }
+    }
+
+    #[test]
+    fn concentrated_swap_within_a_range() {
+        let mut storage = MockStorage::new();
+        let mut pool = concentrated_pool(&mut storage);
+
+        let (result, crossings) = pool
+            .swap(
+                &storage,
+                &native("ibc/usdc"),
+                Uint128::new(1_000_000),
+                Uint128::zero(),
+            )
+            .unwrap();
+        assert!(crossings.is_empty());
+        assert_eq!(result.fee, Uint128::new(3_000));
+        assert!(pool.tick < 0 && pool.tick > -100);
+        assert_eq!(pool.liquidity, Uint128::new(11_000_000_000));
+
+        // The whole fee is shared by the active liquidity
+        assert_eq!(
+            pool.fee_growth_global_a,
+            fee_growth(Uint128::new(3_000), Uint128::new(11_000_000_000))
+        );
+        assert!(pool.fee_growth_global_b.is_zero());
+    }
+
+    #[test]
+    fn concentrated_swap_crosses_ticks() {
+        for token_a_in in [true, false] {
+            let mut storage = MockStorage::new();
+            let mut pool = concentrated_pool(&mut storage);
+            let token_in = if token_a_in {
+                pool.token_a.clone()
+            } else {
+                pool.token_b.clone()
+            };
+
+            // About 5.5e7 reaches tick -100 or 100, and 4.5e7 more would reach the wide range's end
+            let (result, crossings) = pool
+                .swap(
+                    &storage,
+                    &token_in,
+                    Uint128::new(80_000_000),
+                    Uint128::new(2_000),
+                )
+                .unwrap();
+
+            // Leaving the narrow range drops its liquidity
+            assert_eq!(crossings.len(), 1);
+            assert_eq!(pool.liquidity, Uint128::new(1_000_000_000));
+            if token_a_in {
+                assert_eq!(crossings[0].tick, -100);
+                assert!(pool.tick < -100 && pool.tick > -1000);
+            } else {
+                assert_eq!(crossings[0].tick, 100);
+                assert!(pool.tick >= 100 && pool.tick < 1000);
+            }
+            assert_eq!(pool.tick, tick_at_sqrt_price(pool.sqrt_price).unwrap());
+
+            // The crossing records the fee growth up to the tick; fees after it are shared
+            // by the smaller liquidity, so they grow faster per unit
+            let (at_crossing, global) = if token_a_in {
+                (crossings[0].fee_growth_global_a, pool.fee_growth_global_a)
+            } else {
+                (crossings[0].fee_growth_global_b, pool.fee_growth_global_b)
+            };
+            assert!(!at_crossing.is_zero());
+            assert!(global - at_crossing > at_crossing);
+
+            // The fee of a step that reaches its tick is rounded up; 20% of it is protocol fee
+            assert!(result.fee.abs_diff(Uint128::new(240_000)) <= Uint128::one());
+            assert!(result.protocol_fee.abs_diff(Uint128::new(48_000)) <= Uint128::one());
+        }
+    }
+
+    #[test]
+    fn concentrated_swap_needs_liquidity_in_its_direction() {
+        let mut storage = MockStorage::new();
+        let mut pool = concentrated_pool(&mut storage);
+
+        // Past the wide range there is no liquidity left to reach
+        let result = pool.swap(
+            &storage,
+            &native("ibc/usdc"),
+            Uint128::new(1_000_000_000),
+            Uint128::zero(),
+        );
+        assert!(result.is_err());
+        assert!(pool
+            .swap(
+                &storage,
+                &native("ibc/eth"),
+                Uint128::new(1_000),
+                Uint128::zero()
+            )
+            .is_err());
+    }
+}
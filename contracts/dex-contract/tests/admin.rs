@@ -0,0 +1,66 @@
+mod common;
+
+use common::{contract_error, Suite, ADMIN, ALICE, USER};
+use cosmwasm_std::{Addr, Uint128};
+use dex_contract::msg::{Config, ExecuteMsg, QueryMsg};
+use dex_contract::ContractError;
+
+fn admin(suite: &Suite) -> Option<Addr> {
+    let config: Config = suite.query(&QueryMsg::Config {});
+    config.admin
+}
+
+#[test]
+fn admin_transfer_needs_the_proposed_admin() {
+    let mut suite = Suite::new();
+    let propose = ExecuteMsg::ProposeNewAdmin {
+        new_admin: ALICE.to_string(),
+        expires_in: 3_600,
+    };
+    let err = contract_error(suite.execute(ALICE, &propose, &[]));
+    assert!(matches!(err, ContractError::Unauthorized {}), "{}", err);
+    suite.execute(ADMIN, &propose, &[]).unwrap();
+
+    // Only the proposed address can accept, and the admin stays until it does
+    let err = contract_error(suite.execute(USER, &ExecuteMsg::AcceptAdmin {}, &[]));
+    assert!(matches!(err, ContractError::Unauthorized {}), "{}", err);
+    let err = contract_error(suite.execute(ADMIN, &ExecuteMsg::AcceptAdmin {}, &[]));
+    assert!(matches!(err, ContractError::Unauthorized {}), "{}", err);
+    assert_eq!(admin(&suite), Some(Addr::unchecked(ADMIN)));
+
+    suite
+        .execute(ALICE, &ExecuteMsg::AcceptAdmin {}, &[])
+        .unwrap();
+    assert_eq!(admin(&suite), Some(Addr::unchecked(ALICE)));
+    let update = ExecuteMsg::UpdateFeeRate {
+        fee_rate: Uint128::new(30),
+    };
+    let err = contract_error(suite.execute(ADMIN, &update, &[]));
+    assert!(matches!(err, ContractError::Unauthorized {}), "{}", err);
+    suite.execute(ALICE, &update, &[]).unwrap();
+}
+
+#[test]
+fn renouncing_removes_the_admin() {
+    let mut suite = Suite::new();
+    let err = contract_error(suite.execute(USER, &ExecuteMsg::RenounceAdmin {}, &[]));
+    assert!(matches!(err, ContractError::Unauthorized {}), "{}", err);
+
+    suite
+        .execute(ADMIN, &ExecuteMsg::RenounceAdmin {}, &[])
+        .unwrap();
+    assert_eq!(admin(&suite), None);
+
+    // Nobody can change the config or take the role back
+    let update = ExecuteMsg::UpdateFeeRate {
+        fee_rate: Uint128::new(30),
+    };
+    let err = contract_error(suite.execute(ADMIN, &update, &[]));
+    assert!(matches!(err, ContractError::Unauthorized {}), "{}", err);
+    let propose = ExecuteMsg::ProposeNewAdmin {
+        new_admin: ADMIN.to_string(),
+        expires_in: 3_600,
+    };
+    let err = contract_error(suite.execute(ADMIN, &propose, &[]));
+    assert!(matches!(err, ContractError::Unauthorized {}), "{}", err);
+}
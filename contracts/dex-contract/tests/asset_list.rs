@@ -0,0 +1,52 @@
+mod common;
+
+use common::{contract_error, native, Suite, ADMIN, ALICE, USER};
+use cosmwasm_std::{coin, Uint128};
+use dex_contract::msg::ExecuteMsg;
+use dex_contract::ContractError;
+
+#[test]
+fn only_the_admin_updates_the_asset_list() {
+    let mut suite = Suite::new();
+    let pauser = ExecuteMsg::UpdatePauser {
+        pauser: ALICE.to_string(),
+    };
+    suite.execute(ADMIN, &pauser, &[]).unwrap();
+
+    // Neither users nor the pauser may list or delist
+    let delist = ExecuteMsg::UpdateAssetList {
+        list: vec![],
+        delist: vec![native("ibc/eth")],
+        remove: vec![],
+    };
+    let err = contract_error(suite.execute(USER, &delist, &[]));
+    assert!(matches!(err, ContractError::Unauthorized {}), "{}", err);
+    let err = contract_error(suite.execute(ALICE, &delist, &[]));
+    assert!(matches!(err, ContractError::Unauthorized {}), "{}", err);
+
+    // A delisted asset cannot join new pools
+    suite.execute(ADMIN, &delist, &[]).unwrap();
+    let create = ExecuteMsg::CreatePool {
+        token_a: native("ibc/eth"),
+        token_b: native("uatom"),
+        initial_a: Uint128::new(1_000_000),
+        initial_b: Uint128::new(1_000_000),
+        fee_rate: None,
+        pool_type: None,
+    };
+    let funds = [coin(1_000_000, "ibc/eth"), coin(1_000_000, "uatom")];
+    let err = contract_error(suite.execute(USER, &create, &funds));
+    assert!(
+        matches!(err, ContractError::AssetDelisted { .. }),
+        "{}",
+        err
+    );
+
+    let remove = ExecuteMsg::UpdateAssetList {
+        list: vec![],
+        delist: vec![],
+        remove: vec![native("ibc/eth")],
+    };
+    suite.execute(ADMIN, &remove, &[]).unwrap();
+    suite.execute(USER, &create, &funds).unwrap();
+}
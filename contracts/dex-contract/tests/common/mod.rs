@@ -0,0 +1,246 @@
+// Shared setup for the integration tests: a DEX instance with the cw20-base code as its
+// LP token code, and accounts funded with the bank tokens the tests pool
+#![allow(dead_code)]
+
+use cosmwasm_std::{coin, to_json_binary, Addr, Coin, Empty, Uint128};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw_multi_test::{App, AppBuilder, AppResponse, Contract, ContractWrapper, Executor};
+use dex_contract::msg::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolInfo, QueryMsg};
+use dex_contract::state::AssetInfo;
+use dex_contract::ContractError;
+
+pub const ADMIN: &str = "admin";
+pub const USER: &str = "user";
+pub const ALICE: &str = "alice";
+
+pub const DENOMS: [&str; 4] = ["uatom", "ibc/usdc", "ibc/eth", "ustake"];
+// Enough for pools of 18 decimal tokens
+pub const INITIAL_BALANCE: u128 = 1_000_000_000_000_000_000_000;
+
+fn dex_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(
+            dex_contract::contract::execute,
+            dex_contract::contract::instantiate,
+            dex_contract::contract::query,
+        )
+        .with_reply(dex_contract::contract::reply),
+    )
+}
+
+fn cw20_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    ))
+}
+
+pub struct Suite {
+    pub app: App,
+    pub dex: Addr,
+    pub cw20_code_id: u64,
+}
+
+impl Suite {
+    pub fn new() -> Self {
+        let mut app = AppBuilder::new().build(|router, _, storage| {
+            for account in [ADMIN, USER, ALICE] {
+                let balance = DENOMS
+                    .iter()
+                    .map(|denom| coin(INITIAL_BALANCE, *denom))
+                    .collect();
+                router
+                    .bank
+                    .init_balance(storage, &Addr::unchecked(account), balance)
+                    .unwrap();
+            }
+        });
+
+        let dex_code_id = app.store_code(dex_contract());
+        let cw20_code_id = app.store_code(cw20_contract());
+        let dex = app
+            .instantiate_contract(
+                dex_code_id,
+                Addr::unchecked(ADMIN),
+                &InstantiateMsg {
+                    admin: None,
+                    fee_rate: Uint128::new(30),
+                    fee_tiers: None,
+                    lp_token_code_id: cw20_code_id,
+                    treasury: None,
+                    protocol_fee_share: None,
+                    native_denoms: None,
+                    native_denom_prefixes: None,
+                    pauser: None,
+                    max_fee_rate: None,
+                    fee_change_delay: None,
+                    pool_creation_fee: None,
+                    asset_list_mode: None,
+                    restrict_swaps: None,
+                },
+                &[],
+                "dex",
+                None,
+            )
+            .unwrap();
+
+        Suite {
+            app,
+            dex,
+            cw20_code_id,
+        }
+    }
+
+    // A CW20 token with INITIAL_BALANCE for USER and ALICE
+    pub fn create_token(&mut self, symbol: &str) -> Addr {
+        let initial_balances = [USER, ALICE]
+            .iter()
+            .map(|account| Cw20Coin {
+                address: account.to_string(),
+                amount: Uint128::new(INITIAL_BALANCE),
+            })
+            .collect();
+
+        self.app
+            .instantiate_contract(
+                self.cw20_code_id,
+                Addr::unchecked(ADMIN),
+                &cw20_base::msg::InstantiateMsg {
+                    name: format!("{} token", symbol),
+                    symbol: symbol.to_string(),
+                    decimals: 6,
+                    initial_balances,
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                symbol,
+                None,
+            )
+            .unwrap()
+    }
+
+    pub fn execute(
+        &mut self,
+        sender: &str,
+        msg: &ExecuteMsg,
+        funds: &[Coin],
+    ) -> anyhow::Result<AppResponse> {
+        self.app
+            .execute_contract(Addr::unchecked(sender), self.dex.clone(), msg, funds)
+    }
+
+    // Sends CW20 tokens to the DEX with a hook message
+    pub fn send(
+        &mut self,
+        sender: &str,
+        token: &Addr,
+        amount: u128,
+        hook: &Cw20HookMsg,
+    ) -> anyhow::Result<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(sender),
+            token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: self.dex.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_json_binary(hook).unwrap(),
+            },
+            &[],
+        )
+    }
+
+    // Lets the DEX pull or burn amount of a CW20 token held by owner
+    pub fn approve(&mut self, owner: &str, token: &Addr, amount: u128) {
+        self.app
+            .execute_contract(
+                Addr::unchecked(owner),
+                token.clone(),
+                &Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: self.dex.to_string(),
+                    amount: Uint128::new(amount),
+                    expires: None,
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    pub fn balance(&self, denom: &str, account: &str) -> u128 {
+        self.app
+            .wrap()
+            .query_balance(account, denom)
+            .unwrap()
+            .amount
+            .u128()
+    }
+
+    pub fn token_balance(&self, token: &Addr, account: &str) -> u128 {
+        let response: BalanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                token,
+                &Cw20QueryMsg::Balance {
+                    address: account.to_string(),
+                },
+            )
+            .unwrap();
+        response.balance.u128()
+    }
+
+    pub fn query<T: serde::de::DeserializeOwned>(&self, msg: &QueryMsg) -> T {
+        self.app.wrap().query_wasm_smart(&self.dex, msg).unwrap()
+    }
+
+    pub fn pool(&self, token_a: &str, token_b: &str) -> PoolInfo {
+        self.query(&QueryMsg::Pool {
+            token_a: native(token_a),
+            token_b: native(token_b),
+        })
+    }
+
+    pub fn advance_time(&mut self, seconds: u64) {
+        self.app.update_block(|block| {
+            block.time = block.time.plus_seconds(seconds);
+            block.height += 1;
+        });
+    }
+
+    pub fn now(&self) -> u64 {
+        self.app.block_info().time.seconds()
+    }
+
+    pub fn lp_token(&self, token_a: &str, token_b: &str) -> Addr {
+        Addr::unchecked(self.pool(token_a, token_b).lp_token_address.unwrap())
+    }
+}
+
+pub fn native(denom: &str) -> AssetInfo {
+    AssetInfo::Native {
+        denom: denom.to_string(),
+    }
+}
+
+pub fn cw20(contract_addr: &Addr) -> AssetInfo {
+    AssetInfo::Cw20 {
+        contract_addr: contract_addr.clone(),
+    }
+}
+
+// The DEX error a message failed with
+pub fn contract_error(result: anyhow::Result<AppResponse>) -> ContractError {
+    result.unwrap_err().downcast().unwrap()
+}
+
+// The first value of an attribute emitted while handling a message
+pub fn attribute(response: &AppResponse, key: &str) -> String {
+    response
+        .events
+        .iter()
+        .flat_map(|event| event.attributes.iter())
+        .find(|attribute| attribute.key == key)
+        .map(|attribute| attribute.value.clone())
+        .unwrap_or_else(|| panic!("no {} attribute", key))
+}
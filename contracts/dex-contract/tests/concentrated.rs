@@ -0,0 +1,222 @@
+mod common;
+
+use common::{attribute, native, Suite, ALICE, USER};
+use cosmwasm_std::{coin, Decimal256, Uint128};
+use dex_contract::msg::{
+    ActiveLiquidityResponse, ConcentratedPoolInfo, ExecuteMsg, PositionInfo, QueryMsg,
+    SimulationResponse,
+};
+
+fn open_position(lower_tick: i32, upper_tick: i32, amount: u128) -> ExecuteMsg {
+    ExecuteMsg::OpenPosition {
+        pool_id: 1,
+        lower_tick,
+        upper_tick,
+        amount_a: Uint128::new(amount),
+        amount_b: Uint128::new(amount),
+        min_liquidity: Uint128::zero(),
+    }
+}
+
+fn swap(denom: &str, amount_in: u128, min_amount_out: Uint128) -> ExecuteMsg {
+    ExecuteMsg::ConcentratedSwap {
+        pool_id: 1,
+        token_in: native(denom),
+        amount_in: Uint128::new(amount_in),
+        min_amount_out,
+    }
+}
+
+fn active_liquidity(suite: &Suite) -> ActiveLiquidityResponse {
+    suite.query(&QueryMsg::ActiveLiquidity {
+        pool_id: 1,
+        start_after: None,
+        limit: None,
+    })
+}
+
+fn position(suite: &Suite, position_id: u64) -> PositionInfo {
+    suite.query(&QueryMsg::Position { position_id })
+}
+
+#[test]
+fn create_swap_withdraw() {
+    let mut suite = Suite::new();
+    let create = ExecuteMsg::CreateConcentratedPool {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        fee_rate: None,
+        initial_price: Decimal256::one(),
+    };
+    suite.execute(USER, &create, &[]).unwrap();
+
+    // The 0.3% tier spaces ticks 60 apart
+    let pool: ConcentratedPoolInfo = suite.query(&QueryMsg::ConcentratedPool { pool_id: 1 });
+    assert_eq!(pool.tick_spacing, 60);
+    assert_eq!(pool.current_tick, 0);
+    assert!(pool.liquidity.is_zero());
+
+    let funds = |amount: u128| [coin(amount, "ibc/usdc"), coin(amount, "uatom")];
+    suite
+        .execute(
+            ALICE,
+            &swap("ibc/usdc", 1_000, Uint128::zero()),
+            &[coin(1_000, "ibc/usdc")],
+        )
+        .unwrap_err();
+    suite
+        .execute(ALICE, &open_position(-610, 600, 1_000), &funds(1_000))
+        .unwrap_err();
+    suite
+        .execute(ALICE, &open_position(600, 600, 1_000), &funds(1_000))
+        .unwrap_err();
+
+    // A wide range from ALICE and a narrow one from USER, both around the price
+    let response = suite
+        .execute(
+            ALICE,
+            &open_position(-6000, 6000, 1_000_000),
+            &funds(1_000_000),
+        )
+        .unwrap();
+    let wide: u128 = attribute(&response, "liquidity").parse().unwrap();
+    let response = suite
+        .execute(
+            USER,
+            &open_position(-600, 600, 1_000_000),
+            &funds(1_000_000),
+        )
+        .unwrap();
+    let narrow: u128 = attribute(&response, "liquidity").parse().unwrap();
+    assert!(narrow > wide * 8);
+
+    let active = active_liquidity(&suite);
+    assert_eq!(active.liquidity.u128(), wide + narrow);
+    let ticks: Vec<i32> = active.ticks.iter().map(|tick| tick.tick).collect();
+    assert_eq!(ticks, vec![-6000, -600, 600, 6000]);
+
+    // A small swap stays within both ranges and pays what was simulated
+    let simulation: SimulationResponse = suite.query(&QueryMsg::ConcentratedSimulation {
+        pool_id: 1,
+        token_in: native("ibc/usdc"),
+        amount_in: Uint128::new(10_000),
+    });
+    assert_eq!(simulation.fee, Uint128::new(30));
+    let atom_before = suite.balance("uatom", ALICE);
+    suite
+        .execute(
+            ALICE,
+            &swap("ibc/usdc", 10_000, simulation.amount_out),
+            &[coin(10_000, "ibc/usdc")],
+        )
+        .unwrap();
+    assert_eq!(
+        suite.balance("uatom", ALICE) - atom_before,
+        simulation.amount_out.u128()
+    );
+    assert_eq!(active_liquidity(&suite).liquidity.u128(), wide + narrow);
+
+    // A larger one crosses out of the narrow range, leaving only the wide one active
+    suite
+        .execute(
+            ALICE,
+            &swap("ibc/usdc", 1_200_000, Uint128::zero()),
+            &[coin(1_200_000, "ibc/usdc")],
+        )
+        .unwrap();
+    let active = active_liquidity(&suite);
+    assert!(active.current_tick < -600 && active.current_tick > -6000);
+    assert_eq!(active.liquidity.u128(), wide);
+
+    // Swapping back crosses into it again
+    suite
+        .execute(
+            ALICE,
+            &swap("uatom", 1_200_000, Uint128::zero()),
+            &[coin(1_200_000, "uatom")],
+        )
+        .unwrap();
+    let active = active_liquidity(&suite);
+    assert!(active.current_tick >= -600 && active.current_tick < 600);
+    assert_eq!(active.liquidity.u128(), wide + narrow);
+
+    // The narrow position earned most of the fees, in both tokens
+    let (wide_position, narrow_position) = (position(&suite, 1), position(&suite, 2));
+    assert!(narrow_position.fees_a > wide_position.fees_a);
+    assert!(!narrow_position.fees_b.is_zero() && !wide_position.fees_b.is_zero());
+
+    // Only the owner collects, and fees are paid once
+    suite
+        .execute(ALICE, &ExecuteMsg::CollectFees { position_id: 2 }, &[])
+        .unwrap_err();
+    let usdc_before = suite.balance("ibc/usdc", USER);
+    let response = suite
+        .execute(USER, &ExecuteMsg::CollectFees { position_id: 2 }, &[])
+        .unwrap();
+    assert_eq!(
+        attribute(&response, "fees_a"),
+        narrow_position.fees_a.to_string()
+    );
+    assert_eq!(
+        suite.balance("ibc/usdc", USER) - usdc_before,
+        narrow_position.fees_a.u128()
+    );
+
+    let response = suite
+        .execute(USER, &ExecuteMsg::CollectFees { position_id: 2 }, &[])
+        .unwrap();
+    assert_eq!(attribute(&response, "fees_a"), "0");
+
+    // Withdrawals respect the minimums, and can be partial
+    let withdraw = |position: &PositionInfo, liquidity: Uint128, min_a: Uint128| {
+        ExecuteMsg::WithdrawPosition {
+            position_id: position.position_id,
+            liquidity,
+            min_a,
+            min_b: Uint128::zero(),
+        }
+    };
+    let half = wide_position.liquidity.multiply_ratio(1u128, 2u128);
+    suite
+        .execute(
+            ALICE,
+            &withdraw(
+                &wide_position,
+                wide_position.liquidity,
+                wide_position.amount_a + Uint128::one(),
+            ),
+            &[],
+        )
+        .unwrap_err();
+    suite
+        .execute(ALICE, &withdraw(&wide_position, half, Uint128::zero()), &[])
+        .unwrap();
+    assert_eq!(
+        position(&suite, 1).liquidity,
+        wide_position.liquidity - half
+    );
+
+    let wide_position = position(&suite, 1);
+    suite
+        .execute(
+            ALICE,
+            &withdraw(&wide_position, wide_position.liquidity, Uint128::zero()),
+            &[],
+        )
+        .unwrap();
+    let narrow_position = position(&suite, 2);
+    suite
+        .execute(
+            USER,
+            &withdraw(&narrow_position, narrow_position.liquidity, Uint128::zero()),
+            &[],
+        )
+        .unwrap();
+
+    // Everything is paid out but rounding dust
+    let active = active_liquidity(&suite);
+    assert!(active.liquidity.is_zero());
+    assert!(active.ticks.is_empty());
+    assert!(suite.balance("ibc/usdc", suite.dex.as_str()) < 10);
+    assert!(suite.balance("uatom", suite.dex.as_str()) < 10);
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::{contract_error, cw20, native, Suite, ADMIN, USER};
+use cosmwasm_std::{coin, Decimal256, Uint128};
+use dex_contract::msg::{Cw20HookMsg, ExecuteMsg, PoolCreationFeeParams};
+use dex_contract::ContractError;
+
+const COLLECTOR: &str = "collector";
+
+fn set_creation_fee(suite: &mut Suite) {
+    let update = ExecuteMsg::UpdatePoolCreationFee {
+        pool_creation_fee: Some(PoolCreationFeeParams {
+            fee: coin(1_000, "ibc/eth"),
+            collector: Some(COLLECTOR.to_string()),
+        }),
+    };
+    suite.execute(ADMIN, &update, &[]).unwrap();
+}
+
+#[test]
+fn every_pool_type_pays_the_creation_fee_to_the_collector() {
+    let mut suite = Suite::new();
+    set_creation_fee(&mut suite);
+
+    let create = ExecuteMsg::CreatePool {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        initial_a: Uint128::new(4_000_000),
+        initial_b: Uint128::new(1_000_000),
+        fee_rate: None,
+        pool_type: None,
+    };
+    let reserves = [coin(4_000_000, "ibc/usdc"), coin(1_000_000, "uatom")];
+    let err = contract_error(suite.execute(USER, &create, &reserves));
+    assert!(
+        matches!(err, ContractError::FundsMismatch { .. }),
+        "{}",
+        err
+    );
+    let funds = [
+        coin(1_000, "ibc/eth"),
+        coin(4_000_000, "ibc/usdc"),
+        coin(1_000_000, "uatom"),
+    ];
+    suite.execute(USER, &create, &funds).unwrap();
+    assert_eq!(suite.balance("ibc/eth", COLLECTOR), 1_000);
+
+    // Concentrated pools start empty, so the fee is all they take
+    let create = ExecuteMsg::CreateConcentratedPool {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        fee_rate: None,
+        initial_price: Decimal256::one(),
+    };
+    let err = contract_error(suite.execute(USER, &create, &[]));
+    assert!(
+        matches!(err, ContractError::FundsMismatch { .. }),
+        "{}",
+        err
+    );
+    suite
+        .execute(USER, &create, &[coin(1_000, "ibc/eth")])
+        .unwrap();
+    assert_eq!(suite.balance("ibc/eth", COLLECTOR), 2_000);
+    assert_eq!(suite.balance("ibc/eth", suite.dex.as_str()), 0);
+}
+
+#[test]
+fn the_create_pool_hook_is_refused_while_a_fee_is_set() {
+    let mut suite = Suite::new();
+    let (tka, tkb) = (suite.create_token("TKA"), suite.create_token("TKB"));
+    suite.approve(USER, &tkb, 2_000_000);
+    set_creation_fee(&mut suite);
+
+    let create = Cw20HookMsg::CreatePool {
+        token_b: cw20(&tkb),
+        initial_b: Uint128::new(1_000_000),
+        fee_rate: None,
+        pool_type: None,
+    };
+    let err = contract_error(suite.send(USER, &tka, 1_000_000, &create));
+    assert!(
+        matches!(err, ContractError::PoolCreationFeeNotPayable {}),
+        "{}",
+        err
+    );
+
+    // Once the fee is waived the hook works again
+    let waive = ExecuteMsg::UpdatePoolCreationFee {
+        pool_creation_fee: None,
+    };
+    suite.execute(ADMIN, &waive, &[]).unwrap();
+    suite.send(USER, &tka, 1_000_000, &create).unwrap();
+}
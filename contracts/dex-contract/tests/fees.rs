@@ -0,0 +1,95 @@
+mod common;
+
+use common::{attribute, contract_error, native, Suite, ADMIN, ALICE, USER};
+use cosmwasm_std::{coin, Uint128};
+use dex_contract::msg::ExecuteMsg;
+use dex_contract::ContractError;
+
+fn create_pool(
+    suite: &mut Suite,
+    fee_rate: Option<u128>,
+) -> anyhow::Result<cw_multi_test::AppResponse> {
+    let create = ExecuteMsg::CreatePool {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        initial_a: Uint128::new(4_000_000),
+        initial_b: Uint128::new(1_000_000),
+        fee_rate: fee_rate.map(Uint128::new),
+        pool_type: None,
+    };
+    suite.execute(
+        USER,
+        &create,
+        &[coin(4_000_000, "ibc/usdc"), coin(1_000_000, "uatom")],
+    )
+}
+
+#[test]
+fn fee_changes_apply_only_after_the_delay() {
+    let mut suite = Suite::new();
+    create_pool(&mut suite, None).unwrap();
+
+    let update = ExecuteMsg::UpdatePoolFee {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        fee_rate: Uint128::new(100),
+    };
+    let response = suite.execute(ADMIN, &update, &[]).unwrap();
+    let change_id: u64 = attribute(&response, "change_id").parse().unwrap();
+    let apply = ExecuteMsg::ApplyFeeChange { change_id };
+
+    // The default delay is one day
+    let err = contract_error(suite.execute(ALICE, &apply, &[]));
+    assert!(
+        matches!(err, ContractError::FeeChangeNotReady { .. }),
+        "{}",
+        err
+    );
+    suite.advance_time(86_399);
+    let err = contract_error(suite.execute(ALICE, &apply, &[]));
+    assert!(
+        matches!(err, ContractError::FeeChangeNotReady { .. }),
+        "{}",
+        err
+    );
+    assert_eq!(suite.pool("ibc/usdc", "uatom").fee_rate, Uint128::new(30));
+
+    // Then anyone can apply it, once
+    suite.advance_time(1);
+    suite.execute(ALICE, &apply, &[]).unwrap();
+    assert_eq!(suite.pool("ibc/usdc", "uatom").fee_rate, Uint128::new(100));
+    let err = contract_error(suite.execute(ALICE, &apply, &[]));
+    assert!(
+        matches!(err, ContractError::FeeChangeNotFound { .. }),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn fee_tiers_are_bounded_by_the_max_fee_rate() {
+    let mut suite = Suite::new();
+
+    // The max fee rate defaults to 100 bps
+    let update = ExecuteMsg::UpdateFeeTiers {
+        fee_tiers: vec![Uint128::new(30), Uint128::new(101)],
+    };
+    let err = contract_error(suite.execute(ADMIN, &update, &[]));
+    assert!(
+        matches!(err, ContractError::FeeRateTooHigh { .. }),
+        "{}",
+        err
+    );
+    let err = contract_error(create_pool(&mut suite, Some(101)));
+    assert!(
+        matches!(err, ContractError::InvalidFeeTier { .. }),
+        "{}",
+        err
+    );
+
+    let update = ExecuteMsg::UpdateFeeTiers {
+        fee_tiers: vec![Uint128::new(30), Uint128::new(100)],
+    };
+    suite.execute(ADMIN, &update, &[]).unwrap();
+    create_pool(&mut suite, Some(100)).unwrap();
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use common::{cw20, Suite, ALICE, USER};
+use cosmwasm_std::{Addr, Uint128};
+use dex_contract::msg::{Cw20HookMsg, PoolInfo, QueryMsg, SimulationResponse};
+
+#[test]
+fn every_hook_takes_the_sent_tokens() {
+    let mut suite = Suite::new();
+    let (tka, tkb) = (suite.create_token("TKA"), suite.create_token("TKB"));
+    let (token_a, token_b) = (cw20(&tka), cw20(&tkb));
+    let pool_query = QueryMsg::Pool {
+        token_a: token_a.clone(),
+        token_b: token_b.clone(),
+    };
+
+    // CreatePool seeds the pool with the sent TKA and pulls TKB from an allowance
+    let create = Cw20HookMsg::CreatePool {
+        token_b: token_b.clone(),
+        initial_b: Uint128::new(2_000_000),
+        fee_rate: None,
+        pool_type: None,
+    };
+    suite.send(USER, &tka, 1_000_000, &create).unwrap_err();
+    suite.approve(USER, &tkb, 2_000_000);
+    suite.send(USER, &tka, 1_000_000, &create).unwrap();
+
+    let pool: PoolInfo = suite.query(&pool_query);
+    assert_eq!(
+        (pool.reserve_a, pool.reserve_b),
+        (Uint128::new(1_000_000), Uint128::new(2_000_000))
+    );
+    assert_eq!(suite.token_balance(&tka, suite.dex.as_str()), 1_000_000);
+    assert_eq!(suite.token_balance(&tkb, suite.dex.as_str()), 2_000_000);
+
+    // ProvideLiquidity adds the sent TKA and the given amount of TKB
+    let lp_token = Addr::unchecked(pool.lp_token_address.unwrap());
+    let provide = Cw20HookMsg::ProvideLiquidity {
+        token_b: token_b.clone(),
+        amount_b: Uint128::new(1_000_000),
+        min_liquidity: Uint128::new(1),
+    };
+    suite.approve(ALICE, &tkb, 1_000_000);
+    suite.send(ALICE, &tka, 500_000, &provide).unwrap();
+
+    let pool: PoolInfo = suite.query(&pool_query);
+    assert_eq!(
+        (pool.reserve_a, pool.reserve_b),
+        (Uint128::new(1_500_000), Uint128::new(3_000_000))
+    );
+    let liquidity = suite.token_balance(&lp_token, ALICE);
+    assert_eq!(
+        Uint128::new(liquidity),
+        pool.total_liquidity.multiply_ratio(1u128, 3u128)
+    );
+
+    // Swap sells the sent TKA for TKB
+    let simulation: SimulationResponse = suite.query(&QueryMsg::Simulation {
+        token_in: token_a.clone(),
+        token_out: token_b.clone(),
+        amount_in: Uint128::new(100_000),
+    });
+    let swap = Cw20HookMsg::Swap {
+        token_out: token_b.clone(),
+        min_amount_out: simulation.amount_out,
+    };
+    let tkb_before = suite.token_balance(&tkb, ALICE);
+    suite.send(ALICE, &tka, 100_000, &swap).unwrap();
+    assert_eq!(
+        suite.token_balance(&tkb, ALICE) - tkb_before,
+        simulation.amount_out.u128()
+    );
+
+    // WithdrawLiquidity only takes the pool's own LP token
+    let withdraw = Cw20HookMsg::WithdrawLiquidity {
+        token_a: token_a.clone(),
+        token_b: token_b.clone(),
+        min_a: Uint128::zero(),
+        min_b: Uint128::zero(),
+    };
+    suite.send(ALICE, &tka, 1_000, &withdraw).unwrap_err();
+
+    let pool: PoolInfo = suite.query(&pool_query);
+    let (tka_before, tkb_before) = (
+        suite.token_balance(&tka, ALICE),
+        suite.token_balance(&tkb, ALICE),
+    );
+    suite.send(ALICE, &lp_token, liquidity, &withdraw).unwrap();
+    assert_eq!(suite.token_balance(&lp_token, ALICE), 0);
+    assert_eq!(
+        suite.token_balance(&tka, ALICE) - tka_before,
+        pool.reserve_a
+            .multiply_ratio(liquidity, pool.total_liquidity)
+            .u128()
+    );
+    assert_eq!(
+        suite.token_balance(&tkb, ALICE) - tkb_before,
+        pool.reserve_b
+            .multiply_ratio(liquidity, pool.total_liquidity)
+            .u128()
+    );
+}
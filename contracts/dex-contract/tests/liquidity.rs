@@ -0,0 +1,227 @@
+mod common;
+
+use common::{contract_error, native, Suite, ALICE, USER};
+use cosmwasm_std::{coin, Uint128};
+use cw20::{Cw20QueryMsg, TokenInfoResponse};
+use dex_contract::execute::MINIMUM_LIQUIDITY;
+use dex_contract::msg::{Cw20HookMsg, ExecuteMsg};
+use dex_contract::ContractError;
+
+// A pool of 4 USDC and 1 ATOM created by USER
+fn create_pool(suite: &mut Suite) {
+    let create = ExecuteMsg::CreatePool {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        initial_a: Uint128::new(4_000_000),
+        initial_b: Uint128::new(1_000_000),
+        fee_rate: None,
+        pool_type: None,
+    };
+    suite
+        .execute(
+            USER,
+            &create,
+            &[coin(4_000_000, "ibc/usdc"), coin(1_000_000, "uatom")],
+        )
+        .unwrap();
+}
+
+#[test]
+fn lp_tokens_are_minted_on_add_and_burned_on_withdraw() {
+    let mut suite = Suite::new();
+    create_pool(&mut suite);
+
+    // The creator holds the initial liquidity, sqrt(4000000 * 1000000), as LP tokens,
+    // less the locked minimum
+    let lp_token = suite.lp_token("ibc/usdc", "uatom");
+    assert_eq!(
+        suite.token_balance(&lp_token, USER),
+        2_000_000 - MINIMUM_LIQUIDITY
+    );
+
+    // Adding liquidity mints LP tokens to the provider
+    let add = ExecuteMsg::AddLiquidity {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        amount_a: Uint128::new(2_000_000),
+        amount_b: Uint128::new(500_000),
+        min_liquidity: Uint128::new(1),
+    };
+    suite
+        .execute(
+            ALICE,
+            &add,
+            &[coin(2_000_000, "ibc/usdc"), coin(500_000, "uatom")],
+        )
+        .unwrap();
+    assert_eq!(suite.token_balance(&lp_token, ALICE), 1_000_000);
+
+    // RemoveLiquidity burns with BurnFrom, which needs an allowance
+    let remove = ExecuteMsg::RemoveLiquidity {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        liquidity: Uint128::new(400_000),
+        min_a: Uint128::zero(),
+        min_b: Uint128::zero(),
+    };
+    suite.execute(ALICE, &remove, &[]).unwrap_err();
+
+    // Sending LP tokens back with the WithdrawLiquidity hook burns them for their share
+    let withdraw = |min_a: u128| Cw20HookMsg::WithdrawLiquidity {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        min_a: Uint128::new(min_a),
+        min_b: Uint128::zero(),
+    };
+    suite
+        .send(ALICE, &lp_token, 400_000, &withdraw(800_001))
+        .unwrap_err();
+
+    let (usdc_before, atom_before) = (
+        suite.balance("ibc/usdc", ALICE),
+        suite.balance("uatom", ALICE),
+    );
+    suite
+        .send(ALICE, &lp_token, 400_000, &withdraw(800_000))
+        .unwrap();
+    assert_eq!(suite.balance("ibc/usdc", ALICE) - usdc_before, 800_000);
+    assert_eq!(suite.balance("uatom", ALICE) - atom_before, 200_000);
+    assert_eq!(suite.token_balance(&lp_token, ALICE), 600_000);
+
+    // The LP token supply follows the pool's liquidity
+    let pool = suite.pool("ibc/usdc", "uatom");
+    assert_eq!(pool.total_liquidity, Uint128::new(2_600_000));
+    let token_info: TokenInfoResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&lp_token, &Cw20QueryMsg::TokenInfo {})
+        .unwrap();
+    assert_eq!(token_info.total_supply, pool.total_liquidity);
+}
+
+#[test]
+fn minimum_liquidity_is_locked_in_the_dex() {
+    let mut suite = Suite::new();
+
+    // Pools whose initial liquidity doesn't exceed the locked minimum are rejected
+    let tiny = ExecuteMsg::CreatePool {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        initial_a: Uint128::new(1_000),
+        initial_b: Uint128::new(1_000),
+        fee_rate: None,
+        pool_type: None,
+    };
+    let funds = [coin(1_000, "ibc/usdc"), coin(1_000, "uatom")];
+    let err = contract_error(suite.execute(USER, &tiny, &funds));
+    assert!(
+        matches!(err, ContractError::InsufficientInitialLiquidity { .. }),
+        "{}",
+        err
+    );
+
+    create_pool(&mut suite);
+    let lp_token = suite.lp_token("ibc/usdc", "uatom");
+    assert_eq!(
+        suite.token_balance(&lp_token, suite.dex.as_str()),
+        MINIMUM_LIQUIDITY
+    );
+
+    // The creator can't withdraw more than their own shares
+    let withdraw = Cw20HookMsg::WithdrawLiquidity {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        min_a: Uint128::zero(),
+        min_b: Uint128::zero(),
+    };
+    suite
+        .send(USER, &lp_token, 2_000_000, &withdraw)
+        .unwrap_err();
+
+    // Withdrawing everything else leaves the locked shares and their reserves in the pool
+    suite
+        .send(USER, &lp_token, 2_000_000 - MINIMUM_LIQUIDITY, &withdraw)
+        .unwrap();
+    let pool = suite.pool("ibc/usdc", "uatom");
+    assert_eq!(pool.total_liquidity, Uint128::new(MINIMUM_LIQUIDITY));
+    assert_eq!(pool.reserve_a, Uint128::new(2_000));
+    assert_eq!(pool.reserve_b, Uint128::new(500));
+    assert_eq!(
+        suite.token_balance(&lp_token, suite.dex.as_str()),
+        MINIMUM_LIQUIDITY
+    );
+}
+
+#[test]
+fn deposits_follow_the_pool_ratio_and_the_excess_is_refunded() {
+    let mut suite = Suite::new();
+    create_pool(&mut suite);
+    let lp_token = suite.lp_token("ibc/usdc", "uatom");
+
+    // 3 USDC and 0.5 ATOM offered: only 2 USDC match the pool's 4:1 ratio
+    let add = ExecuteMsg::AddLiquidity {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        amount_a: Uint128::new(500_000),
+        amount_b: Uint128::new(3_000_000),
+        min_liquidity: Uint128::new(1),
+    };
+    let usdc_before = suite.balance("ibc/usdc", ALICE);
+    suite
+        .execute(
+            ALICE,
+            &add,
+            &[coin(3_000_000, "ibc/usdc"), coin(500_000, "uatom")],
+        )
+        .unwrap();
+    assert_eq!(usdc_before - suite.balance("ibc/usdc", ALICE), 2_000_000);
+    assert_eq!(suite.token_balance(&lp_token, ALICE), 1_000_000);
+
+    let pool = suite.pool("ibc/usdc", "uatom");
+    assert_eq!(pool.reserve_a, Uint128::new(6_000_000));
+    assert_eq!(pool.reserve_b, Uint128::new(1_500_000));
+}
+
+#[test]
+fn liquidity_math_does_not_overflow_with_18_decimals() {
+    let mut suite = Suite::new();
+    let ten = 10_000_000_000_000_000_000u128;
+    let create = ExecuteMsg::CreatePool {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        initial_a: Uint128::new(ten),
+        initial_b: Uint128::new(ten),
+        fee_rate: None,
+        pool_type: None,
+    };
+    suite
+        .execute(USER, &create, &[coin(ten, "ibc/usdc"), coin(ten, "uatom")])
+        .unwrap();
+
+    // 100 tokens offered against reserves of 10: the products exceed u128
+    let add = ExecuteMsg::AddLiquidity {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        amount_a: Uint128::new(10 * ten),
+        amount_b: Uint128::new(10 * ten),
+        min_liquidity: Uint128::new(1),
+    };
+    suite
+        .execute(
+            ALICE,
+            &add,
+            &[coin(10 * ten, "ibc/usdc"), coin(10 * ten, "uatom")],
+        )
+        .unwrap();
+    let lp_token = suite.lp_token("ibc/usdc", "uatom");
+    assert_eq!(suite.token_balance(&lp_token, ALICE), 10 * ten);
+
+    let withdraw = Cw20HookMsg::WithdrawLiquidity {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        min_a: Uint128::new(10 * ten),
+        min_b: Uint128::new(10 * ten),
+    };
+    suite.send(ALICE, &lp_token, 10 * ten, &withdraw).unwrap();
+    assert_eq!(suite.pool("ibc/usdc", "uatom").reserve_a, Uint128::new(ten));
+}
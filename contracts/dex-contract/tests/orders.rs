@@ -0,0 +1,102 @@
+mod common;
+
+use common::{attribute, contract_error, native, Suite, ALICE, USER};
+use cosmwasm_std::{coin, Decimal256, Uint128};
+use dex_contract::msg::{ExecuteMsg, LimitOrderInfo, QueryMsg};
+use dex_contract::ContractError;
+use std::str::FromStr;
+
+// 4 ibc/usdc per uatom
+fn create_pool(suite: &mut Suite) {
+    let create = ExecuteMsg::CreatePool {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        initial_a: Uint128::new(4_000_000),
+        initial_b: Uint128::new(1_000_000),
+        fee_rate: None,
+        pool_type: None,
+    };
+    suite
+        .execute(
+            USER,
+            &create,
+            &[coin(4_000_000, "ibc/usdc"), coin(1_000_000, "uatom")],
+        )
+        .unwrap();
+}
+
+// ALICE sells 100_000 ibc/usdc for 0.2 uatom each, cheaper than the pool's 0.25
+fn place_order(suite: &mut Suite) -> u64 {
+    let place = ExecuteMsg::PlaceLimitOrder {
+        offer_token: native("ibc/usdc"),
+        ask_token: native("uatom"),
+        offer_amount: Uint128::new(100_000),
+        price: Decimal256::from_str("0.2").unwrap(),
+    };
+    let response = suite
+        .execute(ALICE, &place, &[coin(100_000, "ibc/usdc")])
+        .unwrap();
+    attribute(&response, "order_id").parse().unwrap()
+}
+
+#[test]
+fn swaps_crossing_a_resting_order_fill_it() {
+    let mut suite = Suite::new();
+    create_pool(&mut suite);
+    let order_id = place_order(&mut suite);
+
+    let atom_before = suite.balance("uatom", ALICE);
+    let usdc_before = suite.balance("ibc/usdc", USER);
+    let swap = ExecuteMsg::Swap {
+        token_in: native("uatom"),
+        token_out: native("ibc/usdc"),
+        amount_in: Uint128::new(10_000),
+        min_amount_out: Uint128::zero(),
+    };
+    let response = suite
+        .execute(USER, &swap, &[coin(10_000, "uatom")])
+        .unwrap();
+    assert_eq!(attribute(&response, "orders_filled"), "1");
+
+    // The order pays 5 ibc/usdc per uatom, more than the pool, so it takes the whole input
+    assert_eq!(suite.balance("ibc/usdc", USER) - usdc_before, 50_000);
+    assert_eq!(suite.balance("uatom", ALICE) - atom_before, 10_000);
+    let order: LimitOrderInfo = suite.query(&QueryMsg::LimitOrder { order_id });
+    assert_eq!(order.remaining, Uint128::new(50_000));
+    assert_eq!(order.received, Uint128::new(10_000));
+    let pool = suite.pool("ibc/usdc", "uatom");
+    assert_eq!(
+        (pool.reserve_a.u128(), pool.reserve_b.u128()),
+        (4_000_000, 1_000_000)
+    );
+}
+
+#[test]
+fn cancelling_an_order_refunds_the_unsold_part() {
+    let mut suite = Suite::new();
+    create_pool(&mut suite);
+    let order_id = place_order(&mut suite);
+    let swap = ExecuteMsg::Swap {
+        token_in: native("uatom"),
+        token_out: native("ibc/usdc"),
+        amount_in: Uint128::new(10_000),
+        min_amount_out: Uint128::zero(),
+    };
+    suite
+        .execute(USER, &swap, &[coin(10_000, "uatom")])
+        .unwrap();
+
+    let cancel = ExecuteMsg::CancelLimitOrder { order_id };
+    let err = contract_error(suite.execute(USER, &cancel, &[]));
+    assert!(matches!(err, ContractError::Unauthorized {}), "{}", err);
+
+    let usdc_before = suite.balance("ibc/usdc", ALICE);
+    suite.execute(ALICE, &cancel, &[]).unwrap();
+    assert_eq!(suite.balance("ibc/usdc", ALICE) - usdc_before, 50_000);
+    let orders: Vec<LimitOrderInfo> = suite.query(&QueryMsg::LimitOrdersByOwner {
+        owner: ALICE.to_string(),
+        start_after: None,
+        limit: None,
+    });
+    assert!(orders.is_empty());
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::{attribute, native, Suite, ALICE, USER};
+use cosmwasm_std::{coin, Uint128};
+use dex_contract::msg::{Cw20HookMsg, ExecuteMsg, QueryMsg, SimulationResponse};
+
+fn create_pool(fee_rate: u128) -> ExecuteMsg {
+    ExecuteMsg::CreatePool {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        initial_a: Uint128::new(1_000_000),
+        initial_b: Uint128::new(4_000_000),
+        fee_rate: Some(Uint128::new(fee_rate)),
+        pool_type: None,
+    }
+}
+
+#[test]
+fn create_swap_withdraw() {
+    let mut suite = Suite::new();
+    let funds = [coin(4_000_000, "ibc/usdc"), coin(1_000_000, "uatom")];
+
+    // Only the configured fee tiers can be chosen
+    suite.execute(USER, &create_pool(7), &funds).unwrap_err();
+    suite.execute(USER, &create_pool(100), &funds).unwrap();
+
+    // Pool tokens are kept in byte order
+    let pool = suite.pool("uatom", "ibc/usdc");
+    assert_eq!(pool.token_a, native("ibc/usdc"));
+    assert_eq!(pool.fee_rate, Uint128::new(100));
+    assert_eq!(pool.total_liquidity, Uint128::new(2_000_000));
+
+    // The creator gets sqrt(a * b) shares less the permanently locked minimum
+    let lp_token = suite.lp_token("uatom", "ibc/usdc");
+    assert_eq!(suite.token_balance(&lp_token, USER), 1_999_000);
+
+    // 10000 uatom, 1% fee: 9900 * 4000000 / 1009900
+    let simulation: SimulationResponse = suite.query(&QueryMsg::Simulation {
+        token_in: native("uatom"),
+        token_out: native("ibc/usdc"),
+        amount_in: Uint128::new(10_000),
+    });
+    assert_eq!(simulation.amount_out, Uint128::new(39_211));
+    assert_eq!(simulation.fee, Uint128::new(100));
+
+    let swap = ExecuteMsg::Swap {
+        token_in: native("uatom"),
+        token_out: native("ibc/usdc"),
+        amount_in: Uint128::new(10_000),
+        min_amount_out: simulation.amount_out,
+    };
+    let usdc_before = suite.balance("ibc/usdc", ALICE);
+    suite
+        .execute(ALICE, &swap, &[coin(10_000, "uatom")])
+        .unwrap();
+    assert_eq!(suite.balance("ibc/usdc", ALICE) - usdc_before, 39_211);
+
+    let pool = suite.pool("uatom", "ibc/usdc");
+    assert_eq!(pool.reserve_a, Uint128::new(3_960_789));
+    assert_eq!(pool.reserve_b, Uint128::new(1_010_000));
+
+    // The same swap again would now fall short of min_amount_out
+    suite
+        .execute(ALICE, &swap, &[coin(10_000, "uatom")])
+        .unwrap_err();
+
+    // Withdrawing with RemoveLiquidity burns the LP tokens from an allowance
+    let remove = ExecuteMsg::RemoveLiquidity {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        liquidity: Uint128::new(200_000),
+        min_a: Uint128::zero(),
+        min_b: Uint128::zero(),
+    };
+    suite.execute(USER, &remove, &[]).unwrap_err();
+    suite.approve(USER, &lp_token, 200_000);
+
+    let (atom_before, usdc_before) = (
+        suite.balance("uatom", USER),
+        suite.balance("ibc/usdc", USER),
+    );
+    suite.execute(USER, &remove, &[]).unwrap();
+    assert_eq!(suite.balance("uatom", USER) - atom_before, 101_000);
+    assert_eq!(suite.balance("ibc/usdc", USER) - usdc_before, 396_078);
+    assert_eq!(suite.token_balance(&lp_token, USER), 1_799_000);
+
+    // The WithdrawLiquidity hook burns sent LP tokens without an allowance
+    let withdraw = Cw20HookMsg::WithdrawLiquidity {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        min_a: Uint128::new(101_001),
+        min_b: Uint128::zero(),
+    };
+    suite.send(USER, &lp_token, 200_000, &withdraw).unwrap_err();
+
+    let withdraw = Cw20HookMsg::WithdrawLiquidity {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        min_a: Uint128::new(101_000),
+        min_b: Uint128::zero(),
+    };
+    let response = suite.send(USER, &lp_token, 200_000, &withdraw).unwrap();
+    assert_eq!(attribute(&response, "amount_a"), "101000");
+    assert_eq!(suite.token_balance(&lp_token, USER), 1_599_000);
+
+    let pool = suite.pool("uatom", "ibc/usdc");
+    assert_eq!(pool.total_liquidity, Uint128::new(1_600_000));
+    assert_eq!(pool.reserve_b, Uint128::new(808_000));
+}
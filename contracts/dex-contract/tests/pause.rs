@@ -0,0 +1,91 @@
+mod common;
+
+use common::{contract_error, native, Suite, ADMIN, ALICE, USER};
+use cosmwasm_std::{coin, Uint128};
+use dex_contract::msg::ExecuteMsg;
+use dex_contract::state::PoolId;
+use dex_contract::ContractError;
+
+fn pair() -> PoolId {
+    PoolId::Pair {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+    }
+}
+
+fn swap() -> ExecuteMsg {
+    ExecuteMsg::Swap {
+        token_in: native("uatom"),
+        token_out: native("ibc/usdc"),
+        amount_in: Uint128::new(10_000),
+        min_amount_out: Uint128::zero(),
+    }
+}
+
+fn remove(liquidity: u128) -> ExecuteMsg {
+    ExecuteMsg::RemoveLiquidity {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        liquidity: Uint128::new(liquidity),
+        min_a: Uint128::zero(),
+        min_b: Uint128::zero(),
+    }
+}
+
+#[test]
+fn pauses_block_swaps_but_not_withdrawals() {
+    let mut suite = Suite::new();
+    let create = ExecuteMsg::CreatePool {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        initial_a: Uint128::new(4_000_000),
+        initial_b: Uint128::new(1_000_000),
+        fee_rate: None,
+        pool_type: None,
+    };
+    suite
+        .execute(
+            USER,
+            &create,
+            &[coin(4_000_000, "ibc/usdc"), coin(1_000_000, "uatom")],
+        )
+        .unwrap();
+    let lp_token = suite.lp_token("ibc/usdc", "uatom");
+    suite.approve(USER, &lp_token, 200_000);
+
+    // Only the pauser, which defaults to the admin, can pause
+    let pause = |pool: Option<PoolId>| ExecuteMsg::Pause {
+        pool,
+        reason: "incident".to_string(),
+    };
+    let err = contract_error(suite.execute(ALICE, &pause(Some(pair())), &[]));
+    assert!(matches!(err, ContractError::Unauthorized {}), "{}", err);
+
+    // A paused pool refuses swaps, while its liquidity can still leave
+    suite.execute(ADMIN, &pause(Some(pair())), &[]).unwrap();
+    let err = contract_error(suite.execute(ALICE, &swap(), &[coin(10_000, "uatom")]));
+    assert!(
+        matches!(err, ContractError::PoolPaused { ref reason } if reason == "incident"),
+        "{}",
+        err
+    );
+    let usdc_before = suite.balance("ibc/usdc", USER);
+    suite.execute(USER, &remove(100_000), &[]).unwrap();
+    assert_eq!(suite.balance("ibc/usdc", USER) - usdc_before, 200_000);
+
+    // The same holds for a contract-wide pause
+    suite
+        .execute(ADMIN, &ExecuteMsg::Unpause { pool: Some(pair()) }, &[])
+        .unwrap();
+    suite.execute(ADMIN, &pause(None), &[]).unwrap();
+    let err = contract_error(suite.execute(ALICE, &swap(), &[coin(10_000, "uatom")]));
+    assert!(matches!(err, ContractError::Paused { .. }), "{}", err);
+    suite.execute(USER, &remove(100_000), &[]).unwrap();
+
+    suite
+        .execute(ADMIN, &ExecuteMsg::Unpause { pool: None }, &[])
+        .unwrap();
+    suite
+        .execute(ALICE, &swap(), &[coin(10_000, "uatom")])
+        .unwrap();
+}
@@ -0,0 +1,184 @@
+mod common;
+
+use common::{attribute, native, Suite, ADMIN, ALICE, USER};
+use cosmwasm_std::{coin, Uint128};
+use dex_contract::msg::{ExecuteMsg, QueryMsg, ReverseSimulationResponse, SimulationResponse};
+use dex_contract::state::PoolType;
+
+fn create_pool(amp: u64) -> ExecuteMsg {
+    ExecuteMsg::CreatePool {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        initial_a: Uint128::new(10_000_000),
+        initial_b: Uint128::new(10_000_000),
+        fee_rate: Some(Uint128::new(5)),
+        pool_type: Some(PoolType::Stable { amp }),
+    }
+}
+
+fn simulate(suite: &Suite, amount_in: u128) -> SimulationResponse {
+    suite.query(&QueryMsg::Simulation {
+        token_in: native("uatom"),
+        token_out: native("ibc/usdc"),
+        amount_in: Uint128::new(amount_in),
+    })
+}
+
+// A suite with a balanced stable pool of 10 ATOM and 10 USDC
+fn stable_suite(amp: u64) -> Suite {
+    let mut suite = Suite::new();
+    let funds = [coin(10_000_000, "ibc/usdc"), coin(10_000_000, "uatom")];
+    suite.execute(USER, &create_pool(amp), &funds).unwrap();
+    suite
+}
+
+#[test]
+fn create_and_swap() {
+    let mut suite = Suite::new();
+    let funds = [coin(10_000_000, "ibc/usdc"), coin(10_000_000, "uatom")];
+
+    suite.execute(USER, &create_pool(0), &funds).unwrap_err();
+    suite.execute(USER, &create_pool(100), &funds).unwrap();
+
+    let pool = suite.pool("uatom", "ibc/usdc");
+    assert_eq!(pool.pool_type, PoolType::Stable { amp: 100 });
+    assert_eq!(pool.amp, Some(100));
+
+    // A 10% trade of a balanced pool stays close to 1:1, less the 0.05% fee
+    let simulation = simulate(&suite, 1_000_000);
+    assert_eq!(simulation.fee, Uint128::new(500));
+    assert!(simulation.amount_out > Uint128::new(990_000));
+    assert!(simulation.amount_out < Uint128::new(999_500));
+
+    let usdc_before = suite.balance("ibc/usdc", ALICE);
+    let swap = ExecuteMsg::Swap {
+        token_in: native("uatom"),
+        token_out: native("ibc/usdc"),
+        amount_in: Uint128::new(1_000_000),
+        min_amount_out: simulation.amount_out,
+    };
+    suite
+        .execute(ALICE, &swap, &[coin(1_000_000, "uatom")])
+        .unwrap();
+    assert_eq!(
+        suite.balance("ibc/usdc", ALICE) - usdc_before,
+        simulation.amount_out.u128()
+    );
+
+    // Exact output swaps charge what the reverse simulation quotes and refund the rest
+    let reverse: ReverseSimulationResponse = suite.query(&QueryMsg::ReverseSimulation {
+        token_in: native("ibc/usdc"),
+        token_out: native("uatom"),
+        amount_out: Uint128::new(500_000),
+    });
+    let (atom_before, usdc_before) = (
+        suite.balance("uatom", ALICE),
+        suite.balance("ibc/usdc", ALICE),
+    );
+    let swap_exact_out = ExecuteMsg::SwapExactOut {
+        token_in: native("ibc/usdc"),
+        token_out: native("uatom"),
+        amount_out: Uint128::new(500_000),
+        max_amount_in: Uint128::new(600_000),
+    };
+    suite
+        .execute(ALICE, &swap_exact_out, &[coin(600_000, "ibc/usdc")])
+        .unwrap();
+    assert_eq!(suite.balance("uatom", ALICE) - atom_before, 500_000);
+    assert_eq!(
+        usdc_before - suite.balance("ibc/usdc", ALICE),
+        reverse.amount_in.u128()
+    );
+}
+
+#[test]
+fn imbalanced_deposit_and_withdrawal() {
+    let mut suite = stable_suite(100);
+    let deposit = ExecuteMsg::AddLiquidity {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        amount_a: Uint128::new(2_000_000),
+        amount_b: Uint128::one(),
+        min_liquidity: Uint128::new(1),
+    };
+    let (atom_before, usdc_before) = (
+        suite.balance("uatom", ALICE),
+        suite.balance("ibc/usdc", ALICE),
+    );
+    let response = suite
+        .execute(
+            ALICE,
+            &deposit,
+            &[coin(1, "ibc/usdc"), coin(2_000_000, "uatom")],
+        )
+        .unwrap();
+    let liquidity: u128 = attribute(&response, "liquidity").parse().unwrap();
+
+    suite.approve(ALICE, &suite.lp_token("uatom", "ibc/usdc"), liquidity);
+    let remove = ExecuteMsg::RemoveLiquidity {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        liquidity: Uint128::new(liquidity),
+        min_a: Uint128::zero(),
+        min_b: Uint128::zero(),
+    };
+    suite.execute(ALICE, &remove, &[]).unwrap();
+
+    // Depositing one side and withdrawing both right away is a swap in disguise, and the
+    // imbalance fee makes it cost about as much as swapping the same amount
+    let atom_spent = atom_before - suite.balance("uatom", ALICE);
+    let usdc_received = suite.balance("ibc/usdc", ALICE) - usdc_before;
+    let quote = simulate(&stable_suite(100), atom_spent).amount_out.u128();
+    assert!(
+        usdc_received.abs_diff(quote) <= quote / 10_000,
+        "{} vs {}",
+        usdc_received,
+        quote
+    );
+}
+
+#[test]
+fn ramp_amp() {
+    let mut suite = stable_suite(100);
+
+    let now = suite.now();
+    let ramp = |future_amp: u64, future_time: u64| ExecuteMsg::RampAmp {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        future_amp,
+        future_time,
+    };
+
+    // At most a tenfold change, over at least a day, by the admin
+    suite
+        .execute(ADMIN, &ramp(1001, now + 2 * 86_400), &[])
+        .unwrap_err();
+    suite
+        .execute(ADMIN, &ramp(1000, now + 3_600), &[])
+        .unwrap_err();
+    suite
+        .execute(USER, &ramp(1000, now + 2 * 86_400), &[])
+        .unwrap_err();
+
+    let before = simulate(&suite, 3_000_000).amount_out;
+    suite
+        .execute(ADMIN, &ramp(1000, now + 2 * 86_400), &[])
+        .unwrap();
+
+    // Halfway through, amp has moved linearly and swaps follow it
+    suite.advance_time(86_400);
+    assert_eq!(suite.pool("uatom", "ibc/usdc").amp, Some(550));
+    assert!(simulate(&suite, 3_000_000).amount_out > before);
+
+    // Stopping the ramp keeps the current amp
+    let stop = ExecuteMsg::StopRampAmp {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+    };
+    suite.execute(ADMIN, &stop, &[]).unwrap();
+    suite.advance_time(86_400);
+
+    let pool = suite.pool("uatom", "ibc/usdc");
+    assert_eq!(pool.pool_type, PoolType::Stable { amp: 550 });
+    assert_eq!(pool.amp, Some(550));
+}
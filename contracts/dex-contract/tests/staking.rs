@@ -0,0 +1,224 @@
+mod common;
+
+use common::{native, Suite, ADMIN, ALICE, USER};
+use cosmwasm_std::{coin, Addr, Uint128};
+use dex_contract::msg::{
+    Cw20HookMsg, ExecuteMsg, PendingRewardsResponse, QueryMsg, RewardSchedulesResponse,
+};
+
+// A uatom/usdc pool where USER holds 999000 LP tokens and ALICE 3000000
+fn setup() -> (Suite, Addr) {
+    let mut suite = Suite::new();
+    let create = ExecuteMsg::CreatePool {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        initial_a: Uint128::new(1_000_000),
+        initial_b: Uint128::new(1_000_000),
+        fee_rate: None,
+        pool_type: None,
+    };
+    suite
+        .execute(
+            USER,
+            &create,
+            &[coin(1_000_000, "ibc/usdc"), coin(1_000_000, "uatom")],
+        )
+        .unwrap();
+
+    let deposit = ExecuteMsg::AddLiquidity {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        amount_a: Uint128::new(3_000_000),
+        amount_b: Uint128::new(3_000_000),
+        min_liquidity: Uint128::new(1),
+    };
+    suite
+        .execute(
+            ALICE,
+            &deposit,
+            &[coin(3_000_000, "ibc/usdc"), coin(3_000_000, "uatom")],
+        )
+        .unwrap();
+
+    let lp_token = suite.lp_token("uatom", "ibc/usdc");
+    assert_eq!(suite.token_balance(&lp_token, USER), 999_000);
+    assert_eq!(suite.token_balance(&lp_token, ALICE), 3_000_000);
+    (suite, lp_token)
+}
+
+// 1000 ustake per second for 100 seconds
+fn reward_schedule(start_time: u64) -> ExecuteMsg {
+    ExecuteMsg::CreateRewardSchedule {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        reward: native("ustake"),
+        start_time,
+        end_time: start_time + 100,
+        rate: Uint128::new(1_000),
+    }
+}
+
+fn bond() -> Cw20HookMsg {
+    Cw20HookMsg::Bond {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+    }
+}
+
+fn claim() -> ExecuteMsg {
+    ExecuteMsg::Claim {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+    }
+}
+
+fn pending(suite: &Suite, user: &str) -> u128 {
+    let response: PendingRewardsResponse = suite.query(&QueryMsg::PendingRewards {
+        user: user.to_string(),
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+    });
+    response
+        .rewards
+        .iter()
+        .map(|reward| reward.amount.u128())
+        .sum()
+}
+
+#[test]
+fn bond_claim_unbond() {
+    let (mut suite, lp_token) = setup();
+    let now = suite.now();
+
+    // Only the admin funds schedules, with the whole emission, starting in the future
+    let funds = [coin(100_000, "ustake")];
+    suite
+        .execute(USER, &reward_schedule(now + 10), &funds)
+        .unwrap_err();
+    suite
+        .execute(ADMIN, &reward_schedule(now + 10), &[coin(99_999, "ustake")])
+        .unwrap_err();
+    suite
+        .execute(ADMIN, &reward_schedule(now - 1), &funds)
+        .unwrap_err();
+    suite
+        .execute(ADMIN, &reward_schedule(now + 10), &funds)
+        .unwrap();
+
+    // Pools with an LP token bond it with the hook only
+    let bond_shares = ExecuteMsg::Bond {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        amount: Uint128::new(10),
+    };
+    suite.execute(USER, &bond_shares, &[]).unwrap_err();
+    suite.send(USER, &lp_token, 999_000, &bond()).unwrap();
+    suite.send(ALICE, &lp_token, 3_000_000, &bond()).unwrap();
+
+    let schedules: RewardSchedulesResponse = suite.query(&QueryMsg::RewardSchedules {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+    });
+    assert_eq!(schedules.total_bonded, Uint128::new(3_999_000));
+    assert_eq!(schedules.schedules.len(), 1);
+
+    // 50 seconds of emission, shared by bond
+    suite.advance_time(60);
+    assert_eq!(pending(&suite, USER), 12_490);
+    assert_eq!(pending(&suite, ALICE), 37_509);
+
+    // ALICE leaves with the LP tokens and keeps what she earned; USER earns the rest alone
+    let unbond = |amount: u128| ExecuteMsg::Unbond {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        amount: Uint128::new(amount),
+    };
+    suite.execute(ALICE, &unbond(3_000_000), &[]).unwrap();
+    suite.execute(ALICE, &unbond(1), &[]).unwrap_err();
+    assert_eq!(suite.token_balance(&lp_token, ALICE), 3_000_000);
+
+    suite.advance_time(1_000);
+    assert_eq!(pending(&suite, USER), 62_490);
+    assert_eq!(pending(&suite, ALICE), 37_509);
+
+    // Ended schedules are no longer listed, but their rewards can still be claimed
+    let schedules: RewardSchedulesResponse = suite.query(&QueryMsg::RewardSchedules {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+    });
+    assert!(schedules.schedules.is_empty());
+
+    for (user, earned) in [(USER, 62_490), (ALICE, 37_509)] {
+        let before = suite.balance("ustake", user);
+        suite.execute(user, &claim(), &[]).unwrap();
+        assert_eq!(suite.balance("ustake", user) - before, earned);
+    }
+    suite.execute(ALICE, &claim(), &[]).unwrap_err();
+
+    // Only rounding dust stays behind
+    assert_eq!(suite.balance("ustake", suite.dex.as_str()), 1);
+
+    suite.execute(USER, &unbond(999_000), &[]).unwrap();
+    assert_eq!(suite.token_balance(&lp_token, USER), 999_000);
+}
+
+#[test]
+fn reclaim_undistributed_rewards() {
+    let (mut suite, lp_token) = setup();
+    let now = suite.now();
+    suite
+        .execute(
+            ADMIN,
+            &reward_schedule(now + 10),
+            &[coin(100_000, "ustake")],
+        )
+        .unwrap();
+
+    // Nothing is bonded for the first 50 seconds of the schedule
+    suite.advance_time(60);
+    suite.send(USER, &lp_token, 999_000, &bond()).unwrap();
+    suite.advance_time(100);
+
+    // Only the admin reclaims, once the schedule has ended
+    let reclaim = ExecuteMsg::ReclaimRewards { schedule_id: 1 };
+    suite.execute(USER, &reclaim, &[]).unwrap_err();
+    suite
+        .execute(ADMIN, &ExecuteMsg::ReclaimRewards { schedule_id: 2 }, &[])
+        .unwrap_err();
+
+    let before = suite.balance("ustake", ADMIN);
+    suite.execute(ADMIN, &reclaim, &[]).unwrap();
+    assert_eq!(suite.balance("ustake", ADMIN) - before, 50_000);
+    suite.execute(ADMIN, &reclaim, &[]).unwrap_err();
+
+    // The staker still gets the half emitted while bonded
+    let before = suite.balance("ustake", USER);
+    suite.execute(USER, &claim(), &[]).unwrap();
+    assert_eq!(suite.balance("ustake", USER) - before, 49_999);
+}
+
+#[test]
+fn reclaim_waits_for_the_end() {
+    let (mut suite, _) = setup();
+    let now = suite.now();
+    suite
+        .execute(
+            ADMIN,
+            &reward_schedule(now + 10),
+            &[coin(100_000, "ustake")],
+        )
+        .unwrap();
+
+    suite.advance_time(60);
+    suite
+        .execute(ADMIN, &ExecuteMsg::ReclaimRewards { schedule_id: 1 }, &[])
+        .unwrap_err();
+
+    // With nothing ever bonded the whole emission comes back
+    suite.advance_time(100);
+    let before = suite.balance("ustake", ADMIN);
+    suite
+        .execute(ADMIN, &ExecuteMsg::ReclaimRewards { schedule_id: 1 }, &[])
+        .unwrap();
+    assert_eq!(suite.balance("ustake", ADMIN) - before, 100_000);
+}
@@ -0,0 +1,199 @@
+mod common;
+
+use common::{contract_error, cw20, native, Suite, ALICE, USER};
+use cosmwasm_std::{coin, Uint128};
+use dex_contract::execute::MAX_SWAP_OPERATIONS;
+use dex_contract::msg::{
+    ExecuteMsg, QueryMsg, SimulateSwapOperationsResponse, SimulationResponse, SwapOperation,
+};
+use dex_contract::state::AssetInfo;
+use dex_contract::ContractError;
+
+fn swap(token_in: AssetInfo, token_out: AssetInfo, amount_in: u128) -> ExecuteMsg {
+    ExecuteMsg::Swap {
+        token_in,
+        token_out,
+        amount_in: Uint128::new(amount_in),
+        min_amount_out: Uint128::zero(),
+    }
+}
+
+#[test]
+fn native_swaps_need_exactly_the_input() {
+    let mut suite = Suite::new();
+    let create = ExecuteMsg::CreatePool {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        initial_a: Uint128::new(4_000_000),
+        initial_b: Uint128::new(1_000_000),
+        fee_rate: None,
+        pool_type: None,
+    };
+    suite
+        .execute(
+            USER,
+            &create,
+            &[coin(4_000_000, "ibc/usdc"), coin(1_000_000, "uatom")],
+        )
+        .unwrap();
+
+    let swap = swap(native("uatom"), native("ibc/usdc"), 10_000);
+    let err = contract_error(suite.execute(ALICE, &swap, &[]));
+    assert!(
+        matches!(err, ContractError::FundsMismatch { .. }),
+        "{}",
+        err
+    );
+    let err = contract_error(suite.execute(ALICE, &swap, &[coin(9_999, "uatom")]));
+    assert!(
+        matches!(err, ContractError::FundsMismatch { .. }),
+        "{}",
+        err
+    );
+    let err = contract_error(suite.execute(ALICE, &swap, &[coin(10_001, "uatom")]));
+    assert!(
+        matches!(err, ContractError::FundsMismatch { .. }),
+        "{}",
+        err
+    );
+    let funds = [coin(1, "ibc/usdc"), coin(10_000, "uatom")];
+    let err = contract_error(suite.execute(ALICE, &swap, &funds));
+    assert!(matches!(err, ContractError::UnexpectedFunds {}), "{}", err);
+
+    let simulation: SimulationResponse = suite.query(&QueryMsg::Simulation {
+        token_in: native("uatom"),
+        token_out: native("ibc/usdc"),
+        amount_in: Uint128::new(10_000),
+    });
+    let (atom_before, usdc_before) = (
+        suite.balance("uatom", ALICE),
+        suite.balance("ibc/usdc", ALICE),
+    );
+    suite
+        .execute(ALICE, &swap, &[coin(10_000, "uatom")])
+        .unwrap();
+    assert_eq!(atom_before - suite.balance("uatom", ALICE), 10_000);
+    assert_eq!(
+        suite.balance("ibc/usdc", ALICE) - usdc_before,
+        simulation.amount_out.u128()
+    );
+    assert_eq!(suite.balance("uatom", suite.dex.as_str()), 1_010_000);
+}
+
+#[test]
+fn cw20_swaps_pull_the_input() {
+    let mut suite = Suite::new();
+    let token = suite.create_token("TKN");
+    suite.approve(USER, &token, 1_000_000);
+    let create = ExecuteMsg::CreatePool {
+        token_a: cw20(&token),
+        token_b: native("uatom"),
+        initial_a: Uint128::new(1_000_000),
+        initial_b: Uint128::new(1_000_000),
+        fee_rate: None,
+        pool_type: None,
+    };
+    suite
+        .execute(USER, &create, &[coin(1_000_000, "uatom")])
+        .unwrap();
+
+    // Without an allowance there is nothing to pull, and native funds are refused
+    let swap = swap(cw20(&token), native("uatom"), 10_000);
+    suite.execute(ALICE, &swap, &[]).unwrap_err();
+    suite.approve(ALICE, &token, 10_000);
+    let err = contract_error(suite.execute(ALICE, &swap, &[coin(10_000, "uatom")]));
+    assert!(matches!(err, ContractError::UnexpectedFunds {}), "{}", err);
+
+    let (token_before, atom_before) = (
+        suite.token_balance(&token, ALICE),
+        suite.balance("uatom", ALICE),
+    );
+    suite.execute(ALICE, &swap, &[]).unwrap();
+    assert_eq!(token_before - suite.token_balance(&token, ALICE), 10_000);
+    assert_eq!(suite.token_balance(&token, suite.dex.as_str()), 1_010_000);
+    assert!(suite.balance("uatom", ALICE) > atom_before);
+
+    // The allowance is used up
+    suite.execute(ALICE, &swap, &[]).unwrap_err();
+}
+
+fn hop(token_in: &str, token_out: &str) -> SwapOperation {
+    SwapOperation {
+        token_in: native(token_in),
+        token_out: native(token_out),
+    }
+}
+
+#[test]
+fn multi_hop_swaps_check_the_route_and_the_final_output() {
+    let mut suite = Suite::new();
+    for (token_a, initial_a) in [("ibc/eth", 500_000), ("ibc/usdc", 4_000_000)] {
+        let create = ExecuteMsg::CreatePool {
+            token_a: native(token_a),
+            token_b: native("uatom"),
+            initial_a: Uint128::new(initial_a),
+            initial_b: Uint128::new(1_000_000),
+            fee_rate: None,
+            pool_type: None,
+        };
+        suite
+            .execute(
+                USER,
+                &create,
+                &[coin(initial_a, token_a), coin(1_000_000, "uatom")],
+            )
+            .unwrap();
+    }
+
+    // Routes longer than MAX_SWAP_OPERATIONS are rejected, even over existing pools
+    let too_long: Vec<_> = (0..=MAX_SWAP_OPERATIONS)
+        .map(|i| {
+            if i % 2 == 0 {
+                hop("uatom", "ibc/usdc")
+            } else {
+                hop("ibc/usdc", "uatom")
+            }
+        })
+        .collect();
+    let msg = ExecuteMsg::ExecuteSwapOperations {
+        operations: too_long,
+        amount_in: Uint128::new(10_000),
+        minimum_receive: Uint128::zero(),
+    };
+    let err = contract_error(suite.execute(ALICE, &msg, &[coin(10_000, "uatom")]));
+    assert!(
+        matches!(err, ContractError::InvalidSwapOperations {}),
+        "{}",
+        err
+    );
+
+    // ETH -> ATOM -> USDC pays out the simulated amount, and no less than minimum_receive
+    let operations = vec![hop("ibc/eth", "uatom"), hop("uatom", "ibc/usdc")];
+    let simulation: SimulateSwapOperationsResponse =
+        suite.query(&QueryMsg::SimulateSwapOperations {
+            amount_in: Uint128::new(10_000),
+            operations: operations.clone(),
+        });
+    let swap = |minimum_receive: Uint128| ExecuteMsg::ExecuteSwapOperations {
+        operations: operations.clone(),
+        amount_in: Uint128::new(10_000),
+        minimum_receive,
+    };
+    let funds = [coin(10_000, "ibc/eth")];
+    let err =
+        contract_error(suite.execute(ALICE, &swap(simulation.amount_out + Uint128::one()), &funds));
+    assert!(matches!(err, ContractError::SlippageExceeded {}), "{}", err);
+
+    let usdc_before = suite.balance("ibc/usdc", ALICE);
+    suite
+        .execute(ALICE, &swap(simulation.amount_out), &funds)
+        .unwrap();
+    assert_eq!(
+        suite.balance("ibc/usdc", ALICE) - usdc_before,
+        simulation.amount_out.u128()
+    );
+    assert_eq!(
+        suite.pool("ibc/eth", "uatom").reserve_a,
+        Uint128::new(510_000)
+    );
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::{native, Suite, ALICE, USER};
+use cosmwasm_std::{coin, Decimal256, StdResult, Uint128};
+use dex_contract::msg::{ExecuteMsg, QueryMsg, TwapResponse};
+
+fn twap(
+    suite: &Suite,
+    token_a: &str,
+    token_b: &str,
+    window_seconds: u64,
+) -> StdResult<TwapResponse> {
+    suite.app.wrap().query_wasm_smart(
+        &suite.dex,
+        &QueryMsg::Twap {
+            token_a: native(token_a),
+            token_b: native(token_b),
+            window_seconds,
+        },
+    )
+}
+
+#[test]
+fn time_weighted_average_prices() {
+    let mut suite = Suite::new();
+    let create = ExecuteMsg::CreatePool {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        initial_a: Uint128::new(1_000_000),
+        initial_b: Uint128::new(4_000_000),
+        fee_rate: None,
+        pool_type: None,
+    };
+    suite
+        .execute(
+            USER,
+            &create,
+            &[coin(4_000_000, "ibc/usdc"), coin(1_000_000, "uatom")],
+        )
+        .unwrap();
+
+    // No history yet
+    twap(&suite, "uatom", "ibc/usdc", 10).unwrap_err();
+
+    // 100 seconds at 4 usdc per atom
+    suite.advance_time(100);
+    let response = twap(&suite, "uatom", "ibc/usdc", 100).unwrap();
+    assert_eq!(response.price_a, Decimal256::from_ratio(4u128, 1u128));
+    assert_eq!(response.price_b, Decimal256::percent(25));
+
+    // Selling 1 atom brings the price to 2003005 / 2000000 usdc per atom
+    let swap = ExecuteMsg::Swap {
+        token_in: native("uatom"),
+        token_out: native("ibc/usdc"),
+        amount_in: Uint128::new(1_000_000),
+        min_amount_out: Uint128::zero(),
+    };
+    suite
+        .execute(ALICE, &swap, &[coin(1_000_000, "uatom")])
+        .unwrap();
+    let pool = suite.pool("uatom", "ibc/usdc");
+    assert_eq!(pool.reserve_a, Uint128::new(2_003_005));
+    suite.advance_time(100);
+
+    // The last 100 seconds only see the new price
+    let new_price = Decimal256::from_ratio(2_003_005u128, 2_000_000u128);
+    assert_eq!(
+        twap(&suite, "uatom", "ibc/usdc", 100).unwrap().price_a,
+        new_price
+    );
+
+    // Longer windows average both, weighted by how long each lasted
+    let response = twap(&suite, "uatom", "ibc/usdc", 200).unwrap();
+    assert_eq!(
+        response.price_a,
+        Decimal256::from_ratio(2_500_751_250u128, 1_000_000_000u128)
+    );
+    let response = twap(&suite, "uatom", "ibc/usdc", 150).unwrap();
+    assert_eq!(
+        response.price_a,
+        (Decimal256::from_ratio(200u128, 1u128)
+            + new_price * Decimal256::from_ratio(100u128, 1u128))
+            / Decimal256::from_ratio(150u128, 1u128)
+    );
+
+    // Tokens can be given in either order
+    let forward = twap(&suite, "uatom", "ibc/usdc", 200).unwrap();
+    let reversed = twap(&suite, "ibc/usdc", "uatom", 200).unwrap();
+    assert_eq!(
+        (reversed.price_a, reversed.price_b),
+        (forward.price_b, forward.price_a)
+    );
+
+    // Windows can't reach back before the pool was created
+    twap(&suite, "uatom", "ibc/usdc", 201).unwrap_err();
+    twap(&suite, "uatom", "ibc/usdc", 0).unwrap_err();
+}
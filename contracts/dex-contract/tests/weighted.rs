@@ -0,0 +1,239 @@
+mod common;
+
+use common::{attribute, cw20, native, Suite, ALICE, USER};
+use cosmwasm_std::{coin, Addr, Decimal256, Uint128};
+use dex_contract::execute::MINIMUM_LIQUIDITY;
+use dex_contract::msg::{
+    Cw20HookMsg, ExecuteMsg, QueryMsg, SimulationResponse, TokenAmount, WeightedAssetParams,
+    WeightedPoolInfo,
+};
+use dex_contract::state::AssetInfo;
+
+fn asset(token: AssetInfo, weight: u128, amount: u128) -> WeightedAssetParams {
+    WeightedAssetParams {
+        token,
+        weight: Uint128::new(weight),
+        amount: Uint128::new(amount),
+    }
+}
+
+fn simulate(
+    suite: &Suite,
+    token_in: AssetInfo,
+    token_out: AssetInfo,
+    amount_in: u128,
+) -> SimulationResponse {
+    suite.query(&QueryMsg::WeightedSimulation {
+        pool_id: 1,
+        token_in,
+        token_out,
+        amount_in: Uint128::new(amount_in),
+    })
+}
+
+fn reserve(pool: &WeightedPoolInfo, token: &AssetInfo) -> u128 {
+    pool.assets
+        .iter()
+        .find(|asset| asset.token == *token)
+        .unwrap()
+        .reserve
+        .u128()
+}
+
+// An 80/20 GOV/ATOM pool of 8 GOV and 2 ATOM, so one GOV is worth one ATOM
+fn create_pool(suite: &mut Suite) -> Addr {
+    let gov = suite.create_token("GOV");
+    suite.approve(USER, &gov, 8_000_000);
+
+    let create = ExecuteMsg::CreateWeightedPool {
+        assets: vec![
+            asset(cw20(&gov), 80, 8_000_000),
+            asset(native("uatom"), 20, 2_000_000),
+        ],
+        fee_rate: None,
+    };
+    suite
+        .execute(USER, &create, &[coin(2_000_000, "uatom")])
+        .unwrap();
+    gov
+}
+
+#[test]
+fn create_swap_withdraw() {
+    let mut suite = Suite::new();
+
+    // A pool needs at least two assets
+    let create = ExecuteMsg::CreateWeightedPool {
+        assets: vec![asset(native("uatom"), 1, 1_000_000)],
+        fee_rate: None,
+    };
+    suite
+        .execute(USER, &create, &[coin(1_000_000, "uatom")])
+        .unwrap_err();
+
+    let gov = create_pool(&mut suite);
+    let pool: WeightedPoolInfo = suite.query(&QueryMsg::WeightedPool { pool_id: 1 });
+    assert_eq!(pool.assets[0].weight, Decimal256::percent(80));
+    assert_eq!(pool.assets[1].weight, Decimal256::percent(20));
+
+    // The initial supply is the weighted geometric mean of the deposit,
+    // 8e6 ^ 0.8 * 2e6 ^ 0.2, less the permanently locked minimum for the creator
+    let total_liquidity = pool.total_liquidity.u128();
+    assert!(
+        total_liquidity.abs_diff(6_062_866) <= 1,
+        "{}",
+        total_liquidity
+    );
+    let lp_token = Addr::unchecked(pool.lp_token_address.unwrap());
+    assert_eq!(
+        suite.token_balance(&lp_token, USER),
+        total_liquidity - MINIMUM_LIQUIDITY
+    );
+    assert_eq!(
+        suite.token_balance(&lp_token, suite.dex.as_str()),
+        MINIMUM_LIQUIDITY
+    );
+
+    // A small trade is priced close to the 1:1 spot price, less the 0.3% fee
+    let simulation = simulate(&suite, native("uatom"), cw20(&gov), 10_000);
+    assert!(simulation.amount_out > Uint128::new(9_900));
+    assert!(simulation.amount_out < Uint128::new(9_970));
+
+    // Trades over 30% of the input balance are refused
+    let swap = |amount_in: u128, min_amount_out: Uint128| ExecuteMsg::WeightedSwap {
+        pool_id: 1,
+        token_in: native("uatom"),
+        token_out: cw20(&gov),
+        amount_in: Uint128::new(amount_in),
+        min_amount_out,
+    };
+    suite
+        .execute(
+            ALICE,
+            &swap(700_000, Uint128::zero()),
+            &[coin(700_000, "uatom")],
+        )
+        .unwrap_err();
+
+    let simulation = simulate(&suite, native("uatom"), cw20(&gov), 500_000);
+    let gov_before = suite.token_balance(&gov, ALICE);
+    suite
+        .execute(
+            ALICE,
+            &swap(500_000, simulation.amount_out),
+            &[coin(500_000, "uatom")],
+        )
+        .unwrap();
+    let gov_received = suite.token_balance(&gov, ALICE) - gov_before;
+    assert_eq!(gov_received, simulation.amount_out.u128());
+
+    let pool: WeightedPoolInfo = suite.query(&QueryMsg::WeightedPool { pool_id: 1 });
+    assert_eq!(reserve(&pool, &native("uatom")), 2_500_000);
+    assert_eq!(reserve(&pool, &cw20(&gov)), 8_000_000 - gov_received);
+
+    // Swapping back through the CW20 hook returns less than was paid
+    let atom_before = suite.balance("uatom", ALICE);
+    let hook = Cw20HookMsg::WeightedSwap {
+        pool_id: 1,
+        token_out: native("uatom"),
+        min_amount_out: Uint128::zero(),
+    };
+    suite.send(ALICE, &gov, gov_received, &hook).unwrap();
+    let atom_received = suite.balance("uatom", ALICE) - atom_before;
+    assert!(atom_received < 500_000 && atom_received > 490_000);
+
+    // Exiting pays a share of every asset
+    let pool: WeightedPoolInfo = suite.query(&QueryMsg::WeightedPool { pool_id: 1 });
+    let (atom_reserve, gov_reserve) = (
+        reserve(&pool, &native("uatom")),
+        reserve(&pool, &cw20(&gov)),
+    );
+    let liquidity = total_liquidity / 10;
+    suite.approve(USER, &lp_token, liquidity);
+
+    // A tenth of the shares pays a tenth of every reserve, rounded down
+    let exit = |min_atom: u128| ExecuteMsg::ExitWeightedPool {
+        pool_id: 1,
+        liquidity: Uint128::new(liquidity),
+        min_amounts: vec![TokenAmount {
+            token: native("uatom"),
+            amount: Uint128::new(min_atom),
+        }],
+    };
+    let atom_share = atom_reserve * liquidity / total_liquidity;
+    let gov_share = gov_reserve * liquidity / total_liquidity;
+    suite.execute(USER, &exit(atom_share + 1), &[]).unwrap_err();
+
+    let (atom_before, gov_before) = (
+        suite.balance("uatom", USER),
+        suite.token_balance(&gov, USER),
+    );
+    suite.execute(USER, &exit(atom_share), &[]).unwrap();
+    assert_eq!(suite.balance("uatom", USER) - atom_before, atom_share);
+    assert_eq!(suite.token_balance(&gov, USER) - gov_before, gov_share);
+    assert_eq!(
+        suite.token_balance(&lp_token, USER),
+        total_liquidity - MINIMUM_LIQUIDITY - liquidity
+    );
+
+    let pool: WeightedPoolInfo = suite.query(&QueryMsg::WeightedPool { pool_id: 1 });
+    assert_eq!(pool.total_liquidity.u128(), total_liquidity - liquidity);
+}
+
+#[test]
+fn join_and_exit() {
+    let mut suite = Suite::new();
+    let gov = create_pool(&mut suite);
+    let pool: WeightedPoolInfo = suite.query(&QueryMsg::WeightedPool { pool_id: 1 });
+    let lp_token = Addr::unchecked(pool.lp_token_address.unwrap());
+    let tenth = pool.total_liquidity.u128() / 10;
+
+    // A proportional join refunds what goes over the pool ratio: 0.8 GOV matches 0.2 ATOM
+    suite.approve(ALICE, &gov, 800_000);
+    let join = ExecuteMsg::JoinWeightedPool {
+        pool_id: 1,
+        assets: vec![
+            TokenAmount {
+                token: native("uatom"),
+                amount: Uint128::new(1_000_000),
+            },
+            TokenAmount {
+                token: cw20(&gov),
+                amount: Uint128::new(800_000),
+            },
+        ],
+        min_liquidity: Uint128::zero(),
+    };
+    let atom_before = suite.balance("uatom", ALICE);
+    suite
+        .execute(ALICE, &join, &[coin(1_000_000, "uatom")])
+        .unwrap();
+    assert_eq!(atom_before - suite.balance("uatom", ALICE), 200_000);
+    assert_eq!(suite.token_balance(&lp_token, ALICE), tenth);
+
+    // A single asset join followed by a single asset exit loses a little to the fee
+    let join_single = ExecuteMsg::JoinWeightedPoolSingle {
+        pool_id: 1,
+        token: native("uatom"),
+        amount: Uint128::new(100_000),
+        min_liquidity: Uint128::zero(),
+    };
+    let atom_before = suite.balance("uatom", ALICE);
+    let response = suite
+        .execute(ALICE, &join_single, &[coin(100_000, "uatom")])
+        .unwrap();
+    let liquidity: u128 = attribute(&response, "liquidity").parse().unwrap();
+
+    suite.approve(ALICE, &lp_token, liquidity);
+    let exit_single = ExecuteMsg::ExitWeightedPoolSingle {
+        pool_id: 1,
+        liquidity: Uint128::new(liquidity),
+        token_out: native("uatom"),
+        min_amount_out: Uint128::zero(),
+    };
+    suite.execute(ALICE, &exit_single, &[]).unwrap();
+
+    let lost = atom_before - suite.balance("uatom", ALICE);
+    assert!(lost > 0 && lost < 1_000, "{}", lost);
+    assert_eq!(suite.token_balance(&lp_token, ALICE), tenth);
+}
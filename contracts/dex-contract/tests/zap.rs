@@ -0,0 +1,125 @@
+mod common;
+
+use common::{attribute, contract_error, native, Suite, USER};
+use cosmwasm_std::{coin, Decimal256, Uint128};
+use dex_contract::msg::{ExecuteMsg, QueryMsg, ZapOutSimulationResponse};
+use dex_contract::state::PoolId;
+use dex_contract::ContractError;
+
+fn create_pool(suite: &mut Suite) {
+    let create = ExecuteMsg::CreatePool {
+        token_a: native("uatom"),
+        token_b: native("ibc/usdc"),
+        initial_a: Uint128::new(1_000_000),
+        initial_b: Uint128::new(4_000_000),
+        fee_rate: None,
+        pool_type: None,
+    };
+    let funds = [coin(4_000_000, "ibc/usdc"), coin(1_000_000, "uatom")];
+    suite.execute(USER, &create, &funds).unwrap();
+}
+
+fn zap_in(pool: PoolId, min_liquidity: u128) -> ExecuteMsg {
+    ExecuteMsg::ZapIn {
+        pool,
+        asset: native("uatom"),
+        amount: Uint128::new(100_000),
+        min_liquidity: Uint128::new(min_liquidity),
+    }
+}
+
+#[test]
+fn zap_in_mints_liquidity_from_one_asset() {
+    let mut suite = Suite::new();
+    create_pool(&mut suite);
+    let pair = PoolId::Pair {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+    };
+    let lp_token = suite.lp_token("uatom", "ibc/usdc");
+    let lp_before = suite.token_balance(&lp_token, USER);
+    let usdc_before = suite.balance("ibc/usdc", USER);
+
+    let funds = [coin(100_000, "uatom")];
+    let response = suite
+        .execute(USER, &zap_in(pair.clone(), 1), &funds)
+        .unwrap();
+    let liquidity: u128 = attribute(&response, "liquidity").parse().unwrap();
+    assert!(liquidity > 0);
+    assert_eq!(suite.token_balance(&lp_token, USER) - lp_before, liquidity);
+
+    // Swapping about half at the pool price leaves little of either side to refund
+    let pool = suite.pool("uatom", "ibc/usdc");
+    assert!(pool.reserve_b > Uint128::new(1_099_000));
+    assert!(suite.balance("ibc/usdc", USER) - usdc_before < 100);
+
+    // The same zap into the moved pool mints a little less
+    let err = contract_error(suite.execute(USER, &zap_in(pair, liquidity), &funds));
+    assert!(
+        matches!(err, ContractError::MinLiquidityNotMet {}),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn zap_in_refuses_concentrated_pools() {
+    let mut suite = Suite::new();
+    let create = ExecuteMsg::CreateConcentratedPool {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+        fee_rate: None,
+        initial_price: Decimal256::one(),
+    };
+    suite.execute(USER, &create, &[]).unwrap();
+
+    let funds = [coin(100_000, "uatom")];
+    let zap = zap_in(PoolId::Concentrated { pool_id: 1 }, 1);
+    let err = contract_error(suite.execute(USER, &zap, &funds));
+    assert!(matches!(err, ContractError::ZapNotSupported {}), "{}", err);
+}
+
+#[test]
+fn zap_out_pays_the_simulated_amount_in_one_asset() {
+    let mut suite = Suite::new();
+    create_pool(&mut suite);
+    let pair = PoolId::Pair {
+        token_a: native("ibc/usdc"),
+        token_b: native("uatom"),
+    };
+    let lp_token = suite.lp_token("uatom", "ibc/usdc");
+    let simulation: ZapOutSimulationResponse = suite.query(&QueryMsg::SimulateZapOut {
+        pool: pair.clone(),
+        liquidity: Uint128::new(100_000),
+        asset: native("uatom"),
+    });
+    // More than the 50000 uatom share, less than twice it after the fee and slippage
+    assert!(simulation.amount_out > Uint128::new(50_000));
+    assert!(simulation.amount_out < Uint128::new(100_000));
+
+    let zap_out = |min_out: Uint128| ExecuteMsg::ZapOut {
+        pool: pair.clone(),
+        liquidity: Uint128::new(100_000),
+        asset: native("uatom"),
+        min_out,
+    };
+    suite.approve(USER, &lp_token, 100_000);
+    let err =
+        contract_error(suite.execute(USER, &zap_out(simulation.amount_out + Uint128::one()), &[]));
+    assert!(matches!(err, ContractError::SlippageExceeded {}), "{}", err);
+
+    let (lp_before, usdc_before) = (
+        suite.token_balance(&lp_token, USER),
+        suite.balance("ibc/usdc", USER),
+    );
+    let atom_before = suite.balance("uatom", USER);
+    suite
+        .execute(USER, &zap_out(simulation.amount_out), &[])
+        .unwrap();
+    assert_eq!(lp_before - suite.token_balance(&lp_token, USER), 100_000);
+    assert_eq!(
+        suite.balance("uatom", USER) - atom_before,
+        simulation.amount_out.u128()
+    );
+    assert_eq!(suite.balance("ibc/usdc", USER), usdc_before);
+}
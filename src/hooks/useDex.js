@@ -1,7 +1,8 @@
 import { useState, useCallback } from 'react';
-import { coin } from '@cosmjs/stargate';
+import { toBinary } from '@cosmjs/cosmwasm-stargate';
 import { config } from '../config';
 import toast from 'react-hot-toast';
+import { toAssetInfo, shouldSwapAssets, nativeFunds } from '../services/assetInfo';
 
 export const useDex = (client, account) => {
   const [loading, setLoading] = useState(false);
@@ -29,8 +30,8 @@ export const useDex = (client, account) => {
     }
   }, [client, contractAddress]);
 
-  // Execute contract
-  const executeContract = useCallback(async (msg, funds = []) => {
+  // Execute a list of contract messages in one transaction
+  const executeInstructions = useCallback(async (instructions) => {
     if (!client || !account) {
       toast.error('Please connect your wallet');
       return null;
@@ -49,14 +50,7 @@ export const useDex = (client, account) => {
       console.log('🔧 Original address:', account.address);
       console.log('🔧 Normalized address:', normalizedAddress);
       
-      const result = await client.execute(
-        normalizedAddress,
-        contractAddress,
-        msg,
-        'auto',
-        undefined,
-        funds
-      );
+      const result = await client.executeMultiple(normalizedAddress, instructions, 'auto');
       
       toast.success('Transaction successful!');
       return result;
@@ -69,13 +63,39 @@ export const useDex = (client, account) => {
     }
   }, [client, account, contractAddress]);
 
+  // Execute contract. CW20 tokens in cw20Allowances ([assetInfo, amount] pairs) are
+  // approved in the same transaction, for the contract to pull them with TransferFrom
+  const executeContract = useCallback(async (msg, funds = [], cw20Allowances = []) => {
+    const allowances = cw20Allowances.map(([assetInfo, amount]) => ({
+      contractAddress: assetInfo.cw20.contract_addr,
+      msg: { increase_allowance: { spender: contractAddress, amount: amount.toString() } }
+    }));
+
+    return await executeInstructions([...allowances, { contractAddress, msg, funds }]);
+  }, [executeInstructions, contractAddress]);
+
+  // Execute a contract hook by sending CW20 tokens to the contract
+  const sendCw20 = useCallback(async (assetInfo, amount, hookMsg) => {
+    return await executeInstructions([{
+      contractAddress: assetInfo.cw20.contract_addr,
+      msg: {
+        send: {
+          contract: contractAddress,
+          amount: amount.toString(),
+          msg: toBinary(hookMsg)
+        }
+      }
+    }]);
+  }, [executeInstructions, contractAddress]);
+
   // Create pool
   const createPool = useCallback(async (tokenA, tokenB, amountA, amountB) => {
     console.log('🏊 Creating pool with tokens:', { tokenA, tokenB, amountA, amountB });
     
-    // Sort tokens to ensure consistent ordering (many DEX contracts require this)
-    const shouldSwap = tokenA.localeCompare(tokenB) > 0;
-    const [sortedTokenA, sortedTokenB] = shouldSwap ? [tokenB, tokenA] : [tokenA, tokenB];
+    // Sort tokens the way the contract keys pools
+    const [assetA, assetB] = [toAssetInfo(tokenA), toAssetInfo(tokenB)];
+    const shouldSwap = shouldSwapAssets(assetA, assetB);
+    const [sortedTokenA, sortedTokenB] = shouldSwap ? [assetB, assetA] : [assetA, assetB];
     const [sortedAmountA, sortedAmountB] = shouldSwap ? [amountB, amountA] : [amountA, amountB];
     
     const msg = {
@@ -89,22 +109,15 @@ export const useDex = (client, account) => {
 
     console.log('📝 Create pool message:', msg);
 
-    // Handle native tokens (ATOM and IBC tokens) - send as funds
-    const funds = [];
-    if (sortedTokenA === 'uatom' || sortedTokenA.startsWith('ibc/')) {
-      funds.push(coin(sortedAmountA.toString(), sortedTokenA));
-    }
-    if (sortedTokenB === 'uatom' || sortedTokenB.startsWith('ibc/')) {
-      funds.push(coin(sortedAmountB.toString(), sortedTokenB));
-    }
-    
-    // IMPORTANT: Sort funds by denomination (CosmWasm requirement)
-    funds.sort((a, b) => a.denom.localeCompare(b.denom));
+    // Bank tokens are sent as funds, CW20 tokens are approved for the contract to pull
+    const assets = [[sortedTokenA, sortedAmountA], [sortedTokenB, sortedAmountB]];
+    const funds = nativeFunds(assets);
+    const cw20Allowances = assets.filter(([assetInfo]) => assetInfo.cw20);
     
     console.log('💰 Funds for transaction (sorted):', funds);
 
     try {
-      return await executeContract(msg, funds);
+      return await executeContract(msg, funds, cw20Allowances);
     } catch (error) {
       // Provide more specific error handling
       if (error.message.includes('decoding bech32 failed')) {
@@ -120,36 +133,29 @@ export const useDex = (client, account) => {
 
   // Add liquidity
   const addLiquidity = useCallback(async (tokenA, tokenB, amountA, amountB, minLiquidity) => {
+    const [assetA, assetB] = [toAssetInfo(tokenA), toAssetInfo(tokenB)];
     const msg = {
       add_liquidity: {
-        token_a: tokenA,
-        token_b: tokenB,
+        token_a: assetA,
+        token_b: assetB,
         amount_a: amountA.toString(),
         amount_b: amountB.toString(),
         min_liquidity: minLiquidity.toString()
       }
     };
 
-    const funds = [];
-    if (tokenA === 'uatom' || tokenA.startsWith('ibc/')) {
-      funds.push(coin(amountA.toString(), tokenA));
-    }
-    if (tokenB === 'uatom' || tokenB.startsWith('ibc/')) {
-      funds.push(coin(amountB.toString(), tokenB));
-    }
-    
-    // Sort funds by denomination (CosmWasm requirement)
-    funds.sort((a, b) => a.denom.localeCompare(b.denom));
+    const assets = [[assetA, amountA], [assetB, amountB]];
+    const cw20Allowances = assets.filter(([assetInfo]) => assetInfo.cw20);
 
-    return await executeContract(msg, funds);
+    return await executeContract(msg, nativeFunds(assets), cw20Allowances);
   }, [executeContract]);
 
   // Remove liquidity
   const removeLiquidity = useCallback(async (tokenA, tokenB, liquidity, minA, minB) => {
     const msg = {
       remove_liquidity: {
-        token_a: tokenA,
-        token_b: tokenB,
+        token_a: toAssetInfo(tokenA),
+        token_b: toAssetInfo(tokenB),
         liquidity: liquidity.toString(),
         min_a: minA.toString(),
         min_b: minB.toString()
@@ -159,43 +165,72 @@ export const useDex = (client, account) => {
     return await executeContract(msg);
   }, [executeContract]);
 
-  // Swap tokens
+  // Swap tokens. CW20 tokens are sent to the contract with its swap hook
   const swap = useCallback(async (tokenIn, tokenOut, amountIn, minAmountOut) => {
+    const [assetIn, assetOut] = [toAssetInfo(tokenIn), toAssetInfo(tokenOut)];
+
+    if (assetIn.cw20) {
+      return await sendCw20(assetIn, amountIn, {
+        swap: {
+          token_out: assetOut,
+          min_amount_out: minAmountOut.toString()
+        }
+      });
+    }
+
     const msg = {
       swap: {
-        token_in: tokenIn,
-        token_out: tokenOut,
+        token_in: assetIn,
+        token_out: assetOut,
         amount_in: amountIn.toString(),
         min_amount_out: minAmountOut.toString()
       }
     };
 
-    const funds = [];
-    if (tokenIn === 'uatom' || tokenIn.startsWith('ibc/')) {
-      funds.push(coin(amountIn.toString(), tokenIn));
+    return await executeContract(msg, nativeFunds([[assetIn, amountIn]]));
+  }, [executeContract, sendCw20]);
+
+  // Swap for an exact output amount, paying at most maxAmountIn
+  const swapExactOut = useCallback(async (tokenIn, tokenOut, amountOut, maxAmountIn) => {
+    const [assetIn, assetOut] = [toAssetInfo(tokenIn), toAssetInfo(tokenOut)];
+
+    // The contract refunds whatever part of maxAmountIn it doesn't use
+    if (assetIn.cw20) {
+      return await sendCw20(assetIn, maxAmountIn, {
+        swap_exact_out: {
+          token_out: assetOut,
+          amount_out: amountOut.toString()
+        }
+      });
     }
-    
-    // Sort funds by denomination (CosmWasm requirement)
-    funds.sort((a, b) => a.denom.localeCompare(b.denom));
 
-    return await executeContract(msg, funds);
-  }, [executeContract]);
+    const msg = {
+      swap_exact_out: {
+        token_in: assetIn,
+        token_out: assetOut,
+        amount_out: amountOut.toString(),
+        max_amount_in: maxAmountIn.toString()
+      }
+    };
+
+    return await executeContract(msg, nativeFunds([[assetIn, maxAmountIn]]));
+  }, [executeContract, sendCw20]);
 
   // Get pool info
   const getPool = useCallback(async (tokenA, tokenB) => {
     // Sort tokens to ensure consistent ordering
-    const shouldSwap = tokenA.localeCompare(tokenB) > 0;
-    const [sortedTokenA, sortedTokenB] = shouldSwap ? [tokenB, tokenA] : [tokenA, tokenB];
+    const [assetA, assetB] = [toAssetInfo(tokenA), toAssetInfo(tokenB)];
+    const [sortedTokenA, sortedTokenB] = shouldSwapAssets(assetA, assetB) ? [assetB, assetA] : [assetA, assetB];
     
     return await queryContract({
       pool: { token_a: sortedTokenA, token_b: sortedTokenB }
     });
   }, [queryContract]);
 
-  // Get all pools
+  // Get all pools. startAfter is the [tokenA, tokenB] pair of the last pool returned
   const getPools = useCallback(async (startAfter = null, limit = 10) => {
     return await queryContract({
-      pools: { start_after: startAfter, limit }
+      pools: { start_after: startAfter && startAfter.map(toAssetInfo), limit }
     });
   }, [queryContract]);
 
@@ -204,8 +239,8 @@ export const useDex = (client, account) => {
     return await queryContract({
       liquidity: {
         user: userAddress,
-        token_a: tokenA,
-        token_b: tokenB
+        token_a: toAssetInfo(tokenA),
+        token_b: toAssetInfo(tokenB)
       }
     });
   }, [queryContract]);
@@ -214,13 +249,24 @@ export const useDex = (client, account) => {
   const simulateSwap = useCallback(async (tokenIn, tokenOut, amountIn) => {
     return await queryContract({
       simulation: {
-        token_in: tokenIn,
-        token_out: tokenOut,
+        token_in: toAssetInfo(tokenIn),
+        token_out: toAssetInfo(tokenOut),
         amount_in: amountIn.toString()
       }
     });
   }, [queryContract]);
 
+  // Simulate swap for an exact output amount
+  const reverseSimulateSwap = useCallback(async (tokenIn, tokenOut, amountOut) => {
+    return await queryContract({
+      reverse_simulation: {
+        token_in: toAssetInfo(tokenIn),
+        token_out: toAssetInfo(tokenOut),
+        amount_out: amountOut.toString()
+      }
+    });
+  }, [queryContract]);
+
   // Get config
   const getConfig = useCallback(async () => {
     return await queryContract({ config: {} });
@@ -232,10 +278,12 @@ export const useDex = (client, account) => {
     addLiquidity,
     removeLiquidity,
     swap,
+    swapExactOut,
     getPool,
     getPools,
     getUserLiquidity,
     simulateSwap,
+    reverseSimulateSwap,
     getConfig,
     queryContract,
     executeContract,
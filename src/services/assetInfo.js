@@ -0,0 +1,60 @@
+// Asset Info - Builds the contract's AssetInfo values and the funds that go with them
+
+import { coin } from '@cosmjs/stargate';
+
+/**
+ * Convert a token to the contract's AssetInfo: { native: { denom } } or
+ * { cw20: { contract_addr } }. Accepts an AssetInfo, a registry token or a
+ * denom / CW20 contract address.
+ */
+export function toAssetInfo(token) {
+  if (token?.native || token?.cw20) {
+    return token;
+  }
+
+  if (typeof token === 'object') {
+    if (token.type === 'cw20' || token.contract_addr) {
+      return { cw20: { contract_addr: token.contract_addr || token.denom } };
+    }
+    return { native: { denom: token.denom } };
+  }
+
+  // CW20 tokens are identified by their contract address, bank tokens by denom
+  if (token.startsWith('cosmos1')) {
+    return { cw20: { contract_addr: token } };
+  }
+  return { native: { denom: token } };
+}
+
+/**
+ * Check if an AssetInfo is a bank token
+ */
+export function isNativeAsset(assetInfo) {
+  return !!assetInfo.native;
+}
+
+/**
+ * The denom or contract address of an AssetInfo, as the contract keys pools with
+ */
+export function assetKey(assetInfo) {
+  return assetInfo.native ? assetInfo.native.denom : assetInfo.cw20.contract_addr;
+}
+
+/**
+ * Check if two assets are out of the order the contract keeps pool tokens in
+ * (byte order of their denoms / contract addresses)
+ */
+export function shouldSwapAssets(assetA, assetB) {
+  return assetKey(assetA) > assetKey(assetB);
+}
+
+/**
+ * Bank coins to attach for the native assets of [assetInfo, amount] pairs,
+ * sorted by denom (CosmWasm requirement)
+ */
+export function nativeFunds(assets) {
+  return assets
+    .filter(([assetInfo]) => isNativeAsset(assetInfo))
+    .map(([assetInfo, amount]) => coin(amount.toString(), assetInfo.native.denom))
+    .sort((a, b) => a.denom.localeCompare(b.denom));
+}
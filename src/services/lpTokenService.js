@@ -1,6 +1,7 @@
 // LP Token Service - Manages liquidity provider tokens and positions
 
 import { getTokenRegistry } from './tokenRegistry';
+import { assetKey } from './assetInfo';
 
 /**
  * Generate a unique LP token identifier for a pool
@@ -56,8 +57,10 @@ export async function fetchUserLiquidityPositions(client, contractAddress, userA
     
     const lpTokens = [];
     
-    // For each pool, check if user has liquidity
+    // For each pool, check if user has liquidity. Pool tokens are AssetInfo values,
+    // registry tokens and LP ids use their denom / contract address
     for (const pool of poolsResponse.pools || []) {
+      const [tokenA, tokenB] = [assetKey(pool.token_a), assetKey(pool.token_b)];
       try {
         const liquidityResponse = await client.queryContractSmart(contractAddress, {
           liquidity: {
@@ -67,13 +70,13 @@ export async function fetchUserLiquidityPositions(client, contractAddress, userA
           }
         });
         
-        console.log(`🔍 Liquidity in ${pool.token_a}/${pool.token_b}:`, liquidityResponse);
+        console.log(`🔍 Liquidity in ${tokenA}/${tokenB}:`, liquidityResponse);
         
         // If user has liquidity in this pool, create LP token
         if (liquidityResponse.liquidity && liquidityResponse.liquidity !== '0') {
           const lpToken = generateLPTokenInfo(
-            pool.token_a, 
-            pool.token_b, 
+            tokenA, 
+            tokenB, 
             liquidityResponse.liquidity
           );
           
@@ -90,7 +93,7 @@ export async function fetchUserLiquidityPositions(client, contractAddress, userA
           console.log('✅ Added LP token:', lpToken.symbol);
         }
       } catch (error) {
-        console.log(`⚠️ No liquidity in pool ${pool.token_a}/${pool.token_b}:`, error.message);
+        console.log(`⚠️ No liquidity in pool ${tokenA}/${tokenB}:`, error.message);
       }
     }
     